@@ -0,0 +1,123 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::error::Error as StdError;
+
+use crate::registry::{self, Descriptor, Platform};
+use crate::verify;
+
+pub const ANNOTATION_PREDICATE_TYPE: &str = "in-toto.io/predicate-type";
+/// BuildKit이 인덱스에 넣는 attestation 매니페스트 표시
+pub const ANNOTATION_REFERENCE_TYPE: &str = "vnd.docker.reference.type";
+pub const ANNOTATION_REFERENCE_DIGEST: &str = "vnd.docker.reference.digest";
+pub const REFERENCE_TYPE_ATTESTATION: &str = "attestation-manifest";
+
+/// 이미지에 붙어 있는 JSON 문서 하나 (SBOM, provenance 등)
+#[derive(Debug, Clone, Serialize)]
+pub struct AttachedDocument {
+    /// 문서를 찾은 곳. 예: "referrer sha256:…", "cosign .att", "buildkit attestation sha256:…"
+    pub source: String,
+    pub media_type: String,
+    /// in-toto statement에서 꺼낸 경우의 predicateType
+    pub predicate_type: Option<String>,
+    /// in-toto statement라면 predicate, 아니면 문서 전체
+    pub document: Value,
+}
+
+/// blob 내용을 풀어 문서를 꺼냅니다. DSSE envelope → in-toto statement → predicate 순으로 벗김
+pub fn unwrap_document(bytes: &[u8]) -> Option<(Option<String>, Value)> {
+    let mut value: Value = serde_json::from_slice(bytes).ok()?;
+    if let (Some(payload), Some(_)) = (value.get("payload").and_then(|p| p.as_str()), value.get("payloadType")) {
+        let decoded = openssl::base64::decode_block(payload).ok()?;
+        value = serde_json::from_slice(&decoded).ok()?;
+    }
+    let is_statement = value.get("_type").and_then(|t| t.as_str()).is_some_and(|t| t.starts_with("https://in-toto.io/Statement"));
+    if is_statement {
+        let predicate_type = value.get("predicateType").and_then(|t| t.as_str()).map(|t| t.to_string());
+        let predicate = value.get_mut("predicate").map(Value::take).unwrap_or(Value::Null);
+        return Some((predicate_type, predicate));
+    }
+    Some((None, value))
+}
+
+/// 매니페스트의 JSON 레이어를 모두 읽어 문서로 만듭니다.
+async fn read_manifest_documents(image: &str, digest: &str, source: &str) -> Result<Vec<AttachedDocument>, Box<dyn StdError>> {
+    let manifest = registry::fetch_manifest_raw(image, digest).await?.parse()?;
+    let mut documents = Vec::new();
+    for layer in manifest.layers.iter().filter(|l| l.media_type.contains("json")) {
+        // 서명 payload는 문서가 아님
+        if layer.media_type == verify::MEDIA_TYPE_SIMPLE_SIGNING {
+            continue;
+        }
+        let bytes = registry::fetch_blob(image, layer, Vec::new()).await?;
+        if let Some((predicate_type, document)) = unwrap_document(&bytes) {
+            documents.push(AttachedDocument {
+                source: source.to_string(),
+                media_type: layer.media_type.clone(),
+                predicate_type: predicate_type.or_else(|| layer.annotations.get(ANNOTATION_PREDICATE_TYPE).cloned()),
+                document,
+            });
+        }
+    }
+    Ok(documents)
+}
+
+/// 문서를 담은 매니페스트 후보: (다이제스트, 출처 설명)
+async fn candidate_manifests(image: &str, digests: &[String], buildkit: &[Descriptor]) -> Result<Vec<(String, String)>, Box<dyn StdError>> {
+    let mut candidates: Vec<(String, String)> = buildkit
+        .iter()
+        .map(|d| (d.digest.clone(), format!("buildkit attestation {}", short_digest(&d.digest))))
+        .collect();
+    for digest in digests {
+        for referrer in registry::fetch_referrers(image, digest).await? {
+            if referrer.artifact_type.as_deref() != Some(verify::ARTIFACT_TYPE_COSIGN_SIGNATURE) {
+                candidates.push((referrer.digest.clone(), format!("referrer {}", short_digest(&referrer.digest))));
+            }
+        }
+        let att_tag = format!("{}.att", registry::referrers_tag(digest));
+        match registry::fetch_manifest_digest(image, &att_tag).await {
+            Ok(att_digest) => candidates.push((att_digest, "cosign .att".to_string())),
+            Err(e) if registry::is_not_found(e.as_ref()) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    let mut seen = HashSet::new();
+    candidates.retain(|(digest, _)| seen.insert(digest.clone()));
+    Ok(candidates)
+}
+
+pub fn short_digest(digest: &str) -> String {
+    digest.chars().take(19).collect()
+}
+
+/// 인덱스 안에서 `digest` 이미지를 가리키는 BuildKit attestation 매니페스트
+pub fn buildkit_attestations<'a>(index: &'a registry::Manifest, digest: &str) -> Vec<&'a Descriptor> {
+    index
+        .manifests
+        .iter()
+        .filter(|m| {
+            m.annotations.get(ANNOTATION_REFERENCE_TYPE).map(|t| t.as_str()) == Some(REFERENCE_TYPE_ATTESTATION)
+                && m.annotations.get(ANNOTATION_REFERENCE_DIGEST).map(|d| d.as_str()) == Some(digest)
+        })
+        .collect()
+}
+
+/// 태그에 붙은 모든 문서를 모읍니다. 인덱스와 플랫폼 매니페스트 양쪽의 referrer를 확인합니다.
+pub async fn fetch_documents(image: &str, reference: &str, platform: &Platform) -> Result<Vec<AttachedDocument>, Box<dyn StdError>> {
+    let top = registry::fetch_manifest_raw(image, reference).await?;
+    let top_manifest = top.parse()?;
+    let mut digests = vec![top.digest.clone()];
+    let mut buildkit = Vec::new();
+    if top_manifest.is_index() {
+        let (child, _) = registry::fetch_image_manifest(image, &top.digest, platform).await?;
+        buildkit = buildkit_attestations(&top_manifest, &child.digest).into_iter().cloned().collect();
+        digests.push(child.digest);
+    }
+
+    let mut documents = Vec::new();
+    let candidates = candidate_manifests(image, &digests, &buildkit).await?;
+    for (digest, source) in candidates {
+        documents.extend(read_manifest_documents(image, &digest, &source).await?);
+    }
+    Ok(documents)
+}
@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error as StdError;
+use std::path::Path;
+
+use crate::export::{ExportFormat, ExportSummary, Exporter};
+use crate::import::{self, ImportOptions, ImportSummary, ImportTarget, Importer, Layout};
+use crate::registry;
+use crate::time;
+
+/// 번들 안에 함께 들어가는 참조 목록 파일
+pub const BUNDLE_INDEX: &str = "bundle.json";
+
+#[derive(Debug, Default, Clone)]
+pub struct BundleSelection {
+    /// 1뎁스 그룹 이름 (group_images_by_depth의 키)
+    pub group: Option<String>,
+    /// 태그 와일드카드 (예: "v1.*")
+    pub tag_filter: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleIndex {
+    pub source_registry: String,
+    pub created: String,
+    #[serde(default)]
+    pub group: Option<String>,
+    #[serde(default)]
+    pub tag_filter: Option<String>,
+    pub images: Vec<BundleImage>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BundleImage {
+    pub reference: String,
+    pub digest: String,
+}
+
+#[derive(Debug, Default)]
+pub struct BundleImportSummary {
+    pub added: Vec<String>,
+    pub present: Vec<String>,
+    pub transfer: ImportSummary,
+}
+
+/// 선택 조건에 맞는 모든 "repo:tag" 참조를 registry에서 수집합니다.
+pub async fn select_references(selection: &BundleSelection) -> Result<Vec<String>, Box<dyn StdError>> {
+    let images = registry::fetch_images().await.map_err(|e| e as Box<dyn StdError>)?;
+    let grouped = registry::group_images_by_depth(images);
+
+    let mut groups: Vec<_> = grouped
+        .into_iter()
+        .filter(|(depth1, _)| selection.group.as_ref().is_none_or(|g| g == depth1))
+        .collect();
+    groups.sort();
+
+    let mut references = Vec::new();
+    for (depth1, mut depth2_list) in groups {
+        depth2_list.sort();
+        for depth2 in depth2_list {
+            let repository = if depth2.is_empty() { depth1.clone() } else { format!("{}/{}", depth1, depth2) };
+            for tag in registry::fetch_tags(&repository).await.map_err(|e| e as Box<dyn StdError>)? {
+                if selection.tag_filter.as_ref().is_none_or(|f| registry::matches_pattern(f, &tag)) {
+                    references.push(format!("{}:{}", repository, tag));
+                }
+            }
+        }
+    }
+    Ok(references)
+}
+
+/// 선택한 이미지를 중복 없는 하나의 OCI 레이아웃(디렉터리 또는 tar)으로 묶습니다.
+pub async fn export_bundle(
+    selection: &BundleSelection,
+    archive: bool,
+    output: &Path,
+) -> Result<(BundleIndex, ExportSummary), Box<dyn StdError>> {
+    let references = select_references(selection).await?;
+    if references.is_empty() {
+        return Err("no images match the bundle selection".into());
+    }
+
+    let format = if archive { ExportFormat::OciArchive } else { ExportFormat::OciLayout };
+    let mut exporter = Exporter::create(format, output)?;
+    let host = registry::registry_host();
+    let mut bundle = BundleIndex {
+        source_registry: host.clone(),
+        created: time::now_rfc3339(),
+        group: selection.group.clone(),
+        tag_filter: selection.tag_filter.clone(),
+        images: Vec::new(),
+    };
+
+    for reference in references {
+        let (image, tag) = registry::parse_reference(&reference)?;
+        let raw = registry::fetch_manifest_raw(&image, &tag).await?;
+        exporter.put_manifest(&image, &raw).await?;
+        exporter.add_index_entry(&raw, &format!("{}/{}:{}", host, image, tag), Some(&tag));
+        exporter.summary.images += 1;
+        log::info!("bundled {}", reference);
+        bundle.images.push(BundleImage { reference, digest: raw.digest });
+    }
+
+    exporter.put_file(BUNDLE_INDEX, &serde_json::to_vec_pretty(&bundle)?)?;
+    let summary = exporter.finish()?;
+    Ok((bundle, summary))
+}
+
+/// bundle.json의 이미지 목록을 index.json과 대조해 올릴 대상을 만듭니다.
+/// 한쪽에만 있는 이미지나 다이제스트가 다른 이미지가 있으면 번들이 손상된 것으로 봄
+pub fn bundle_targets(bundle: &BundleIndex, layout: &Layout) -> Result<Vec<ImportTarget>, Box<dyn StdError>> {
+    let mut targets = Vec::new();
+    for image in &bundle.images {
+        let (repository, tag) = registry::parse_reference(&image.reference)?;
+        let root = layout
+            .images
+            .iter()
+            .find(|i| i.root.digest == image.digest)
+            .ok_or_else(|| format!("{} lists {} ({}) but index.json has no such manifest", BUNDLE_INDEX, image.reference, image.digest))?;
+        targets.push((repository, tag, root.root.clone()));
+    }
+    if let Some(extra) = layout.images.iter().find(|i| !bundle.images.iter().any(|b| b.digest == i.root.digest)) {
+        return Err(format!("index.json has manifest {} that {} does not list", extra.root.digest, BUNDLE_INDEX).into());
+    }
+    Ok(targets)
+}
+
+/// 번들을 대상 registry에 올립니다. 이미 있는 blob/태그는 건너뛰므로 중단된 경우 다시 실행하면 이어서 진행합니다.
+pub async fn import_bundle(path: &Path) -> Result<BundleImportSummary, Box<dyn StdError>> {
+    let layout = Layout::open(path)?;
+    let targets = if layout.source.exists(BUNDLE_INDEX) {
+        let bundle: BundleIndex = serde_json::from_slice(&layout.source.read(BUNDLE_INDEX)?).map_err(|e| format!("invalid {}: {}", BUNDLE_INDEX, e))?;
+        bundle_targets(&bundle, &layout)?
+    } else {
+        log::warn!("{} has no {}, importing as a plain OCI layout", path.display(), BUNDLE_INDEX);
+        import::resolve_targets(&layout, &ImportOptions::default())?
+    };
+
+    let mut importer = Importer::new(&layout);
+    let mut summary = BundleImportSummary::default();
+    for (repository, tag, root) in targets {
+        let present_before = importer.summary.images_present;
+        importer.push_root(&repository, &tag, &root).await?;
+
+        let reference = format!("{}:{}", repository, tag);
+        if importer.summary.images_present > present_before {
+            summary.present.push(reference);
+        } else {
+            summary.added.push(reference);
+        }
+    }
+    summary.transfer = importer.summary;
+    Ok(summary)
+}
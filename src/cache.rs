@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::UNIX_EPOCH;
+
+// --no-cache로 끌 수 있음
+static ENABLED: AtomicBool = AtomicBool::new(true);
+// --offline이면 registry에 요청하지 않고 캐시만 읽음
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// --offline에서 캐시에 없는 데이터를 요청했을 때의 오류
+#[derive(Debug)]
+pub struct OfflineMiss(pub String);
+
+impl std::fmt::Display for OfflineMiss {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is not available in --offline mode (not in the local cache)", self.0)
+    }
+}
+
+impl std::error::Error for OfflineMiss {}
+
+/// 캐시 위치: $XDG_CACHE_HOME/repo-tree (없으면 ~/.cache/repo-tree)
+pub fn cache_root() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+    Some(base.join("repo-tree"))
+}
+
+/// registry 주소("http://host:port/v2/")별 디렉터리 이름. 파일 이름에 못 쓰는 문자는 '_'로
+pub fn registry_key(registry_url: &str) -> String {
+    let without_scheme = registry_url.split_once("://").map(|(_, rest)| rest).unwrap_or(registry_url);
+    without_scheme
+        .trim_end_matches('/')
+        .trim_end_matches("/v2")
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-') { c } else { '_' })
+        .collect()
+}
+
+fn registry_dir(registry_url: &str) -> Option<PathBuf> {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return None;
+    }
+    Some(cache_root()?.join(registry_key(registry_url)))
+}
+
+/// 카탈로그와 태그 목록처럼 바뀔 수 있는 응답. 다음 요청 때 etag로 재검증
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub etag: Option<String>,
+    pub body: Value,
+}
+
+/// 태그가 마지막으로 가리킨 매니페스트
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagRef {
+    pub digest: String,
+    pub etag: Option<String>,
+}
+
+/// 서버가 준 저장소/태그 이름을 경로로 쓰기 전에 캐시 밖을 가리키지 않는지 확인
+pub fn is_safe_name(name: &str) -> bool {
+    !name.is_empty() && name.split('/').all(|part| !part.is_empty() && part != "." && part != ".." && !part.contains('\\'))
+}
+
+fn catalog_path(registry_url: &str) -> Option<PathBuf> {
+    Some(registry_dir(registry_url)?.join("catalog.json"))
+}
+
+fn tags_path(registry_url: &str, repository: &str) -> Option<PathBuf> {
+    if !is_safe_name(repository) {
+        return None;
+    }
+    Some(registry_dir(registry_url)?.join("tags").join(format!("{}.json", repository)))
+}
+
+fn ref_path(registry_url: &str, repository: &str, tag: &str) -> Option<PathBuf> {
+    if !is_safe_name(repository) || !is_safe_name(tag) || tag.contains('/') {
+        return None;
+    }
+    Some(registry_dir(registry_url)?.join("refs").join(repository).join(format!("{}.json", tag)))
+}
+
+/// 다이제스트로 찾는 매니페스트는 내용이 바뀌지 않으므로 저장소와 관계없이 한 곳에 둠
+fn manifest_path(registry_url: &str, digest: &str) -> Option<PathBuf> {
+    let (algorithm, hex) = digest.split_once(':')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some(registry_dir(registry_url)?.join("manifests").join(algorithm).join(hex))
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: Option<PathBuf>) -> Option<T> {
+    serde_json::from_slice(&fs::read(path?).ok()?).ok()
+}
+
+/// 임시 파일에 쓴 뒤 rename해서 동시에 읽는 쪽이 반쯤 쓰인 파일을 보지 않도록 함.
+/// 캐시는 부가 기능이라 쓰기 실패는 무시
+pub fn write_atomic(path: Option<PathBuf>, bytes: &[u8]) {
+    let Some(path) = path else { return };
+    let Some(parent) = path.parent() else { return };
+    if fs::create_dir_all(parent).is_err() {
+        return;
+    }
+    let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+    if fs::write(&tmp, bytes).is_ok() && fs::rename(&tmp, &path).is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+}
+
+fn write_json<T: Serialize>(path: Option<PathBuf>, value: &T) {
+    if let Ok(bytes) = serde_json::to_vec(value) {
+        write_atomic(path, &bytes);
+    }
+}
+
+pub fn read_catalog(registry_url: &str) -> Option<Entry> {
+    read_json(catalog_path(registry_url))
+}
+
+pub fn write_catalog(registry_url: &str, entry: &Entry) {
+    write_json(catalog_path(registry_url), entry);
+}
+
+/// 카탈로그를 마지막으로 registry에서 확인한 시각(유닉스 초). 오프라인 트리의 기준 시점
+pub fn snapshot_time(registry_url: &str) -> Option<i64> {
+    let modified = fs::metadata(catalog_path(registry_url)?).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64)
+}
+
+pub fn read_tags(registry_url: &str, repository: &str) -> Option<Entry> {
+    read_json(tags_path(registry_url, repository))
+}
+
+pub fn write_tags(registry_url: &str, repository: &str, entry: &Entry) {
+    write_json(tags_path(registry_url, repository), entry);
+}
+
+pub fn read_ref(registry_url: &str, repository: &str, tag: &str) -> Option<TagRef> {
+    read_json(ref_path(registry_url, repository, tag))
+}
+
+pub fn write_ref(registry_url: &str, repository: &str, tag: &str, tag_ref: &TagRef) {
+    write_json(ref_path(registry_url, repository, tag), tag_ref);
+}
+
+/// 캐시된 매니페스트 (media type, 본문). 읽을 때 다이제스트를 다시 확인
+pub fn read_manifest(registry_url: &str, digest: &str) -> Option<(String, Vec<u8>)> {
+    let path = manifest_path(registry_url, digest)?;
+    let bytes = fs::read(&path).ok()?;
+    if crate::digest::sha256_digest(&bytes) != digest {
+        return None;
+    }
+    let media_type = fs::read_to_string(path.with_extension("type")).ok()?;
+    Some((media_type, bytes))
+}
+
+pub fn write_manifest(registry_url: &str, digest: &str, media_type: &str, bytes: &[u8]) {
+    let Some(path) = manifest_path(registry_url, digest) else { return };
+    // 본문이 있는데 type이 없는 상태가 없도록 type을 먼저 씀
+    write_atomic(Some(path.with_extension("type")), media_type.as_bytes());
+    write_atomic(Some(path), bytes);
+}
+
+fn dir_size(path: &Path) -> io::Result<u64> {
+    let mut total = 0;
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        total += if metadata.is_dir() { dir_size(&entry.path())? } else { metadata.len() };
+    }
+    Ok(total)
+}
+
+/// registry 디렉터리별 크기 (이름순)
+pub fn sizes(root: &Path) -> io::Result<Vec<(String, u64)>> {
+    let mut sizes = Vec::new();
+    let entries = match fs::read_dir(root) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(sizes),
+        Err(e) => return Err(e),
+    };
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            sizes.push((entry.file_name().to_string_lossy().into_owned(), dir_size(&entry.path())?));
+        }
+    }
+    sizes.sort();
+    Ok(sizes)
+}
+
+/// `registry`가 있으면 그 registry만, 없으면 캐시 전체를 지우고 지운 바이트 수를 반환합니다.
+pub fn purge(root: &Path, registry: Option<&str>) -> io::Result<u64> {
+    let target = match registry {
+        Some(url) => root.join(registry_key(url)),
+        None => root.to_path_buf(),
+    };
+    let size = match dir_size(&target) {
+        Ok(size) => size,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    fs::remove_dir_all(&target)?;
+    Ok(size)
+}
@@ -0,0 +1,257 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error as StdError;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::registry::{Auth, ClientSettings, TreeFilter};
+
+/// --registry도 프로필도 없을 때 쓰는 registry
+pub const DEFAULT_REGISTRY_URL: &str = "http://igloo.airgap.registry";
+
+/// registry 하나에 대한 접속 설정
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// [profiles.<name>]의 이름 (파일에는 없음)
+    #[serde(skip)]
+    pub name: String,
+    pub url: Option<String>,
+    pub username: Option<String>,
+    /// 비밀번호를 담은 환경 변수 이름
+    pub password_env: Option<String>,
+    /// 비밀번호(또는 토큰)를 담은 파일
+    pub password_file: Option<PathBuf>,
+    /// Bearer 토큰을 담은 환경 변수 이름
+    pub token_env: Option<String>,
+    /// 추가로 신뢰할 CA 인증서 (PEM)
+    pub ca_file: Option<PathBuf>,
+    /// 인증서 검증을 끔 (테스트용 자체 서명 registry)
+    #[serde(default)]
+    pub insecure: bool,
+    pub connect_timeout: Option<u64>,
+    pub timeout: Option<u64>,
+    /// 태그 조회 등 동시에 보내는 요청 수
+    pub concurrency: Option<usize>,
+    /// 트리에 보일 저장소 와일드카드 (registry::matches_pattern)
+    pub repository_filter: Option<String>,
+    pub tag_filter: Option<String>,
+}
+
+/// config.toml의 형식
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    default_profile: Option<String>,
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+/// 설정 파일 전체
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub path: Option<PathBuf>,
+    pub default_profile: Option<String>,
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// 설정 파일 위치: $XDG_CONFIG_HOME/repo-tree/config.toml (없으면 ~/.config/repo-tree/config.toml)
+pub fn default_config_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("repo-tree").join("config.toml"))
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
+impl Config {
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let file: ConfigFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        let mut config = Config { path: None, default_profile: file.default_profile, profiles: file.profiles };
+        for (name, profile) in config.profiles.iter_mut() {
+            profile.name = name.clone();
+            profile.password_file = profile.password_file.as_deref().map(expand_home);
+            profile.ca_file = profile.ca_file.as_deref().map(expand_home);
+            profile.concurrency = profile.concurrency.map(|n| n.max(1));
+        }
+        if let Some(name) = &config.default_profile {
+            if !config.profiles.contains_key(name) {
+                return Err(format!("default_profile '{}' is not defined", name));
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<Config, Box<dyn StdError>> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path.display(), e))?;
+        let mut config = Config::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        config.path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// 기본 위치의 설정 파일. 파일이 없으면 빈 설정
+    pub fn load_default() -> Result<Config, Box<dyn StdError>> {
+        match default_config_path() {
+            Some(path) if path.exists() => Config::load(&path),
+            _ => Ok(Config::default()),
+        }
+    }
+
+    /// 이름으로 고른 프로필, 없으면 default_profile. 둘 다 없으면 None
+    pub fn profile(&self, name: Option<&str>) -> Result<Option<&Profile>, String> {
+        match name.or(self.default_profile.as_deref()) {
+            Some(name) => self.profiles.get(name).map(Some).ok_or_else(|| {
+                let known: Vec<&str> = self.profiles.keys().map(|k| k.as_str()).collect();
+                match &self.path {
+                    Some(path) => format!("profile '{}' is not defined in {} (known: {})", name, path.display(), known.join(", ")),
+                    None => format!("profile '{}' is not defined: no config file at {}", name, default_config_path().map(|p| p.display().to_string()).unwrap_or_default()),
+                }
+            }),
+            None => Ok(None),
+        }
+    }
+}
+
+impl Profile {
+    /// 비밀번호 참조를 실제 값으로
+    pub fn password(&self) -> Result<Option<String>, Box<dyn StdError>> {
+        if let Some(var) = &self.password_env {
+            return Ok(Some(std::env::var(var).map_err(|_| format!("profile '{}': environment variable {} is not set", self.name, var))?));
+        }
+        if let Some(path) = &self.password_file {
+            let text = std::fs::read_to_string(path).map_err(|e| format!("profile '{}': could not read {}: {}", self.name, path.display(), e))?;
+            return Ok(Some(text.trim_end_matches(['\r', '\n']).to_string()));
+        }
+        Ok(None)
+    }
+
+    pub fn token(&self) -> Result<Option<String>, Box<dyn StdError>> {
+        match &self.token_env {
+            Some(var) => Ok(Some(std::env::var(var).map_err(|_| format!("profile '{}': environment variable {} is not set", self.name, var))?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 인증, CA, 타임아웃을 registry 클라이언트 설정으로
+    pub fn client_settings(&self) -> Result<ClientSettings, Box<dyn StdError>> {
+        let auth = match (self.token()?, &self.username) {
+            (Some(token), _) => Some(Auth::Bearer(token)),
+            (None, Some(username)) => Some(Auth::Basic { username: username.clone(), password: self.password()? }),
+            (None, None) => None,
+        };
+        let ca_pem = match &self.ca_file {
+            Some(path) => Some(std::fs::read(path).map_err(|e| format!("profile '{}': could not read {}: {}", self.name, path.display(), e))?),
+            None => None,
+        };
+        Ok(ClientSettings {
+            auth,
+            auth_url: Some(self.url_or_default()),
+            ca_pem,
+            insecure: self.insecure,
+            connect_timeout: self.connect_timeout.map(Duration::from_secs),
+            read_timeout: self.timeout.map(Duration::from_secs),
+            concurrency: self.concurrency,
+        })
+    }
+
+    pub fn tree_filter(&self) -> TreeFilter {
+        TreeFilter { repository: self.repository_filter.clone(), tag: self.tag_filter.clone() }
+    }
+
+    pub fn url_or_default(&self) -> String {
+        self.url.clone().unwrap_or_else(|| DEFAULT_REGISTRY_URL.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_profiles() {
+        let config = Config::parse(
+            r#"
+default_profile = "prod"
+
+[profiles.prod]
+url = "https://registry.example.com"
+username = "ci"
+password_env = "REGISTRY_PASSWORD"
+insecure = true
+timeout = 30
+concurrency = 0
+repository_filter = "team/*"
+
+[profiles.lab]
+url = "http://lab:5000" # 주석
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.default_profile.as_deref(), Some("prod"));
+        let prod = config.profile(None).unwrap().unwrap();
+        assert_eq!(prod.name, "prod");
+        assert_eq!(prod.url.as_deref(), Some("https://registry.example.com"));
+        assert_eq!(prod.username.as_deref(), Some("ci"));
+        assert!(prod.insecure);
+        assert_eq!(prod.timeout, Some(30));
+        assert_eq!(prod.concurrency, Some(1));
+        assert_eq!(prod.repository_filter.as_deref(), Some("team/*"));
+        let lab = config.profile(Some("lab")).unwrap().unwrap();
+        assert!(!lab.insecure);
+        assert!(config.profile(Some("missing")).is_err());
+    }
+
+    #[test]
+    fn empty_file_has_no_profiles() {
+        let config = Config::parse("").unwrap();
+        assert!(config.profiles.is_empty());
+        assert_eq!(config.profile(None).unwrap(), None);
+    }
+
+    #[test]
+    fn inline_tables_are_accepted() {
+        let config = Config::parse(r#"profiles = { a = { url = "http://a" } }"#).unwrap();
+        assert_eq!(config.profiles["a"].url.as_deref(), Some("http://a"));
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(Config::parse("[profiles.a]\nurl = \"http://a\"\npasword_env = \"X\"\n").is_err());
+        assert!(Config::parse("defaults = 1\n").is_err());
+    }
+
+    #[test]
+    fn rejects_redefined_tables() {
+        assert!(Config::parse("[profiles.a]\nurl = \"http://a\"\n[profiles.a]\nurl = \"http://b\"\n").is_err());
+        assert!(Config::parse("[profiles.a]\nurl = \"http://a\"\nurl = \"http://b\"\n").is_err());
+    }
+
+    #[test]
+    fn rejects_wrong_types() {
+        assert!(Config::parse("[profiles.a]\ninsecure = \"yes\"\n").is_err());
+        assert!(Config::parse("[profiles.a]\ntimeout = 1.5\n").is_err());
+        assert!(Config::parse("[profiles.a]\nconcurrency = -1\n").is_err());
+    }
+
+    #[test]
+    fn rejects_undefined_default_profile() {
+        assert!(Config::parse("default_profile = \"x\"\n[profiles.a]\n").is_err());
+    }
+
+    #[test]
+    fn expands_home_in_paths() {
+        let home = std::env::var_os("HOME").map(PathBuf::from);
+        let config = Config::parse("[profiles.a]\nca_file = \"~/ca.pem\"\npassword_file = \"/etc/pw\"\n").unwrap();
+        let profile = &config.profiles["a"];
+        if let Some(home) = home {
+            assert_eq!(profile.ca_file.as_deref(), Some(home.join("ca.pem").as_path()));
+        }
+        assert_eq!(profile.password_file.as_deref(), Some(Path::new("/etc/pw")));
+    }
+}
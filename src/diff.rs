@@ -0,0 +1,281 @@
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error as StdError;
+use tabled::Tabled;
+use tui::style::Color;
+
+use crate::picker::RegistryChoice;
+use crate::registry::{self, Endpoint};
+use crate::ui::{self, App, TreeItems};
+
+/// 저장소 → (태그 → 매니페스트 다이제스트)
+pub type RegistryState = BTreeMap<String, BTreeMap<String, String>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffStatus {
+    OnlyInA,
+    OnlyInB,
+    Differs,
+    /// 한쪽에서 다이제스트(또는 태그 목록)를 읽지 못해 비교하지 못함
+    Failed,
+}
+
+impl DiffStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            DiffStatus::OnlyInA => "only in A",
+            DiffStatus::OnlyInB => "only in B",
+            DiffStatus::Differs => "differs",
+            DiffStatus::Failed => "failed",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            DiffStatus::OnlyInA => Color::Red,
+            DiffStatus::OnlyInB => Color::Cyan,
+            DiffStatus::Differs => Color::Yellow,
+            DiffStatus::Failed => Color::Magenta,
+        }
+    }
+}
+
+/// 비교하는 두 registry 중 어느 쪽인지
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    A,
+    B,
+}
+
+impl std::fmt::Display for DiffStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+fn display_digest(digest: &Option<String>) -> String {
+    digest.as_deref().map(|d| d.chars().take(19).collect()).unwrap_or_else(|| "-".to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Tabled)]
+pub struct TagDiff {
+    #[tabled(rename = "Repository")]
+    pub repository: String,
+    #[tabled(rename = "Tag")]
+    pub tag: String,
+    #[tabled(rename = "Status")]
+    pub status: DiffStatus,
+    #[tabled(rename = "Digest A", display_with = "display_digest")]
+    pub digest_a: Option<String>,
+    #[tabled(rename = "Digest B", display_with = "display_digest")]
+    pub digest_b: Option<String>,
+    /// 태그 상세를 읽을 registry (B에만 있으면 B, 실패 행은 실패한 쪽)
+    #[tabled(skip)]
+    pub source: Side,
+    #[tabled(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 태그 목록이나 다이제스트를 읽지 못한 항목. tag가 None이면 저장소의 태그 목록을 못 읽음
+#[derive(Debug, Clone)]
+pub struct Failure {
+    pub repository: String,
+    pub tag: Option<String>,
+    pub error: String,
+}
+
+/// registry 하나에서 읽은 상태와 읽지 못한 항목
+#[derive(Debug, Clone, Default)]
+pub struct Collected {
+    pub state: RegistryState,
+    pub failures: Vec<Failure>,
+}
+
+impl Collected {
+    fn failed(&self, repository: &str, tag: &str) -> Option<&Failure> {
+        self.failures.iter().find(|f| f.repository == repository && f.tag.as_deref().is_none_or(|t| t == tag))
+    }
+}
+
+/// 현재 registry의 모든 저장소/태그 다이제스트를 수집합니다.
+/// 카탈로그를 못 읽으면 오류, 태그 목록이나 다이제스트를 못 읽은 항목은 failures에 모음
+pub async fn collect_state() -> Result<Collected, Box<dyn StdError>> {
+    let mut collected = Collected::default();
+    for repository in registry::fetch_images().await.map_err(|e| e as Box<dyn StdError>)? {
+        let tags = match registry::fetch_tags(&repository).await {
+            Ok(tags) => tags,
+            Err(e) => {
+                collected.failures.push(Failure { repository, tag: None, error: e.to_string() });
+                continue;
+            }
+        };
+        let digests: Vec<(String, Result<String, String>)> = stream::iter(tags)
+            .map(|tag| {
+                let repository = repository.clone();
+                async move {
+                    let digest = registry::fetch_manifest_digest(&repository, &tag).await.map_err(|e| e.to_string());
+                    (tag, digest)
+                }
+            })
+            .buffer_unordered(registry::concurrency())
+            .collect()
+            .await;
+
+        let mut tag_digests = BTreeMap::new();
+        for (tag, digest) in digests {
+            match digest {
+                Ok(digest) => {
+                    tag_digests.insert(tag, digest);
+                }
+                Err(error) => collected.failures.push(Failure { repository: repository.clone(), tag: Some(tag), error }),
+            }
+        }
+        collected.state.insert(repository, tag_digests);
+    }
+    Ok(collected)
+}
+
+/// 두 registry를 각자의 주소와 클라이언트 설정(인증 정보, CA)으로 동시에 읽습니다.
+pub async fn collect_states(a: &RegistryChoice, b: &RegistryChoice) -> Result<(Collected, Collected), Box<dyn StdError>> {
+    let endpoint = |choice: &RegistryChoice, side: &str| -> Result<Endpoint, Box<dyn StdError>> {
+        let settings = choice.settings.clone().map_err(|e| format!("registry {} ({}): {}", side, choice.name, e))?;
+        Endpoint::new(&choice.url, &settings).map_err(|e| format!("registry {} ({}): {}", side, choice.name, e).into())
+    };
+    let (endpoint_a, endpoint_b) = (endpoint(a, "A")?, endpoint(b, "B")?);
+    let (state_a, state_b) = futures::join!(endpoint_a.scope(collect_state()), endpoint_b.scope(collect_state()));
+    let state_a = state_a.map_err(|e| format!("registry A ({}): {}", a.url, e))?;
+    let state_b = state_b.map_err(|e| format!("registry B ({}): {}", b.url, e))?;
+    Ok((state_a, state_b))
+}
+
+pub fn compare(a: &Collected, b: &Collected) -> Vec<TagDiff> {
+    let repositories: BTreeSet<&String> = a.state.keys().chain(b.state.keys()).collect();
+    let empty = BTreeMap::new();
+    let mut diffs = Vec::new();
+
+    for repository in repositories {
+        let tags_a = a.state.get(repository).unwrap_or(&empty);
+        let tags_b = b.state.get(repository).unwrap_or(&empty);
+        let tags: BTreeSet<&String> = tags_a.keys().chain(tags_b.keys()).collect();
+        for tag in tags {
+            // 한쪽에서 읽지 못한 태그는 아래에서 실패 행으로 보고함
+            if a.failed(repository, tag).is_some() || b.failed(repository, tag).is_some() {
+                continue;
+            }
+            let digest_a = tags_a.get(tag).cloned();
+            let digest_b = tags_b.get(tag).cloned();
+            let (status, source) = match (&digest_a, &digest_b) {
+                (Some(_), None) => (DiffStatus::OnlyInA, Side::A),
+                (None, Some(_)) => (DiffStatus::OnlyInB, Side::B),
+                (Some(x), Some(y)) if x != y => (DiffStatus::Differs, Side::A),
+                _ => continue,
+            };
+            diffs.push(TagDiff { repository: repository.clone(), tag: tag.clone(), status, digest_a, digest_b, source, error: None });
+        }
+    }
+    for (side, collected, other) in [(Side::A, a, b), (Side::B, b, a)] {
+        for failure in &collected.failures {
+            let other_digest = failure.tag.as_ref().and_then(|t| other.state.get(&failure.repository)?.get(t).cloned());
+            let (digest_a, digest_b) = if side == Side::A { (None, other_digest) } else { (other_digest, None) };
+            diffs.push(TagDiff {
+                repository: failure.repository.clone(),
+                tag: failure.tag.clone().unwrap_or_default(),
+                status: DiffStatus::Failed,
+                digest_a,
+                digest_b,
+                source: side,
+                error: Some(format!("registry {:?}: {}", side, failure.error)),
+            });
+        }
+    }
+    diffs
+}
+
+/// 트리 줄의 이름 (ui::tree_rows의 full_image_names와 같은 형식)
+pub fn tree_name(diff: &TagDiff) -> String {
+    format!("{}/{}", ui::repository_row_name(&diff.repository), diff.tag)
+}
+
+/// 동기화되지 않은 태그만 보여 주는 TUI 트리를 만듭니다.
+/// 태그 상세는 그 태그가 있는 registry(`registries`의 A=0, B=1)에서 읽음
+pub fn diff_app(diffs: &[TagDiff], title: &str, registries: Vec<RegistryChoice>) -> App {
+    let mut repositories: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    // 태그 목록을 못 읽은 저장소는 태그 줄이 없으므로 트리에서 뺌
+    for diff in diffs.iter().filter(|d| !d.tag.is_empty()) {
+        repositories.entry(diff.repository.clone()).or_default().push(diff.tag.clone());
+    }
+    let items: TreeItems = registry::tree_items_from_tags(&repositories);
+
+    let mut app = App::new(items).with_title(title);
+    app.registries = registries;
+    for diff in diffs.iter().filter(|d| !d.tag.is_empty()) {
+        let name = tree_name(diff);
+        let label = match diff.status {
+            DiffStatus::Failed => "failed to read",
+            status => status.label(),
+        };
+        app.set_mark(&name, label, diff.status.color());
+        app.row_registry.insert(name, if diff.source == Side::A { 0 } else { 1 });
+    }
+    app
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collected(entries: &[(&str, &[(&str, &str)])], failures: &[(&str, Option<&str>)]) -> Collected {
+        Collected {
+            state: entries
+                .iter()
+                .map(|(repository, tags)| (repository.to_string(), tags.iter().map(|(t, d)| (t.to_string(), d.to_string())).collect()))
+                .collect(),
+            failures: failures
+                .iter()
+                .map(|(repository, tag)| Failure { repository: repository.to_string(), tag: tag.map(str::to_string), error: "HTTP 500".to_string() })
+                .collect(),
+        }
+    }
+
+    fn rows(diffs: &[TagDiff]) -> Vec<(&str, &str, DiffStatus, Side)> {
+        diffs.iter().map(|d| (d.repository.as_str(), d.tag.as_str(), d.status, d.source)).collect()
+    }
+
+    #[test]
+    fn compares_tags_and_records_the_owning_side() {
+        let a = collected(&[("grp/app", &[("1.0", "sha256:a"), ("2.0", "sha256:b"), ("3.0", "sha256:c")])], &[]);
+        let b = collected(&[("grp/app", &[("1.0", "sha256:a"), ("2.0", "sha256:x"), ("4.0", "sha256:d")]), ("solo", &[("1", "sha256:e")])], &[]);
+        assert_eq!(
+            rows(&compare(&a, &b)),
+            [
+                ("grp/app", "2.0", DiffStatus::Differs, Side::A),
+                ("grp/app", "3.0", DiffStatus::OnlyInA, Side::A),
+                ("grp/app", "4.0", DiffStatus::OnlyInB, Side::B),
+                ("solo", "1", DiffStatus::OnlyInB, Side::B),
+            ]
+        );
+    }
+
+    #[test]
+    fn failed_reads_become_rows_instead_of_false_differences() {
+        let a = collected(&[("grp/app", &[("1.0", "sha256:a")]), ("grp/db", &[])], &[("grp/app", Some("2.0")), ("grp/db", None)]);
+        let b = collected(&[("grp/app", &[("1.0", "sha256:a"), ("2.0", "sha256:b")]), ("grp/db", &[("9", "sha256:z")])], &[]);
+        let diffs = compare(&a, &b);
+        assert_eq!(rows(&diffs), [("grp/app", "2.0", DiffStatus::Failed, Side::A), ("grp/db", "", DiffStatus::Failed, Side::A)]);
+        assert_eq!(diffs[0].digest_b.as_deref(), Some("sha256:b"));
+        assert!(diffs[0].error.as_deref().unwrap().contains("HTTP 500"));
+    }
+
+    #[test]
+    fn diff_tree_rows_remember_their_registry() {
+        let a = collected(&[("app", &[("1", "sha256:a")])], &[]);
+        let b = collected(&[("app", &[("2", "sha256:b")])], &[]);
+        let app = diff_app(&compare(&a, &b), "t", Vec::new());
+        assert_eq!(app.row_registry.get("app//1"), Some(&0));
+        assert_eq!(app.row_registry.get("app//2"), Some(&1));
+    }
+}
@@ -0,0 +1,114 @@
+use openssl::sha::Sha256;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// 바이트 배열의 `sha256:<hex>` 다이제스트를 계산합니다.
+pub fn sha256_digest(bytes: &[u8]) -> String {
+    format!("sha256:{}", to_hex(&openssl::sha::sha256(bytes)))
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// 파일 내용의 크기와 `sha256:<hex>` 다이제스트를 계산합니다.
+pub fn file_digest(path: &Path) -> io::Result<(u64, String)> {
+    let mut reader = DigestReader::new(File::open(path)?);
+    io::copy(&mut reader, &mut io::sink())?;
+    Ok(reader.finish())
+}
+
+/// "sha256:abcd..." 에서 알고리즘과 hex 부분을 분리합니다.
+pub fn split_digest(digest: &str) -> Option<(&str, &str)> {
+    let (algorithm, hex) = digest.split_once(':')?;
+    if algorithm.is_empty() || hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some((algorithm, hex))
+}
+
+/// 쓰여지는 데이터를 그대로 전달하면서 sha256을 함께 계산하는 Writer
+pub struct DigestWriter<W: Write> {
+    inner: W,
+    hasher: Sha256,
+    written: u64,
+}
+
+impl<W: Write> DigestWriter<W> {
+    pub fn new(inner: W) -> Self {
+        DigestWriter { inner, hasher: Sha256::new(), written: 0 }
+    }
+
+    /// 지금까지 쓰여진 바이트 수와 다이제스트를 반환합니다.
+    pub fn finish(self) -> (W, u64, String) {
+        let digest = format!("sha256:{}", to_hex(&self.hasher.finish()));
+        (self.inner, self.written, digest)
+    }
+}
+
+impl<W: Write> Write for DigestWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// 읽어 들이는 데이터의 sha256을 함께 계산하는 Reader
+pub struct DigestReader<R: Read> {
+    inner: R,
+    hasher: Sha256,
+    read: u64,
+}
+
+impl<R: Read> DigestReader<R> {
+    pub fn new(inner: R) -> Self {
+        DigestReader { inner, hasher: Sha256::new(), read: 0 }
+    }
+
+    pub fn finish(self) -> (u64, String) {
+        (self.read, format!("sha256:{}", to_hex(&self.hasher.finish())))
+    }
+}
+
+impl<R: Read> Read for DigestReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.read += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EMPTY: &str = "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    #[test]
+    fn digests_bytes_readers_and_files() {
+        assert_eq!(sha256_digest(b""), EMPTY);
+        let mut reader = DigestReader::new(&b"hello"[..]);
+        io::copy(&mut reader, &mut io::sink()).unwrap();
+        assert_eq!(reader.finish(), (5, sha256_digest(b"hello")));
+
+        let path = std::env::temp_dir().join(format!("repo-tree-digest-{}", std::process::id()));
+        std::fs::write(&path, b"hello").unwrap();
+        assert_eq!(file_digest(&path).unwrap(), (5, sha256_digest(b"hello")));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn splits_digests() {
+        assert_eq!(split_digest(EMPTY).map(|(a, _)| a), Some("sha256"));
+        assert_eq!(split_digest("sha256:xyz"), None);
+        assert_eq!(split_digest("abc"), None);
+    }
+}
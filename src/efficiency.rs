@@ -0,0 +1,182 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::error::Error as StdError;
+use tabled::Tabled;
+
+use crate::layers::{self, FileEntry, FileKind, LayerFiles};
+use crate::registry::{self, Platform};
+
+/// 레이어별로 보여 줄 낭비 파일 수 기본값
+pub const DEFAULT_TOP: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WasteReason {
+    Overwritten,
+    Deleted,
+}
+
+impl std::fmt::Display for WasteReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            WasteReason::Overwritten => "overwritten",
+            WasteReason::Deleted => "deleted",
+        })
+    }
+}
+
+fn display_size(size: &u64) -> String {
+    layers::format_size(*size)
+}
+
+fn display_layer(layer: &usize) -> String {
+    format!("L{}", layer + 1)
+}
+
+/// 뒤 레이어에서 덮어쓰거나 지워서 최종 이미지에 남지 않는 파일
+#[derive(Debug, Clone, Serialize, Tabled)]
+pub struct WastedFile {
+    #[tabled(rename = "Layer", display_with = "display_layer")]
+    pub layer: usize,
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Size", display_with = "display_size")]
+    pub size: u64,
+    #[tabled(rename = "Reason")]
+    pub reason: WasteReason,
+    #[tabled(rename = "By", display_with = "display_layer")]
+    pub by_layer: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LayerWaste {
+    pub index: usize,
+    pub digest: String,
+    pub compressed_size: u64,
+    /// 레이어에 들어 있는 일반 파일의 크기 합
+    pub file_bytes: u64,
+    pub wasted_bytes: u64,
+    /// 낭비가 큰 순서로 상위 몇 개
+    pub offenders: Vec<WastedFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EfficiencyReport {
+    pub image: String,
+    pub total_bytes: u64,
+    pub wasted_bytes: u64,
+    /// 0.0 ~ 1.0, 모든 레이어 파일 중 최종 이미지에 남는 바이트 비율
+    pub efficiency: f64,
+    pub layers: Vec<LayerWaste>,
+}
+
+impl EfficiencyReport {
+    pub fn summary(&self) -> String {
+        format!(
+            "{}: efficiency {:.2}%, {} wasted of {} in {} layers",
+            self.image,
+            self.efficiency * 100.0,
+            layers::format_size(self.wasted_bytes),
+            layers::format_size(self.total_bytes),
+            self.layers.len()
+        )
+    }
+
+    /// 모든 레이어의 상위 낭비 파일을 크기순으로 모읍니다.
+    pub fn offenders(&self) -> Vec<&WastedFile> {
+        let mut all: Vec<&WastedFile> = self.layers.iter().flat_map(|l| l.offenders.iter()).collect();
+        all.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+        all
+    }
+}
+
+fn wasted(entry: FileEntry, reason: WasteReason, by_layer: usize) -> Option<WastedFile> {
+    if entry.kind != FileKind::File || entry.size == 0 {
+        return None;
+    }
+    Some(WastedFile { layer: entry.layer, path: entry.path, size: entry.size, reason, by_layer })
+}
+
+/// 레이어를 순서대로 겹치면서 가려지는 파일을 모두 찾습니다. (`layers::merge_layers`와 같은 규칙)
+pub fn analyze(image: &str, layer_files: &[LayerFiles], top: usize) -> EfficiencyReport {
+    let mut state: BTreeMap<String, FileEntry> = BTreeMap::new();
+    let mut waste: Vec<WastedFile> = Vec::new();
+
+    for (index, layer) in layer_files.iter().enumerate() {
+        for dir in &layer.opaque_dirs {
+            let removed = layers::remove_descendants(&mut state, dir);
+            waste.extend(removed.into_iter().filter_map(|e| wasted(e, WasteReason::Deleted, index)));
+        }
+        for path in &layer.whiteouts {
+            let mut removed = layers::remove_descendants(&mut state, path);
+            removed.extend(state.remove(path));
+            waste.extend(removed.into_iter().filter_map(|e| wasted(e, WasteReason::Deleted, index)));
+        }
+        for entry in &layer.entries {
+            if entry.kind != FileKind::Dir && state.get(&entry.path).is_some_and(|e| e.kind == FileKind::Dir) {
+                let removed = layers::remove_descendants(&mut state, &entry.path);
+                waste.extend(removed.into_iter().filter_map(|e| wasted(e, WasteReason::Overwritten, index)));
+            }
+            let mut entry = entry.clone();
+            entry.layer = index;
+            if let Some(previous) = state.insert(entry.path.clone(), entry) {
+                waste.extend(wasted(previous, WasteReason::Overwritten, index));
+            }
+        }
+    }
+
+    let mut report_layers: Vec<LayerWaste> = layer_files
+        .iter()
+        .enumerate()
+        .map(|(index, layer)| LayerWaste {
+            index,
+            digest: layer.digest.clone(),
+            compressed_size: layer.size,
+            file_bytes: layer.entries.iter().filter(|e| e.kind == FileKind::File).map(|e| e.size).sum(),
+            wasted_bytes: 0,
+            offenders: Vec::new(),
+        })
+        .collect();
+    waste.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    for file in waste {
+        let layer = &mut report_layers[file.layer];
+        layer.wasted_bytes += file.size;
+        if layer.offenders.len() < top {
+            layer.offenders.push(file);
+        }
+    }
+
+    let total_bytes: u64 = report_layers.iter().map(|l| l.file_bytes).sum();
+    let wasted_bytes: u64 = report_layers.iter().map(|l| l.wasted_bytes).sum();
+    let efficiency = if total_bytes == 0 { 1.0 } else { (total_bytes - wasted_bytes) as f64 / total_bytes as f64 };
+    EfficiencyReport { image: image.to_string(), total_bytes, wasted_bytes, efficiency, layers: report_layers }
+}
+
+pub async fn analyze_image(reference: &str, platform: &Platform, top: usize) -> Result<EfficiencyReport, Box<dyn StdError>> {
+    let (image, tag) = registry::parse_reference(reference)?;
+    let (_, manifest) = registry::fetch_image_manifest(&image, &tag, platform).await?;
+    let mut files = Vec::new();
+    for (index, layer) in manifest.layers.iter().enumerate() {
+        files.push(layers::fetch_layer_files(&image, layer, index).await?);
+    }
+    Ok(analyze(&format!("{}:{}", image, tag), &files, top))
+}
+
+/// TUI Efficiency 탭에 표시할 줄 목록
+pub fn report_lines(report: &EfficiencyReport) -> Vec<String> {
+    let mut lines = vec![report.summary(), String::new()];
+    for layer in &report.layers {
+        lines.push(format!(
+            "L{} {}  {} compressed, {} files, {} wasted",
+            layer.index + 1,
+            layer.digest.chars().take(19).collect::<String>(),
+            layers::format_size(layer.compressed_size),
+            layers::format_size(layer.file_bytes),
+            layers::format_size(layer.wasted_bytes)
+        ));
+        for file in &layer.offenders {
+            lines.push(format!("    {:>10}  /{}  ({} by L{})", layers::format_size(file.size), file.path, file.reason, file.by_layer + 1));
+        }
+    }
+    lines
+}
@@ -0,0 +1,292 @@
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, HashSet};
+use std::error::Error as StdError;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use crate::digest;
+use crate::registry::{self, Descriptor, Platform, RawManifest, MEDIA_TYPE_OCI_INDEX};
+use crate::tar;
+
+pub const ANNOTATION_REF_NAME: &str = "org.opencontainers.image.ref.name";
+pub const ANNOTATION_IMAGE_NAME: &str = "io.containerd.image.name";
+/// index.json에 기록하는 내보낸 registry의 "host[:port]". 가져올 때 이미지 이름에서 이 주소만 떼어 냄
+pub const ANNOTATION_SOURCE_REGISTRY: &str = "io.repo-tree.source.registry";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// oci-layout, index.json, blobs/sha256/... 디렉터리
+    OciLayout,
+    /// OCI 레이아웃을 그대로 묶은 tar
+    OciArchive,
+    /// `docker load`로 읽을 수 있는 tar (manifest.json + OCI 레이아웃)
+    DockerArchive,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Option<ExportFormat> {
+        match value {
+            "oci" | "oci-layout" => Some(ExportFormat::OciLayout),
+            "oci-archive" => Some(ExportFormat::OciArchive),
+            "docker" | "docker-archive" => Some(ExportFormat::DockerArchive),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ExportSummary {
+    pub images: usize,
+    pub blobs_written: usize,
+    /// 이번 실행에서 이미 기록한 blob (이미지 사이에서 공유)
+    pub blobs_skipped: usize,
+    /// 이전 실행에서 출력 디렉터리에 완성돼 있던 blob
+    pub blobs_reused: usize,
+    pub bytes_written: u64,
+}
+
+enum LayoutWriter {
+    Dir(PathBuf),
+    /// `partial`에 쓰고 finish에서 출력 경로로 옮김. 중간에 실패하면 완성된 것처럼 보이는 tar가 남지 않음
+    Tar { builder: tar::Builder<BufWriter<File>>, partial: PathBuf, output: PathBuf },
+}
+
+/// OCI 레이아웃 구조로 blob과 메타데이터 파일을 기록합니다.
+/// 디렉터리와 tar 모두 같은 경로 규칙(blobs/<alg>/<hex>)을 사용합니다.
+pub struct Exporter {
+    format: ExportFormat,
+    writer: LayoutWriter,
+    written: HashSet<String>,
+    index: Vec<Descriptor>,
+    docker_manifests: Vec<Value>,
+    pub summary: ExportSummary,
+}
+
+fn blob_path(digest: &str) -> Result<String, Box<dyn StdError>> {
+    let (algorithm, hex) = digest::split_digest(digest).ok_or_else(|| format!("invalid digest '{}'", digest))?;
+    Ok(format!("blobs/{}/{}", algorithm, hex))
+}
+
+impl Exporter {
+    pub fn create(format: ExportFormat, output: &Path) -> Result<Exporter, Box<dyn StdError>> {
+        let writer = match format {
+            ExportFormat::OciLayout => {
+                fs::create_dir_all(output.join("blobs").join("sha256"))?;
+                LayoutWriter::Dir(output.to_path_buf())
+            }
+            ExportFormat::OciArchive | ExportFormat::DockerArchive => {
+                let mut partial = output.as_os_str().to_owned();
+                partial.push(".partial");
+                let partial = PathBuf::from(partial);
+                let builder = tar::Builder::new(BufWriter::new(File::create(&partial)?));
+                LayoutWriter::Tar { builder, partial, output: output.to_path_buf() }
+            }
+        };
+        Ok(Exporter {
+            format,
+            writer,
+            written: HashSet::new(),
+            index: Vec::new(),
+            docker_manifests: Vec::new(),
+            summary: ExportSummary::default(),
+        })
+    }
+
+    /// registry에서 blob을 받아 기록합니다. 이미 기록된 blob은 건너뜁니다.
+    pub async fn put_blob(&mut self, image: &str, descriptor: &Descriptor) -> Result<(), Box<dyn StdError>> {
+        if self.written.contains(&descriptor.digest) {
+            self.summary.blobs_skipped += 1;
+            return Ok(());
+        }
+        let path = blob_path(&descriptor.digest)?;
+
+        match &mut self.writer {
+            LayoutWriter::Dir(root) => {
+                let target = root.join(&path);
+                // 이전 실행에서 완료된 blob은 다시 받지 않음. 크기만 맞고 내용이 깨진 파일은 다시 받음
+                let complete = fs::metadata(&target).map(|m| m.len() == descriptor.size).unwrap_or(false)
+                    && digest::file_digest(&target).map(|(_, d)| d == descriptor.digest).unwrap_or(false);
+                if complete {
+                    self.summary.blobs_reused += 1;
+                    self.written.insert(descriptor.digest.clone());
+                    return Ok(());
+                }
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let partial = target.with_extension("partial");
+                let file = BufWriter::new(File::create(&partial)?);
+                let result = registry::fetch_blob(image, descriptor, file).await;
+                match result {
+                    Ok(mut file) => {
+                        file.flush()?;
+                        fs::rename(&partial, &target)?;
+                    }
+                    Err(e) => {
+                        let _ = fs::remove_file(&partial);
+                        return Err(e);
+                    }
+                }
+            }
+            LayoutWriter::Tar { builder, .. } => {
+                builder.start_entry(&path, descriptor.size)?;
+                registry::fetch_blob(image, descriptor, builder.writer()).await?;
+                builder.end_entry(descriptor.size)?;
+            }
+        }
+
+        self.summary.blobs_written += 1;
+        self.summary.bytes_written += descriptor.size;
+        self.written.insert(descriptor.digest.clone());
+        Ok(())
+    }
+
+    /// 이미 검증된 데이터(매니페스트 등)를 blob으로 기록합니다.
+    pub fn put_blob_bytes(&mut self, digest: &str, bytes: &[u8]) -> Result<(), Box<dyn StdError>> {
+        if !self.written.insert(digest.to_string()) {
+            self.summary.blobs_skipped += 1;
+            return Ok(());
+        }
+        let path = blob_path(digest)?;
+        self.put_file(&path, bytes)?;
+        self.summary.blobs_written += 1;
+        self.summary.bytes_written += bytes.len() as u64;
+        Ok(())
+    }
+
+    pub fn put_file(&mut self, path: &str, bytes: &[u8]) -> Result<(), Box<dyn StdError>> {
+        match &mut self.writer {
+            LayoutWriter::Dir(root) => {
+                let target = root.join(path);
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(target, bytes)?;
+            }
+            LayoutWriter::Tar { builder, .. } => builder.append_bytes(path, bytes)?,
+        }
+        Ok(())
+    }
+
+    /// 매니페스트(인덱스면 하위 매니페스트 포함)와 참조하는 blob을 모두 기록합니다.
+    pub async fn put_manifest(&mut self, image: &str, raw: &RawManifest) -> Result<(), Box<dyn StdError>> {
+        let manifest = raw.parse()?;
+        if manifest.is_index() {
+            for child in &manifest.manifests {
+                let child_raw = registry::fetch_manifest_raw(image, &child.digest).await?;
+                let child_manifest = child_raw.parse()?;
+                if child_manifest.is_index() {
+                    return Err(format!("nested index {} in {} is not supported", child.digest, image).into());
+                }
+                self.put_image_blobs(image, &child_raw).await?;
+            }
+            self.put_blob_bytes(&raw.digest, &raw.bytes)?;
+        } else {
+            self.put_image_blobs(image, raw).await?;
+        }
+        Ok(())
+    }
+
+    async fn put_image_blobs(&mut self, image: &str, raw: &RawManifest) -> Result<(), Box<dyn StdError>> {
+        let manifest = raw.parse()?;
+        if let Some(config) = &manifest.config {
+            self.put_blob(image, config).await?;
+        }
+        for layer in &manifest.layers {
+            self.put_blob(image, layer).await?;
+        }
+        self.put_blob_bytes(&raw.digest, &raw.bytes)
+    }
+
+    /// index.json에 들어갈 최상위 항목을 등록합니다.
+    pub fn add_index_entry(&mut self, raw: &RawManifest, image_name: &str, tag: Option<&str>) {
+        let mut annotations = BTreeMap::new();
+        annotations.insert(ANNOTATION_IMAGE_NAME.to_string(), image_name.to_string());
+        if let Some(tag) = tag {
+            annotations.insert(ANNOTATION_REF_NAME.to_string(), tag.to_string());
+        }
+        self.index.push(Descriptor {
+            media_type: raw.media_type.clone(),
+            digest: raw.digest.clone(),
+            size: raw.bytes.len() as u64,
+            platform: None,
+            artifact_type: None,
+            annotations,
+        });
+    }
+
+    /// docker-archive의 manifest.json 항목을 등록합니다.
+    pub fn add_docker_entry(&mut self, raw: &RawManifest, repo_tag: Option<&str>) -> Result<(), Box<dyn StdError>> {
+        let manifest = raw.parse()?;
+        let config = manifest.config.as_ref().ok_or("image manifest has no config")?;
+        let layers = manifest.layers.iter().map(|l| blob_path(&l.digest)).collect::<Result<Vec<_>, _>>()?;
+        self.docker_manifests.push(json!({
+            "Config": blob_path(&config.digest)?,
+            "RepoTags": repo_tag.map(|t| vec![t.to_string()]).unwrap_or_default(),
+            "Layers": layers,
+        }));
+        Ok(())
+    }
+
+    /// oci-layout, index.json(, manifest.json)을 쓰고 출력을 마무리합니다.
+    pub fn finish(mut self) -> Result<ExportSummary, Box<dyn StdError>> {
+        let annotations = BTreeMap::from([(ANNOTATION_SOURCE_REGISTRY, registry::registry_host())]);
+        let index = json!({
+            "schemaVersion": 2,
+            "mediaType": MEDIA_TYPE_OCI_INDEX,
+            "manifests": self.index,
+            "annotations": annotations,
+        });
+        self.put_file("oci-layout", br#"{"imageLayoutVersion":"1.0.0"}"#)?;
+        self.put_file("index.json", &serde_json::to_vec(&index)?)?;
+
+        if self.format == ExportFormat::DockerArchive {
+            let manifests = serde_json::to_vec(&self.docker_manifests)?;
+            self.put_file("manifest.json", &manifests)?;
+        }
+        if let LayoutWriter::Tar { builder, partial, output } = self.writer {
+            builder.finish()?;
+            fs::rename(&partial, &output)?;
+        }
+        Ok(self.summary)
+    }
+}
+
+/// 선택한 태그들을 OCI 레이아웃 디렉터리 또는 docker-archive tar로 내보냅니다.
+pub async fn export_images(
+    references: &[String],
+    format: ExportFormat,
+    output: &Path,
+    platform: Option<&Platform>,
+) -> Result<ExportSummary, Box<dyn StdError>> {
+    let mut exporter = Exporter::create(format, output)?;
+    let host = registry::registry_host();
+
+    for reference in references {
+        let (image, tag) = registry::parse_reference(reference)?;
+        let is_digest = tag.starts_with("sha256:");
+        let image_name = if is_digest { format!("{}/{}@{}", host, image, tag) } else { format!("{}/{}:{}", host, image, tag) };
+        let ref_name = if is_digest { None } else { Some(tag.as_str()) };
+
+        match format {
+            ExportFormat::OciLayout | ExportFormat::OciArchive => {
+                let raw = registry::fetch_manifest_raw(&image, &tag).await?;
+                exporter.put_manifest(&image, &raw).await?;
+                exporter.add_index_entry(&raw, &image_name, ref_name);
+            }
+            ExportFormat::DockerArchive => {
+                // docker load는 단일 플랫폼 이미지만 읽을 수 있음
+                let platform = registry::image_platform(&image, &tag, platform).await;
+                let (raw, _) = registry::fetch_image_manifest(&image, &tag, &platform).await?;
+                exporter.put_manifest(&image, &raw).await?;
+                exporter.add_index_entry(&raw, &image_name, ref_name);
+                exporter.add_docker_entry(&raw, ref_name.map(|_| image_name.as_str()))?;
+            }
+        }
+        exporter.summary.images += 1;
+        log::info!("exported {}", image_name);
+    }
+
+    exporter.finish()
+}
@@ -0,0 +1,553 @@
+use std::error::Error as StdError;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::layers::{self, FileKind};
+use crate::registry::{self, Descriptor};
+use crate::tar;
+
+// 심볼릭 링크를 따라가는 최대 횟수 (순환 링크 방지)
+const MAX_LINK_HOPS: usize = 16;
+
+// --preserve-setuid: setuid/setgid/sticky 비트를 그대로 둠 (기본은 지움)
+static PRESERVE_SPECIAL_BITS: AtomicBool = AtomicBool::new(false);
+
+pub fn set_preserve_special_bits(preserve: bool) {
+    PRESERVE_SPECIAL_BITS.store(preserve, Ordering::Relaxed);
+}
+
+#[derive(Debug, Default)]
+pub struct ExtractResult {
+    /// 심볼릭 링크를 따라간 뒤 실제로 꺼낸 이미지 내부 경로
+    pub source_path: String,
+    pub files: usize,
+    pub bytes: u64,
+    /// 내용을 읽은 레이어 번호 (0부터)
+    pub layers: Vec<usize>,
+    /// 대상 내용을 찾지 못해 꺼내지 못한 하드링크 ("링크 -> 대상")
+    pub missing_links: Vec<String>,
+}
+
+/// 대상이 꺼낸 디렉터리 밖에 있어 레이어를 다시 읽어 내용을 복사해야 하는 하드링크
+struct PendingLink {
+    /// 이미지 내부 경로
+    path: String,
+    /// `dest` 기준 상대 경로
+    relative: String,
+    target: String,
+}
+
+/// 레이어 하나를 훑은 결과
+enum LayerHit {
+    File(u64),
+    Link(String),
+    Dir,
+    Deleted,
+    Hidden,
+    AncestorLink { ancestor: String, target: String },
+    NotFound,
+}
+
+/// 이미지 내부 경로를 정규화합니다. "./a/../b/" → "b"
+pub fn normalize(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+/// 링크 위치 기준으로 링크 대상을 이미지 내부 절대 경로로 바꿉니다.
+pub fn resolve_link(link_path: &str, target: &str) -> String {
+    if target.starts_with('/') {
+        return normalize(target);
+    }
+    let parent = link_path.rsplit_once('/').map(|(p, _)| p).unwrap_or("");
+    normalize(&format!("{}/{}", parent, target))
+}
+
+fn is_ancestor(ancestor: &str, path: &str) -> bool {
+    ancestor.is_empty() || (path.len() > ancestor.len() && path.starts_with(ancestor) && path.as_bytes()[ancestor.len()] == b'/')
+}
+
+fn is_symlink(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|meta| meta.file_type().is_symlink())
+}
+
+/// 대상 디렉터리 밖으로 나가는 경로(.., 절대 경로)와 이미 꺼낸 심볼릭 링크를 거쳐 가는 경로를 거부합니다.
+/// (앞 레이어의 `etc -> /etc` 뒤에 오는 `etc/passwd`가 호스트 파일을 건드리지 않도록)
+fn safe_join(dest: &Path, relative: &str) -> io::Result<PathBuf> {
+    let relative = Path::new(relative);
+    if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("refusing unsafe path '{}'", relative.display())));
+    }
+    let mut target = dest.to_path_buf();
+    let mut components = relative.components().peekable();
+    while let Some(component) = components.next() {
+        target.push(component);
+        if components.peek().is_some() && is_symlink(&target) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("refusing to write '{}' through the symbolic link '{}'", relative.display(), target.display()),
+            ));
+        }
+    }
+    Ok(target)
+}
+
+fn write_file(target: &Path, data: &mut dyn Read, mode: u32) -> io::Result<u64> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // 이전 레이어에서 만든 심볼릭 링크를 따라 쓰지 않도록 먼저 지움
+    let _ = fs::remove_file(target);
+    let mut out = BufWriter::new(File::create(target)?);
+    let written = io::copy(data, &mut out)?;
+    out.flush()?;
+    set_mode(target, mode)?;
+    Ok(written)
+}
+
+#[cfg(unix)]
+fn set_mode(target: &Path, mode: u32) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mask = if PRESERVE_SPECIAL_BITS.load(Ordering::Relaxed) { 0o7777 } else { 0o777 };
+    fs::set_permissions(target, fs::Permissions::from_mode(mode & mask))
+}
+
+#[cfg(not(unix))]
+fn set_mode(_target: &Path, _mode: u32) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_symlink(target: &str, link: &Path) -> io::Result<()> {
+    if let Some(parent) = link.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let _ = fs::remove_file(link);
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(not(unix))]
+fn make_symlink(target: &str, link: &Path) -> io::Result<()> {
+    // 심볼릭 링크를 만들 수 없는 환경에서는 대상 경로만 기록
+    fs::write(link, target)
+}
+
+fn remove_path(target: &Path) -> io::Result<()> {
+    match fs::symlink_metadata(target) {
+        Ok(meta) if meta.is_dir() => fs::remove_dir_all(target),
+        Ok(_) => fs::remove_file(target),
+        Err(_) => Ok(()),
+    }
+}
+
+/// 디렉터리 자체는 남기고 내용만 지움. 링크가 된 디렉터리는 따라가지 않음
+fn clear_dir(target: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(target).is_ok_and(|meta| meta.is_dir()) {
+        for child in fs::read_dir(target)? {
+            remove_path(&child?.path())?;
+        }
+    }
+    Ok(())
+}
+
+/// 레이어 스트림에서 `path`를 찾고, 일반 파일이면 바로 `dest`에 씁니다.
+fn search_layer(reader: &mut dyn Read, path: &str, dest: &Path, follow_links: bool) -> io::Result<LayerHit> {
+    let stream = layers::decompressed(reader)?;
+    let mut archive = tar::Archive::new(stream);
+    let mut result = LayerHit::NotFound;
+
+    while let Some(header) = archive.next_entry()? {
+        let entry_path = header.path.trim_end_matches('/').to_string();
+        if let Some((dir, hidden)) = layers::whiteout_target(&entry_path) {
+            match hidden {
+                Some(name) => {
+                    let removed = layers::join_path(&dir, &name);
+                    if removed == path || is_ancestor(&removed, path) {
+                        result = LayerHit::Deleted;
+                    }
+                }
+                None if is_ancestor(&dir, path) => {
+                    if matches!(result, LayerHit::NotFound) {
+                        result = LayerHit::Hidden;
+                    }
+                }
+                None => {}
+            }
+            continue;
+        }
+
+        let entry = layers::file_entry(&header, 0);
+        if entry_path == path {
+            match entry.kind {
+                FileKind::File => {
+                    let written = write_file(dest, &mut archive.data(), entry.mode)?;
+                    return Ok(LayerHit::File(written));
+                }
+                FileKind::Symlink if follow_links => return Ok(LayerHit::Link(resolve_link(path, &header.link_name))),
+                FileKind::Symlink => {
+                    make_symlink(&header.link_name, dest)?;
+                    return Ok(LayerHit::File(0));
+                }
+                // 하드링크 대상은 이미지 루트 기준 경로
+                FileKind::Hardlink => return Ok(LayerHit::Link(normalize(&header.link_name))),
+                FileKind::Dir => result = LayerHit::Dir,
+                FileKind::Other => {
+                    return Err(io::Error::new(io::ErrorKind::Unsupported, format!("'{}' is a special file", path)));
+                }
+            }
+        } else if entry.kind == FileKind::Symlink && is_ancestor(&entry_path, path) {
+            result = LayerHit::AncestorLink { ancestor: entry_path, target: header.link_name.clone() };
+        }
+    }
+    Ok(result)
+}
+
+/// 레이어를 아래에서 위로 적용해 디렉터리 `prefix`의 최종 내용을 `dest`에 만듭니다.
+/// 대상이 아직 `dest`에 없는 하드링크는 돌려주어 같은 레이어를 다시 읽어 채우게 합니다.
+fn apply_layer_to_dir(reader: &mut dyn Read, prefix: &str, dest: &Path) -> io::Result<(usize, u64, Vec<PendingLink>)> {
+    let stream = layers::decompressed(reader)?;
+    let mut archive = tar::Archive::new(stream);
+    let (mut files, mut bytes) = (0usize, 0u64);
+    let mut pending: Vec<PendingLink> = Vec::new();
+
+    while let Some(header) = archive.next_entry()? {
+        let entry_path = header.path.trim_end_matches('/').to_string();
+        if let Some((dir, hidden)) = layers::whiteout_target(&entry_path) {
+            let inside = dir == prefix || is_ancestor(prefix, &dir);
+            let removed = hidden.as_ref().map(|name| layers::join_path(&dir, name));
+            match hidden {
+                // prefix 자체나 그 조상이 지워지면 하위 레이어에서 꺼낸 내용 전체가 사라짐
+                Some(_) if removed.as_deref().is_some_and(|r| r == prefix || is_ancestor(r, prefix)) => clear_dir(dest)?,
+                Some(name) if inside => {
+                    let relative = dir.strip_prefix(prefix).unwrap_or("").trim_start_matches('/');
+                    remove_path(&safe_join(dest, &layers::join_path(relative, &name))?)?
+                }
+                // opaque: 하위 레이어에서 온 내용을 모두 지움. 조상의 opaque는 prefix 전체를 가림
+                None if inside => {
+                    let relative = dir.strip_prefix(prefix).unwrap_or("").trim_start_matches('/');
+                    clear_dir(&if relative.is_empty() { dest.to_path_buf() } else { safe_join(dest, relative)? })?
+                }
+                None if is_ancestor(&dir, prefix) => clear_dir(dest)?,
+                _ => {}
+            }
+            continue;
+        }
+
+        if !is_ancestor(prefix, &entry_path) {
+            continue;
+        }
+        let relative = entry_path[prefix.len()..].trim_start_matches('/');
+        let target = safe_join(dest, relative)?;
+        let entry = layers::file_entry(&header, 0);
+        match entry.kind {
+            FileKind::Dir => fs::create_dir_all(&target)?,
+            FileKind::File => {
+                bytes += write_file(&target, &mut archive.data(), entry.mode)?;
+                files += 1;
+            }
+            FileKind::Symlink => {
+                make_symlink(&header.link_name, &target)?;
+                files += 1;
+            }
+            FileKind::Hardlink => {
+                // 같은 디렉터리 안에서 이미 꺼낸 파일이면 복사
+                let link = normalize(&header.link_name);
+                let source = match is_ancestor(prefix, &link) {
+                    true => Some(safe_join(dest, link[prefix.len()..].trim_start_matches('/'))?),
+                    false => None,
+                };
+                // 링크를 따라가면 호스트 파일을 복사할 수 있으므로 일반 파일만
+                match source.filter(|source| fs::symlink_metadata(source).is_ok_and(|meta| meta.is_file())) {
+                    Some(source) => {
+                        remove_path(&target)?;
+                        bytes += fs::copy(&source, &target)?;
+                        files += 1;
+                    }
+                    None => {
+                        // 아직 채우지 못한 링크를 가리키면 그 링크의 대상을 따라감
+                        let target = pending.iter().find(|p| p.path == link).map_or(link, |p| p.target.clone());
+                        pending.push(PendingLink { path: entry_path.clone(), relative: relative.to_string(), target });
+                    }
+                }
+            }
+            FileKind::Other => {}
+        }
+    }
+    Ok((files, bytes, pending))
+}
+
+/// 하드링크 대상은 같은 레이어에서 링크보다 앞에 있으므로, 레이어를 다시 읽어 대상 내용을 링크 위치에 씁니다.
+/// 대상을 찾지 못한 링크는 "링크 -> 대상"으로 돌려줍니다.
+fn copy_link_targets(reader: &mut dyn Read, dest: &Path, pending: &[PendingLink]) -> io::Result<(usize, u64, Vec<String>)> {
+    let stream = layers::decompressed(reader)?;
+    let mut archive = tar::Archive::new(stream);
+    let (mut files, mut bytes) = (0usize, 0u64);
+    let mut copied = vec![false; pending.len()];
+
+    while let Some(header) = archive.next_entry()? {
+        let entry_path = header.path.trim_end_matches('/');
+        let entry = layers::file_entry(&header, 0);
+        if entry.kind != FileKind::File {
+            continue;
+        }
+        let mut first: Option<PathBuf> = None;
+        for (link, done) in pending.iter().zip(copied.iter_mut()).filter(|(link, _)| link.target == entry_path) {
+            let target = safe_join(dest, &link.relative)?;
+            bytes += match &first {
+                Some(source) => {
+                    remove_path(&target)?;
+                    fs::copy(source, &target)?
+                }
+                None => write_file(&target, &mut archive.data(), entry.mode)?,
+            };
+            first.get_or_insert(target);
+            files += 1;
+            *done = true;
+        }
+    }
+    let missing = pending.iter().zip(copied).filter(|(_, done)| !done).map(|(link, _)| format!("/{} -> /{}", link.path, link.target));
+    Ok((files, bytes, missing.collect()))
+}
+
+/// 특정 레이어 하나만 내려받아 파일을 꺼냅니다. (Files 탭처럼 레이어를 이미 알고 있는 경우)
+pub async fn extract_from_layer(image: &str, layer: &Descriptor, path: &str, dest: &Path) -> Result<u64, Box<dyn StdError>> {
+    let path = normalize(path);
+    let (owned_path, owned_dest) = (path.clone(), dest.to_path_buf());
+    let hit = registry::read_blob_with(image, layer, move |reader| search_layer(reader, &owned_path, &owned_dest, false)).await?;
+    match hit {
+        LayerHit::File(bytes) => Ok(bytes),
+        _ => Err(format!("'{}' is not a file in layer {}", path, layer.digest).into()),
+    }
+}
+
+/// 디렉터리 `prefix`를 레이어 순서대로 적용해 꺼냅니다. `layer_filter`가 있으면 해당 레이어만 읽습니다.
+pub async fn extract_directory(
+    image: &str,
+    layers: &[Descriptor],
+    prefix: &str,
+    dest: &Path,
+    layer_filter: Option<&[usize]>,
+) -> Result<ExtractResult, Box<dyn StdError>> {
+    let prefix = normalize(prefix);
+    fs::create_dir_all(dest)?;
+    let mut result = ExtractResult { source_path: prefix.clone(), ..Default::default() };
+
+    for (index, layer) in layers.iter().enumerate() {
+        if layer_filter.is_some_and(|f| !f.contains(&index)) {
+            continue;
+        }
+        let (owned_prefix, owned_dest) = (prefix.clone(), dest.to_path_buf());
+        let (files, bytes, pending) =
+            registry::read_blob_with(image, layer, move |reader| apply_layer_to_dir(reader, &owned_prefix, &owned_dest)).await?;
+        result.files += files;
+        result.bytes += bytes;
+        result.layers.push(index);
+        if !pending.is_empty() {
+            // 다음 레이어를 적용하기 전에 채워야 위 레이어의 whiteout이 그대로 적용됨
+            let owned_dest = dest.to_path_buf();
+            let (files, bytes, missing) =
+                registry::read_blob_with(image, layer, move |reader| copy_link_targets(reader, &owned_dest, &pending)).await?;
+            result.files += files;
+            result.bytes += bytes;
+            result.missing_links.extend(missing);
+        }
+    }
+    Ok(result)
+}
+
+/// 위 레이어부터 차례로 스트리밍하여 `path`를 가장 먼저 포함한 레이어에서 꺼냅니다.
+/// whiteout으로 지워진 경로는 찾지 않으며, 심볼릭 링크는 `follow_links`일 때 대상을 따라갑니다.
+pub async fn extract_path(
+    image: &str,
+    layers: &[Descriptor],
+    path: &str,
+    dest: &Path,
+    follow_links: bool,
+) -> Result<ExtractResult, Box<dyn StdError>> {
+    let mut current = normalize(path);
+    if current.is_empty() {
+        return extract_directory(image, layers, "", dest, None).await;
+    }
+
+    for _ in 0..MAX_LINK_HOPS {
+        let mut redirect = None;
+        for index in (0..layers.len()).rev() {
+            let (owned_path, owned_dest) = (current.clone(), dest.to_path_buf());
+            let hit = registry::read_blob_with(image, &layers[index], move |reader| {
+                search_layer(reader, &owned_path, &owned_dest, follow_links)
+            })
+            .await?;
+
+            match hit {
+                LayerHit::File(bytes) => {
+                    return Ok(ExtractResult { source_path: current, files: 1, bytes, layers: vec![index], ..Default::default() });
+                }
+                LayerHit::Dir => return extract_directory(image, layers, &current, dest, None).await,
+                LayerHit::Link(target) => {
+                    redirect = Some(target);
+                    break;
+                }
+                LayerHit::AncestorLink { ancestor, target } => {
+                    let rest = &current[ancestor.len()..];
+                    redirect = Some(normalize(&format!("{}{}", resolve_link(&ancestor, &target), rest)));
+                    break;
+                }
+                LayerHit::Deleted => return Err(format!("'{}' was deleted in layer {}", current, index + 1).into()),
+                // opaque 디렉터리 아래는 하위 레이어를 볼 필요가 없음
+                LayerHit::Hidden => break,
+                LayerHit::NotFound => {}
+            }
+        }
+        match redirect {
+            Some(target) => {
+                log::info!("following link {} -> {}", current, target);
+                current = target;
+            }
+            None => return Err(format!("'{}' not found in {}", current, image).into()),
+        }
+    }
+    Err(format!("too many levels of symbolic links while resolving '{}'", path).into())
+}
+
+/// "repo:tag"의 이미지에서 경로를 꺼냅니다.
+pub async fn extract(
+    reference: &str,
+    platform: &registry::Platform,
+    path: &str,
+    dest: &Path,
+    follow_links: bool,
+) -> Result<ExtractResult, Box<dyn StdError>> {
+    let (image, tag) = registry::parse_reference(reference)?;
+    let (_, manifest) = registry::fetch_image_manifest(&image, &tag, platform).await?;
+    extract_path(&image, &manifest.layers, path, dest, follow_links).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("repo-tree-extract-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn normalize_resolves_dots() {
+        assert_eq!(normalize("./a/../b/"), "b");
+        assert_eq!(normalize("/etc//os-release"), "etc/os-release");
+        assert_eq!(resolve_link("usr/bin/python", "python3"), "usr/bin/python3");
+        assert_eq!(resolve_link("lib/x", "/usr/lib/x"), "usr/lib/x");
+    }
+
+    #[test]
+    fn safe_join_rejects_escaping_paths() {
+        let dest = Path::new("/nonexistent-dest");
+        assert!(safe_join(dest, "../etc/passwd").is_err());
+        assert!(safe_join(dest, "/etc/passwd").is_err());
+        assert_eq!(safe_join(dest, "etc/passwd").unwrap(), dest.join("etc/passwd"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn safe_join_rejects_extracted_symlink_ancestors() {
+        let dest = scratch_dir("symlink");
+        std::os::unix::fs::symlink("/etc", dest.join("etc")).unwrap();
+        assert!(safe_join(&dest, "etc/passwd").is_err());
+        assert!(safe_join(&dest, "etc/ssl/certs").is_err());
+        // 링크 자체는 지우거나 바꿀 수 있어야 함
+        assert_eq!(safe_join(&dest, "etc").unwrap(), dest.join("etc"));
+        fs::remove_dir_all(&dest).unwrap();
+    }
+
+    /// (경로, 내용, 하드링크 대상) 목록으로 레이어 tar를 만듦
+    fn layer(entries: &[(&str, &[u8], Option<&str>)]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for (path, data, link) in entries {
+            let mut builder = tar::Builder::new(Vec::new());
+            builder.append_bytes(path, data).unwrap();
+            let mut entry = std::mem::take(builder.writer());
+            if let Some(link) = link {
+                entry[156] = tar::TYPE_HARDLINK;
+                entry[157..157 + link.len()].copy_from_slice(link.as_bytes());
+                entry[148..156].copy_from_slice(b"        ");
+                let checksum: u32 = entry[..512].iter().map(|&b| b as u32).sum();
+                entry[148..155].copy_from_slice(format!("{:06o}\0", checksum).as_bytes());
+            }
+            bytes.extend(entry);
+        }
+        bytes.extend([0u8; 1024]);
+        bytes
+    }
+
+    #[test]
+    fn whiteout_of_the_prefix_or_its_ancestor_clears_dest() {
+        let dest = scratch_dir("whiteout");
+        let prefilled = |dest: &Path| write_file(&dest.join("old"), &mut &b"x"[..], 0o644).unwrap();
+
+        prefilled(&dest);
+        apply_layer_to_dir(&mut &layer(&[("etc/.wh.app", b"", None)])[..], "etc/app", &dest).unwrap();
+        assert_eq!(fs::read_dir(&dest).unwrap().count(), 0);
+
+        prefilled(&dest);
+        apply_layer_to_dir(&mut &layer(&[(".wh.etc", b"", None)])[..], "etc/app", &dest).unwrap();
+        assert_eq!(fs::read_dir(&dest).unwrap().count(), 0);
+
+        // 조상의 opaque는 하위 레이어 내용만 가리고, 같은 레이어에서 다시 넣은 파일은 남김
+        prefilled(&dest);
+        let opaque = layer(&[("etc/.wh..wh..opq", b"", None), ("etc/app/new", b"y", None)]);
+        apply_layer_to_dir(&mut &opaque[..], "etc/app", &dest).unwrap();
+        assert!(!dest.join("old").exists());
+        assert_eq!(fs::read(dest.join("new")).unwrap(), b"y");
+
+        // 이웃 경로의 whiteout은 영향 없음
+        apply_layer_to_dir(&mut &layer(&[("etc/.wh.apple", b"", None)])[..], "etc/app", &dest).unwrap();
+        assert!(dest.join("new").exists());
+        fs::remove_dir_all(&dest).unwrap();
+    }
+
+    #[test]
+    fn hardlinks_outside_the_prefix_copy_their_target_or_are_reported() {
+        let dest = scratch_dir("hardlink");
+        let bytes = layer(&[
+            ("usr/lib/libx.so", b"library", None),
+            ("opt/app/libx.so", b"", Some("usr/lib/libx.so")),
+            ("opt/app/libx.so.1", b"", Some("usr/lib/libx.so")),
+            ("opt/app/inner", b"", Some("opt/app/libx.so.1")),
+            ("opt/app/gone", b"", Some("usr/lib/missing")),
+        ]);
+        let (files, _, pending) = apply_layer_to_dir(&mut &bytes[..], "opt/app", &dest).unwrap();
+        assert_eq!((files, pending.len()), (0, 4));
+
+        let (files, bytes_copied, missing) = copy_link_targets(&mut &bytes[..], &dest, &pending).unwrap();
+        assert_eq!((files, bytes_copied), (3, 21));
+        for name in ["libx.so", "libx.so.1", "inner"] {
+            assert_eq!(fs::read(dest.join(name)).unwrap(), b"library");
+        }
+        assert_eq!(missing, ["/opt/app/gone -> /usr/lib/missing"]);
+        fs::remove_dir_all(&dest).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn set_mode_drops_setuid_by_default() {
+        use std::os::unix::fs::PermissionsExt;
+        let dest = scratch_dir("mode");
+        let file = dest.join("su");
+        write_file(&file, &mut &b"x"[..], 0o4755).unwrap();
+        assert_eq!(fs::metadata(&file).unwrap().permissions().mode() & 0o7777, 0o755);
+        fs::remove_dir_all(&dest).unwrap();
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::layers::{self, FileEntry, FileKind, LayerFiles};
+
+/// 화면에 표시되는 트리 한 줄
+#[derive(Debug, Clone)]
+pub struct Row {
+    pub path: String,
+    pub name: String,
+    pub depth: usize,
+    /// 상위 디렉터리 엔트리가 tar에 없어서 만들어 낸 경우 None
+    pub entry: Option<FileEntry>,
+    pub is_dir: bool,
+    pub whiteout: bool,
+}
+
+/// 상세 팝업의 Files 탭 상태 (레이어별 보기 / 병합된 파일 시스템 보기)
+pub struct FileBrowser {
+    pub image: String,
+    pub tag: String,
+    pub layers: Vec<LayerFiles>,
+    /// 0..layers.len()는 개별 레이어, layers.len()은 병합된 파일 시스템
+    pub view: usize,
+    pub selected: usize,
+    pub scroll: usize,
+    /// 마지막 작업(추출 등)의 결과 메시지
+    pub status: Option<String>,
+    /// 이미 있는 파일을 덮어쓰려고 x를 한 번 누른 경로 (한 번 더 누르면 덮어씀)
+    pub confirm_overwrite: Option<String>,
+    expanded: HashSet<String>,
+    files: BTreeMap<String, FileEntry>,
+    whiteouts: HashSet<String>,
+    children: BTreeMap<String, Vec<String>>,
+    rows: Vec<Row>,
+}
+
+impl FileBrowser {
+    pub fn new(image: &str, tag: &str, layers: Vec<LayerFiles>) -> FileBrowser {
+        let view = layers.len();
+        let mut browser = FileBrowser {
+            image: image.to_string(),
+            tag: tag.to_string(),
+            layers,
+            view,
+            selected: 0,
+            scroll: 0,
+            status: None,
+            confirm_overwrite: None,
+            expanded: HashSet::new(),
+            files: BTreeMap::new(),
+            whiteouts: HashSet::new(),
+            children: BTreeMap::new(),
+            rows: Vec::new(),
+        };
+        browser.rebuild_view();
+        browser
+    }
+
+    pub fn is_merged(&self) -> bool {
+        self.view == self.layers.len()
+    }
+
+    pub fn view_label(&self) -> String {
+        if self.is_merged() {
+            let total: u64 = self.layers.iter().map(|l| l.size).sum();
+            format!(
+                "{}:{} merged filesystem ({} layers, {} compressed)",
+                self.image,
+                self.tag,
+                self.layers.len(),
+                layers::format_size(total)
+            )
+        } else {
+            let layer = &self.layers[self.view];
+            format!(
+                "{}:{} layer {}/{} {} ({} compressed, {} entries, {} whiteouts)",
+                self.image,
+                self.tag,
+                self.view + 1,
+                self.layers.len(),
+                layer.digest.chars().take(19).collect::<String>(),
+                layers::format_size(layer.size),
+                layer.entries.len(),
+                layer.whiteouts.len() + layer.opaque_dirs.len()
+            )
+        }
+    }
+
+    pub fn next_view(&mut self) {
+        self.view = (self.view + 1) % (self.layers.len() + 1);
+        self.rebuild_view();
+    }
+
+    pub fn previous_view(&mut self) {
+        self.view = (self.view + self.layers.len()) % (self.layers.len() + 1);
+        self.rebuild_view();
+    }
+
+    /// `path` 아래에 항목이 있는 레이어 번호 목록
+    pub fn layers_under(&self, path: &str) -> Vec<usize> {
+        let prefix = format!("{}/", path);
+        self.layers
+            .iter()
+            .enumerate()
+            .filter(|(_, layer)| {
+                layer.entries.iter().any(|e| e.path == path || e.path.starts_with(&prefix))
+                    || layer.whiteouts.iter().chain(layer.opaque_dirs.iter()).any(|w| w.starts_with(&prefix) || w == path)
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    pub fn selected_row(&self) -> Option<&Row> {
+        self.rows.get(self.selected)
+    }
+
+    pub fn move_down(&mut self, count: usize, visible: usize) {
+        self.selected = (self.selected + count).min(self.rows.len().saturating_sub(1));
+        if self.selected >= self.scroll + visible {
+            self.scroll = self.selected + 1 - visible.max(1);
+        }
+    }
+
+    pub fn move_up(&mut self, count: usize) {
+        self.selected = self.selected.saturating_sub(count);
+        if self.selected < self.scroll {
+            self.scroll = self.selected;
+        }
+    }
+
+    pub fn expand(&mut self) {
+        if let Some(row) = self.selected_row() {
+            if row.is_dir {
+                let path = row.path.clone();
+                self.expanded.insert(path);
+                self.rebuild_rows();
+            }
+        }
+    }
+
+    /// 펼쳐진 디렉터리면 접고, 아니면 상위 디렉터리로 이동합니다.
+    pub fn collapse(&mut self) {
+        let Some(row) = self.selected_row().cloned() else { return };
+        if row.is_dir && self.expanded.remove(&row.path) {
+            self.rebuild_rows();
+            return;
+        }
+        if let Some((parent, _)) = row.path.rsplit_once('/') {
+            if let Some(index) = self.rows.iter().position(|r| r.path == parent) {
+                self.selected = index;
+                if self.selected < self.scroll {
+                    self.scroll = self.selected;
+                }
+            }
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        let Some(row) = self.selected_row() else { return };
+        if !row.is_dir {
+            return;
+        }
+        let path = row.path.clone();
+        if !self.expanded.remove(&path) {
+            self.expanded.insert(path);
+        }
+        self.rebuild_rows();
+    }
+
+    fn rebuild_view(&mut self) {
+        self.whiteouts.clear();
+        self.files = if self.is_merged() {
+            layers::merge_layers(&self.layers)
+        } else {
+            let layer = &self.layers[self.view];
+            self.whiteouts.extend(layer.whiteouts.iter().cloned());
+            layer.entries.iter().map(|e| (e.path.clone(), e.clone())).collect()
+        };
+
+        // tar에 상위 디렉터리 엔트리가 없어도 트리가 이어지도록 부모 경로를 모두 등록
+        let mut children: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        let mut seen = HashSet::new();
+        let paths: Vec<String> = self.files.keys().chain(self.whiteouts.iter()).cloned().collect();
+        for path in paths {
+            let mut current = path;
+            while seen.insert(current.clone()) {
+                let parent = current.rsplit_once('/').map(|(p, _)| p.to_string()).unwrap_or_default();
+                children.entry(parent.clone()).or_default().push(current.clone());
+                if parent.is_empty() {
+                    break;
+                }
+                current = parent;
+            }
+        }
+        for list in children.values_mut() {
+            list.sort_by(|a, b| {
+                let a_dir = children_is_dir(&self.files, a);
+                let b_dir = children_is_dir(&self.files, b);
+                b_dir.cmp(&a_dir).then_with(|| a.cmp(b))
+            });
+        }
+        self.children = children;
+        self.selected = 0;
+        self.scroll = 0;
+        self.rebuild_rows();
+    }
+
+    fn rebuild_rows(&mut self) {
+        let mut rows = Vec::new();
+        self.push_rows("", 0, &mut rows);
+        self.rows = rows;
+        if self.selected >= self.rows.len() {
+            self.selected = self.rows.len().saturating_sub(1);
+        }
+    }
+
+    fn push_rows(&self, dir: &str, depth: usize, rows: &mut Vec<Row>) {
+        let Some(children) = self.children.get(dir) else { return };
+        for path in children {
+            let entry = self.files.get(path).cloned();
+            let is_dir = entry.as_ref().map(|e| e.kind == FileKind::Dir).unwrap_or(false) || self.children.contains_key(path);
+            let name = path.rsplit('/').next().unwrap_or(path).to_string();
+            let whiteout = entry.is_none() && self.whiteouts.contains(path);
+            rows.push(Row { path: path.clone(), name, depth, entry, is_dir, whiteout });
+            if is_dir && self.expanded.contains(path) {
+                self.push_rows(path, depth + 1, rows);
+            }
+        }
+    }
+
+    /// 한 줄 형식: "drwxr-xr-x root:root    1.2 KB  ▾ name -> target"
+    pub fn format_row(&self, row: &Row) -> String {
+        let (mode, owner, size) = match &row.entry {
+            Some(entry) => (
+                layers::format_mode(entry.kind, entry.mode),
+                entry.owner.clone(),
+                if entry.kind == FileKind::File { layers::format_size(entry.size) } else { String::new() },
+            ),
+            None if row.whiteout => ("----------".to_string(), String::new(), String::new()),
+            None => ("d?????????".to_string(), String::new(), String::new()),
+        };
+        let marker = if row.is_dir {
+            if self.expanded.contains(&row.path) { "▾ " } else { "▸ " }
+        } else {
+            "  "
+        };
+        let mut line = format!("{} {:<14} {:>10}  {}{}{}", mode, owner, size, "  ".repeat(row.depth), marker, row.name);
+        if row.whiteout {
+            line.push_str("  (deleted by whiteout)");
+        }
+        if let Some(target) = row.entry.as_ref().and_then(|e| e.link_target.as_ref()) {
+            line.push_str(&format!(" -> {}", target));
+        }
+        if self.is_merged() {
+            if let Some(entry) = &row.entry {
+                line.push_str(&format!("  [L{}]", entry.layer + 1));
+            }
+        }
+        line
+    }
+}
+
+fn children_is_dir(files: &BTreeMap<String, FileEntry>, path: &str) -> bool {
+    files.get(path).map(|e| e.kind == FileKind::Dir).unwrap_or(true)
+}
@@ -0,0 +1,343 @@
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error as StdError;
+use tabled::Tabled;
+use tui::style::Color;
+
+use crate::layers::{self, FileEntry, FileKind, LayerFiles};
+use crate::registry::{self, Platform};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeStatus {
+    Added,
+    Removed,
+    Modified,
+}
+
+impl ChangeStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeStatus::Added => "added",
+            ChangeStatus::Removed => "removed",
+            ChangeStatus::Modified => "modified",
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            ChangeStatus::Added => '+',
+            ChangeStatus::Removed => '-',
+            ChangeStatus::Modified => '~',
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            ChangeStatus::Added => Color::Green,
+            ChangeStatus::Removed => Color::Red,
+            ChangeStatus::Modified => Color::Yellow,
+        }
+    }
+}
+
+impl std::fmt::Display for ChangeStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+fn display_size(size: &Option<u64>) -> String {
+    size.map(layers::format_size).unwrap_or_else(|| "-".to_string())
+}
+
+fn display_delta(delta: &i64) -> String {
+    format_delta(*delta)
+}
+
+/// 크기 변화량을 부호와 함께 표시합니다. 예: "+1.2 KB", "-300 B"
+pub fn format_delta(delta: i64) -> String {
+    match delta {
+        0 => "0 B".to_string(),
+        d if d > 0 => format!("+{}", layers::format_size(d as u64)),
+        d => format!("-{}", layers::format_size(d.unsigned_abs())),
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Tabled)]
+pub struct FileChange {
+    #[tabled(rename = "Status")]
+    pub status: ChangeStatus,
+    #[tabled(rename = "Path")]
+    pub path: String,
+    #[tabled(rename = "Size A", display_with = "display_size")]
+    pub size_a: Option<u64>,
+    #[tabled(rename = "Size B", display_with = "display_size")]
+    pub size_b: Option<u64>,
+    #[tabled(rename = "Delta", display_with = "display_delta")]
+    pub delta: i64,
+}
+
+/// 같은 경로의 파일이 바뀌었는지 판단한 방법
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareMethod {
+    /// 파일 내용의 sha256
+    Content,
+    /// tar 헤더의 크기, 권한, 소유자, 링크 대상만
+    Metadata,
+}
+
+impl CompareMethod {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CompareMethod::Content => "compared by content sha256",
+            CompareMethod::Metadata => "compared by size, mode and owner only",
+        }
+    }
+}
+
+/// 두 이미지의 파일 시스템 비교 결과
+#[derive(Debug, Clone, Serialize)]
+pub struct FsDiff {
+    pub image_a: String,
+    pub image_b: String,
+    pub layers_a: usize,
+    pub layers_b: usize,
+    /// 두 이미지에 같은 다이제스트로 들어 있어 한 번만 읽은 레이어 수
+    pub shared_layers: usize,
+    /// 위 레이어만으로 결과가 정해져 내려받지 않은 공통 하위 레이어 수
+    pub skipped_layers: usize,
+    pub method: CompareMethod,
+    pub changes: Vec<FileChange>,
+}
+
+impl FsDiff {
+    pub fn count(&self, status: ChangeStatus) -> usize {
+        self.changes.iter().filter(|c| c.status == status).count()
+    }
+
+    pub fn size_delta(&self) -> i64 {
+        self.changes.iter().map(|c| c.delta).sum()
+    }
+
+    pub fn summary(&self) -> String {
+        let skipped = match self.skipped_layers {
+            0 => String::new(),
+            n => format!(", {} shared base layer(s) not downloaded", n),
+        };
+        format!(
+            "{} added, {} removed, {} modified, {} total ({} of {}/{} layers shared{}, {})",
+            self.count(ChangeStatus::Added),
+            self.count(ChangeStatus::Removed),
+            self.count(ChangeStatus::Modified),
+            format_delta(self.size_delta()),
+            self.shared_layers,
+            self.layers_a,
+            self.layers_b,
+            skipped,
+            self.method.label()
+        )
+    }
+}
+
+fn entry_size(entry: &FileEntry) -> u64 {
+    if entry.kind == FileKind::File { entry.size } else { 0 }
+}
+
+/// tar 헤더의 속성과, 양쪽 모두 있으면 내용 해시를 비교합니다.
+fn entry_changed(a: &FileEntry, b: &FileEntry) -> bool {
+    let content_changed = matches!((&a.content_digest, &b.content_digest), (Some(x), Some(y)) if x != y);
+    a.kind != b.kind || a.size != b.size || a.mode != b.mode || a.owner != b.owner || a.link_target != b.link_target || content_changed
+}
+
+/// 모든 일반 파일에 내용 해시가 있으면 Content
+pub fn compare_method<'a>(layers: impl IntoIterator<Item = &'a LayerFiles>) -> CompareMethod {
+    let hashed = layers.into_iter().flat_map(|l| &l.entries).filter(|e| e.kind == FileKind::File).all(|e| e.content_digest.is_some());
+    if hashed { CompareMethod::Content } else { CompareMethod::Metadata }
+}
+
+/// 병합된 두 파일 시스템을 비교합니다. 디렉터리 자체는 결과에서 제외합니다.
+pub fn compare_layers<'a>(layers_a: impl IntoIterator<Item = &'a LayerFiles>, layers_b: impl IntoIterator<Item = &'a LayerFiles>) -> Vec<FileChange> {
+    let merged_a = layers::merge_layers(layers_a);
+    let merged_b = layers::merge_layers(layers_b);
+    let paths: BTreeSet<&String> = merged_a.keys().chain(merged_b.keys()).collect();
+
+    let mut changes = Vec::new();
+    for path in paths {
+        let a = merged_a.get(path).filter(|e| e.kind != FileKind::Dir);
+        let b = merged_b.get(path).filter(|e| e.kind != FileKind::Dir);
+        let status = match (a, b) {
+            (None, Some(_)) => ChangeStatus::Added,
+            (Some(_), None) => ChangeStatus::Removed,
+            (Some(a), Some(b)) if entry_changed(a, b) => ChangeStatus::Modified,
+            _ => continue,
+        };
+        let size_a = a.map(entry_size);
+        let size_b = b.map(entry_size);
+        changes.push(FileChange {
+            status,
+            path: path.clone(),
+            size_a,
+            size_b,
+            delta: size_b.unwrap_or(0) as i64 - size_a.unwrap_or(0) as i64,
+        });
+    }
+    changes
+}
+
+/// 공통 하위 레이어 위에서 두 쪽이 같은 경로 집합을 같은 종류로 쓰고 아무것도 지우지 않으면,
+/// 하위 레이어의 내용과 관계없이 위 레이어끼리의 비교 결과가 전체 비교와 같음
+fn upper_layers_decide(upper_a: &[&LayerFiles], upper_b: &[&LayerFiles]) -> bool {
+    let deletes = |layers: &[&LayerFiles]| layers.iter().any(|l| !l.whiteouts.is_empty() || !l.opaque_dirs.is_empty());
+    if deletes(upper_a) || deletes(upper_b) {
+        return false;
+    }
+    let merged_a = layers::merge_layers(upper_a.iter().copied());
+    let merged_b = layers::merge_layers(upper_b.iter().copied());
+    merged_a.len() == merged_b.len() && merged_a.iter().all(|(path, a)| merged_b.get(path).is_some_and(|b| a.kind == b.kind))
+}
+
+/// 두 이미지의 병합된 파일 시스템을 비교합니다.
+/// 아래에서부터 같은 다이제스트인 레이어는 공통 바탕으로 보고, 그 위 레이어만으로 결과가 정해지면 바탕은 내려받지 않습니다.
+/// 그 밖의 공통 레이어는 한 번만 내려받고, 레이어 목록이 같으면 아무것도 받지 않습니다.
+/// 크기가 같은 파일의 내용 변경도 잡도록 레이어를 읽으면서 파일 내용의 sha256을 계산합니다.
+pub async fn diff_images(reference_a: &str, reference_b: &str, platform: &Platform) -> Result<FsDiff, Box<dyn StdError>> {
+    let (image_a, tag_a) = registry::parse_reference(reference_a)?;
+    let (image_b, tag_b) = registry::parse_reference(reference_b)?;
+    let (_, manifest_a) = registry::fetch_image_manifest(&image_a, &tag_a, platform).await?;
+    let (_, manifest_b) = registry::fetch_image_manifest(&image_b, &tag_b, platform).await?;
+
+    let digests_a: BTreeSet<&String> = manifest_a.layers.iter().map(|l| &l.digest).collect();
+    let shared_layers = manifest_b.layers.iter().filter(|l| digests_a.contains(&l.digest)).count();
+    let mut diff = FsDiff {
+        image_a: format!("{}:{}", image_a, tag_a),
+        image_b: format!("{}:{}", image_b, tag_b),
+        layers_a: manifest_a.layers.len(),
+        layers_b: manifest_b.layers.len(),
+        shared_layers,
+        skipped_layers: 0,
+        method: CompareMethod::Content,
+        changes: Vec::new(),
+    };
+    let base = manifest_a.layers.iter().zip(&manifest_b.layers).take_while(|(a, b)| a.digest == b.digest).count();
+    if base == manifest_a.layers.len() && base == manifest_b.layers.len() {
+        return Ok(diff);
+    }
+
+    // 레이어는 다이제스트마다 한 번만 읽어 `files`에 두고, 양쪽은 그 인덱스로 가리킴
+    let mut files: Vec<LayerFiles> = Vec::new();
+    let mut read: HashMap<String, usize> = HashMap::new();
+    let mut upper_a = Vec::new();
+    let mut upper_b = Vec::new();
+    for (image, manifest, upper) in [(&image_a, &manifest_a, &mut upper_a), (&image_b, &manifest_b, &mut upper_b)] {
+        for (index, layer) in manifest.layers.iter().enumerate().skip(base) {
+            let slot = match read.get(&layer.digest) {
+                Some(&slot) => slot,
+                None => {
+                    files.push(layers::fetch_layer_files_hashed(image, layer, index).await?);
+                    read.insert(layer.digest.clone(), files.len() - 1);
+                    files.len() - 1
+                }
+            };
+            upper.push(slot);
+        }
+    }
+
+    let decided = {
+        let refs = |slots: &[usize]| slots.iter().map(|&slot| &files[slot]).collect::<Vec<_>>();
+        upper_layers_decide(&refs(&upper_a), &refs(&upper_b))
+    };
+    let mut base_slots = Vec::new();
+    if decided {
+        diff.skipped_layers = base;
+    } else {
+        for (index, layer) in manifest_a.layers.iter().enumerate().take(base) {
+            let slot = match read.get(&layer.digest) {
+                Some(&slot) => slot,
+                None => {
+                    files.push(layers::fetch_layer_files_hashed(&image_a, layer, index).await?);
+                    files.len() - 1
+                }
+            };
+            base_slots.push(slot);
+        }
+    }
+
+    let side = |upper: &[usize]| base_slots.iter().chain(upper).map(|&slot| &files[slot]).collect::<Vec<_>>();
+    let (layers_a, layers_b) = (side(&upper_a), side(&upper_b));
+    diff.method = if compare_method(layers_a.iter().copied()) == CompareMethod::Content && compare_method(layers_b.iter().copied()) == CompareMethod::Content {
+        CompareMethod::Content
+    } else {
+        CompareMethod::Metadata
+    };
+    diff.changes = compare_layers(layers_a.iter().copied(), layers_b.iter().copied());
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tar;
+
+    fn layer(files: &[(&str, &[u8])], hashed: bool) -> LayerFiles {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, data) in files {
+            builder.append_bytes(path, data).unwrap();
+        }
+        let bytes = builder.finish().unwrap();
+        let descriptor = LayerFiles { digest: String::new(), size: 0, entries: vec![], whiteouts: vec![], opaque_dirs: vec![] }.descriptor();
+        let scan = if hashed { layers::scan_layer_hashed } else { layers::scan_layer };
+        scan(&mut &bytes[..], &descriptor, 0).unwrap()
+    }
+
+    fn statuses(changes: &[FileChange]) -> Vec<(ChangeStatus, &str)> {
+        changes.iter().map(|c| (c.status, c.path.as_str())).collect()
+    }
+
+    #[test]
+    fn detects_added_removed_and_resized_files() {
+        let a = [layer(&[("etc/a", b"1"), ("etc/b", b"22")], true)];
+        let b = [layer(&[("etc/b", b"333"), ("etc/c", b"4")], true)];
+        let changes = compare_layers(&a, &b);
+        assert_eq!(
+            statuses(&changes),
+            [(ChangeStatus::Removed, "etc/a"), (ChangeStatus::Modified, "etc/b"), (ChangeStatus::Added, "etc/c")]
+        );
+        assert_eq!(changes[1].delta, 1);
+    }
+
+    #[test]
+    fn same_size_content_change_needs_hashes() {
+        let hashed = compare_layers(&[layer(&[("app.conf", b"port=1")], true)], &[layer(&[("app.conf", b"port=2")], true)]);
+        assert_eq!(statuses(&hashed), [(ChangeStatus::Modified, "app.conf")]);
+        assert_eq!(hashed[0].delta, 0);
+
+        let unchanged = compare_layers(&[layer(&[("app.conf", b"port=1")], true)], &[layer(&[("app.conf", b"port=1")], true)]);
+        assert!(unchanged.is_empty());
+
+        let headers_only = [layer(&[("app.conf", b"port=1")], false)];
+        assert_eq!(compare_method(&headers_only), CompareMethod::Metadata);
+        assert!(compare_layers(&headers_only, &[layer(&[("app.conf", b"port=2")], false)]).is_empty());
+    }
+
+    #[test]
+    fn formats_deltas() {
+        assert_eq!(format_delta(0), "0 B");
+        assert!(format_delta(2048).starts_with('+'));
+        assert!(format_delta(-300).starts_with('-'));
+    }
+
+    #[test]
+    fn upper_layers_decide_only_when_both_sides_rewrite_the_same_paths() {
+        let a = layer(&[("app/bin", b"v1"), ("app/conf", b"1")], true);
+        let b = layer(&[("app/bin", b"v2"), ("app/conf", b"1")], true);
+        assert!(upper_layers_decide(&[&a], &[&b]));
+        // 한쪽에만 있는 경로는 바탕에 있었는지에 따라 추가/수정이 갈림
+        let extra = layer(&[("app/bin", b"v2"), ("app/conf", b"1"), ("etc/os-release", b"x")], true);
+        assert!(!upper_layers_decide(&[&a], &[&extra]));
+        assert!(!upper_layers_decide(&[], &[&b]));
+        let whiteout = layer(&[("app/bin", b"v2"), ("app/conf", b"1"), ("etc/.wh.motd", b"")], true);
+        assert!(!upper_layers_decide(&[&a], &[&whiteout]));
+    }
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tabled::Tabled;
+
+use crate::cache;
+use crate::time;
+
+/// 태그가 한 다이제스트를 가리키는 동안의 관찰 구간 (유닉스 초)
+#[derive(Debug, Clone, Serialize, Deserialize, Tabled)]
+pub struct Observation {
+    #[tabled(rename = "Digest")]
+    pub digest: String,
+    #[tabled(rename = "First seen", display_with = "display_time")]
+    pub first_seen: i64,
+    #[tabled(rename = "Last seen", display_with = "display_time")]
+    pub last_seen: i64,
+}
+
+fn display_time(secs: &i64) -> String {
+    time::format_unix(*secs)
+}
+
+/// 기록 위치: $XDG_DATA_HOME/repo-tree/history (없으면 ~/.local/share/repo-tree/history).
+/// 캐시와 달리 지우면 되살릴 수 없으므로 캐시 디렉터리에 두지 않음
+pub fn history_root() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")))?;
+    Some(base.join("repo-tree").join("history"))
+}
+
+fn timeline_path(registry_url: &str, repository: &str, tag: &str) -> Option<PathBuf> {
+    if !cache::is_safe_name(repository) || !cache::is_safe_name(tag) || tag.contains('/') {
+        return None;
+    }
+    Some(history_root()?.join(cache::registry_key(registry_url)).join(repository).join(format!("{}.json", tag)))
+}
+
+/// 태그의 다이제스트 변화 기록 (오래된 것부터)
+pub fn timeline(registry_url: &str, repository: &str, tag: &str) -> Vec<Observation> {
+    timeline_path(registry_url, repository, tag)
+        .and_then(|path| std::fs::read(path).ok())
+        .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+        .unwrap_or_default()
+}
+
+/// registry에서 태그가 `digest`를 가리키는 것을 확인했을 때 기록합니다.
+/// 마지막 구간과 같은 다이제스트면 last_seen만 늘림
+pub fn observe(registry_url: &str, repository: &str, tag: &str, digest: &str) {
+    if tag.starts_with("sha256:") || cache::is_offline() {
+        return;
+    }
+    let now = time::unix_now();
+    let mut observations = timeline(registry_url, repository, tag);
+    match observations.last_mut() {
+        Some(last) if last.digest == digest => last.last_seen = now,
+        _ => observations.push(Observation { digest: digest.to_string(), first_seen: now, last_seen: now }),
+    }
+    if let Ok(bytes) = serde_json::to_vec_pretty(&observations) {
+        cache::write_atomic(timeline_path(registry_url, repository, tag), &bytes);
+    }
+}
+
+/// `at` 시각에 태그가 가리키던 것으로 보이는 구간: 그 전에 처음 본 것 중 가장 최근
+pub fn at(observations: &[Observation], at: i64) -> Option<&Observation> {
+    observations.iter().rev().find(|o| o.first_seen <= at)
+}
+
+/// 상세 팝업에 넣는 타임라인 (최근 것부터)
+pub fn lines(observations: &[Observation]) -> Vec<String> {
+    let mut lines = vec!["Tag history (first seen → last seen):".to_string()];
+    for (i, observation) in observations.iter().enumerate().rev() {
+        let marker = if i + 1 == observations.len() { "* " } else { "  " };
+        lines.push(format!(
+            "{}{}  {} → {}",
+            marker,
+            observation.digest,
+            time::format_unix(observation.first_seen),
+            time::format_unix(observation.last_seen)
+        ));
+    }
+    lines
+}
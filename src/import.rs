@@ -0,0 +1,460 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::digest::{self, DigestReader};
+use crate::export::{ANNOTATION_IMAGE_NAME, ANNOTATION_REF_NAME, ANNOTATION_SOURCE_REGISTRY};
+use crate::registry::{self, Descriptor, Manifest};
+use crate::tar;
+
+/// 가져올 레이아웃(디렉터리 또는 tar 파일)
+pub enum LayoutSource {
+    Dir(PathBuf),
+    Tar { path: PathBuf, entries: HashMap<String, (u64, u64)> },
+}
+
+impl LayoutSource {
+    pub fn open(path: &Path) -> Result<LayoutSource, Box<dyn StdError>> {
+        if path.is_dir() {
+            return Ok(LayoutSource::Dir(path.to_path_buf()));
+        }
+        // tar는 한 번 훑어서 엔트리별 위치를 기록해 두고 이후에는 seek으로 읽음
+        let mut archive = tar::Archive::new(BufReader::new(File::open(path)?));
+        let mut entries = HashMap::new();
+        while let Some(entry) = archive.next_entry()? {
+            if entry.is_file() {
+                entries.insert(entry.path, (entry.data_offset, entry.size));
+            }
+        }
+        Ok(LayoutSource::Tar { path: path.to_path_buf(), entries })
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        match self {
+            LayoutSource::Dir(root) => root.join(name).is_file(),
+            LayoutSource::Tar { entries, .. } => entries.contains_key(&tar::normalize_path(name)),
+        }
+    }
+
+    pub fn reader(&self, name: &str) -> Result<(Box<dyn Read>, u64), Box<dyn StdError>> {
+        match self {
+            LayoutSource::Dir(root) => {
+                let file = File::open(root.join(name))?;
+                let size = file.metadata()?.len();
+                Ok((Box::new(BufReader::new(file)), size))
+            }
+            LayoutSource::Tar { path, entries } => {
+                let &(offset, size) = entries
+                    .get(&tar::normalize_path(name))
+                    .ok_or_else(|| format!("'{}' not found in {}", name, path.display()))?;
+                let mut file = File::open(path)?;
+                file.seek(SeekFrom::Start(offset))?;
+                Ok((Box::new(BufReader::new(file).take(size)), size))
+            }
+        }
+    }
+
+    pub fn read(&self, name: &str) -> Result<Vec<u8>, Box<dyn StdError>> {
+        let (mut reader, size) = self.reader(name)?;
+        let mut data = Vec::with_capacity(size as usize);
+        reader.read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ImportOptions {
+    /// 모든 이미지를 이 저장소 이름으로 올림
+    pub repository: Option<String>,
+    /// 태그 덮어쓰기 (이미지가 하나일 때만 허용)
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub images: usize,
+    pub images_present: usize,
+    pub blobs_uploaded: usize,
+    pub blobs_present: usize,
+    pub bytes_uploaded: u64,
+    pub manifests_pushed: usize,
+}
+
+/// 레이아웃 안의 최상위 이미지 하나
+pub struct LayoutImage {
+    pub name: Option<(String, String)>,
+    /// org.opencontainers.image.ref.name이 저장소 없이 태그만 담은 경우 그 태그
+    pub tag: Option<String>,
+    pub root: Descriptor,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct DockerArchiveEntry {
+    config: String,
+    #[serde(default)]
+    repo_tags: Option<Vec<String>>,
+    layers: Vec<String>,
+}
+
+/// blob 다이제스트를 레이아웃 안의 경로 또는 메모리 데이터로 찾아 주는 저장소
+pub struct Layout {
+    pub source: LayoutSource,
+    pub images: Vec<LayoutImage>,
+    // docker save(구 형식)는 blobs/ 구조가 아니므로 다이제스트 → 경로 매핑을 따로 보관
+    blob_paths: HashMap<String, String>,
+    synthetic: HashMap<String, Vec<u8>>,
+}
+
+impl Layout {
+    pub fn open(path: &Path) -> Result<Layout, Box<dyn StdError>> {
+        let source = LayoutSource::open(path)?;
+        let mut layout = Layout { source, images: Vec::new(), blob_paths: HashMap::new(), synthetic: HashMap::new() };
+
+        if layout.source.exists("index.json") {
+            let index: Manifest = serde_json::from_slice(&layout.source.read("index.json")?)?;
+            let source_registry = index.annotations.get(ANNOTATION_SOURCE_REGISTRY).map(String::as_str);
+            for root in index.manifests {
+                let ref_name = root.annotations.get(ANNOTATION_REF_NAME);
+                let name = root
+                    .annotations
+                    .get(ANNOTATION_IMAGE_NAME)
+                    .and_then(|n| split_image_name(n, source_registry))
+                    .or_else(|| ref_name.and_then(|r| registry::parse_reference(r).ok()));
+                // OCI 명세상 ref.name은 보통 "1.0"처럼 태그만 담음: 저장소는 --repository로 받음
+                let tag = ref_name.filter(|r| name.is_none() && is_bare_tag(r)).cloned();
+                layout.images.push(LayoutImage { name, tag, root });
+            }
+        } else if layout.source.exists("manifest.json") {
+            layout.load_docker_archive()?;
+        } else {
+            return Err(format!("{} is neither an OCI image layout nor a docker archive", path.display()).into());
+        }
+        Ok(layout)
+    }
+
+    // `docker save` 형식: 다이제스트를 계산해서 docker v2 매니페스트를 만들어 냄
+    fn load_docker_archive(&mut self) -> Result<(), Box<dyn StdError>> {
+        let entries: Vec<DockerArchiveEntry> = serde_json::from_slice(&self.source.read("manifest.json")?)?;
+        for entry in entries {
+            let config = self.describe_file(&entry.config, registry::MEDIA_TYPE_DOCKER_CONFIG)?;
+            let mut layers = Vec::new();
+            for layer in &entry.layers {
+                let media_type = if self.is_gzip(layer)? { registry::MEDIA_TYPE_DOCKER_LAYER_GZIP } else { registry::MEDIA_TYPE_DOCKER_LAYER };
+                layers.push(self.describe_file(layer, media_type)?);
+            }
+            let manifest = Manifest {
+                schema_version: 2,
+                media_type: Some(registry::MEDIA_TYPE_DOCKER_MANIFEST.to_string()),
+                artifact_type: None,
+                config: Some(config),
+                layers,
+                manifests: Vec::new(),
+                subject: None,
+                annotations: Default::default(),
+            };
+            let bytes = serde_json::to_vec(&manifest)?;
+            let root = Descriptor {
+                media_type: registry::MEDIA_TYPE_DOCKER_MANIFEST.to_string(),
+                digest: digest::sha256_digest(&bytes),
+                size: bytes.len() as u64,
+                platform: None,
+                artifact_type: None,
+                annotations: Default::default(),
+            };
+            self.synthetic.insert(root.digest.clone(), bytes);
+
+            let repo_tags = entry.repo_tags.unwrap_or_default();
+            if repo_tags.is_empty() {
+                self.images.push(LayoutImage { name: None, tag: None, root });
+            } else {
+                for repo_tag in repo_tags {
+                    self.images.push(LayoutImage { name: split_image_name(&repo_tag, None), tag: None, root: root.clone() });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn describe_file(&mut self, path: &str, media_type: &str) -> Result<Descriptor, Box<dyn StdError>> {
+        let (reader, _) = self.source.reader(path)?;
+        let mut reader = DigestReader::new(reader);
+        io::copy(&mut reader, &mut io::sink())?;
+        let (size, digest) = reader.finish();
+        self.blob_paths.insert(digest.clone(), path.to_string());
+        Ok(Descriptor {
+            media_type: media_type.to_string(),
+            digest,
+            size,
+            platform: None,
+            artifact_type: None,
+            annotations: Default::default(),
+        })
+    }
+
+    fn is_gzip(&self, path: &str) -> Result<bool, Box<dyn StdError>> {
+        let (mut reader, _) = self.source.reader(path)?;
+        let mut magic = [0u8; 2];
+        Ok(reader.read_exact(&mut magic).is_ok() && magic == [0x1f, 0x8b])
+    }
+
+    fn blob_path(&self, digest: &str) -> Result<String, Box<dyn StdError>> {
+        if let Some(path) = self.blob_paths.get(digest) {
+            return Ok(path.clone());
+        }
+        let (algorithm, hex) = digest::split_digest(digest).ok_or_else(|| format!("invalid digest '{}'", digest))?;
+        Ok(format!("blobs/{}/{}", algorithm, hex))
+    }
+
+    pub fn has_blob(&self, digest: &str) -> bool {
+        self.synthetic.contains_key(digest) || self.blob_path(digest).map(|p| self.source.exists(&p)).unwrap_or(false)
+    }
+
+    pub fn blob_reader(&self, descriptor: &Descriptor) -> Result<Box<dyn Read + '_>, Box<dyn StdError>> {
+        if let Some(bytes) = self.synthetic.get(&descriptor.digest) {
+            return Ok(Box::new(bytes.as_slice()));
+        }
+        Ok(self.source.reader(&self.blob_path(&descriptor.digest)?)?.0)
+    }
+
+    /// 매니페스트처럼 작은 blob을 읽고 다이제스트를 검증합니다.
+    pub fn read_blob(&self, descriptor: &Descriptor) -> Result<Vec<u8>, Box<dyn StdError>> {
+        let mut bytes = Vec::new();
+        self.blob_reader(descriptor)?.read_to_end(&mut bytes)?;
+        let actual = digest::sha256_digest(&bytes);
+        if actual != descriptor.digest {
+            return Err(format!("blob {} is corrupted in layout (got {})", descriptor.digest, actual).into());
+        }
+        Ok(bytes)
+    }
+}
+
+/// "registry.host:5000/group/app:1.0" → ("group/app", "1.0")
+/// 내보낸 registry(`source_registry`)가 기록돼 있으면 첫 조각이 그 주소일 때만 떼어 냄.
+/// 기록이 없는 아카이브(docker save 등)는 docker 참조 규칙대로 '.'이나 ':'가 있거나 localhost인 첫 조각을 주소로 봄
+pub fn split_image_name(name: &str, source_registry: Option<&str>) -> Option<(String, String)> {
+    let without_host = match (name.split_once('/'), source_registry) {
+        (Some((first, rest)), Some(host)) if first == host => rest,
+        (_, Some(_)) => name,
+        (Some((first, rest)), None) if first.contains('.') || first.contains(':') || first == "localhost" => rest,
+        _ => name,
+    };
+    registry::parse_reference(without_host).ok()
+}
+
+/// 저장소 없이 태그만 있는 참조인지 (태그 문법: [A-Za-z0-9_][A-Za-z0-9_.-]{0,127})
+fn is_bare_tag(reference: &str) -> bool {
+    let mut chars = reference.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphanumeric() || c == '_')
+        && reference.len() <= 128
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+pub fn manifest_media_type(descriptor: &Descriptor, bytes: &[u8]) -> String {
+    if !descriptor.media_type.is_empty() {
+        return descriptor.media_type.clone();
+    }
+    serde_json::from_slice::<Manifest>(bytes)
+        .ok()
+        .and_then(|m| m.media_type)
+        .unwrap_or_else(|| registry::MEDIA_TYPE_OCI_MANIFEST.to_string())
+}
+
+pub struct Importer<'a> {
+    pub layout: &'a Layout,
+    pub summary: ImportSummary,
+}
+
+impl<'a> Importer<'a> {
+    pub fn new(layout: &'a Layout) -> Self {
+        Importer { layout, summary: ImportSummary::default() }
+    }
+
+    pub async fn push_blob(&mut self, repository: &str, descriptor: &Descriptor) -> Result<(), Box<dyn StdError>> {
+        if registry::blob_exists(repository, &descriptor.digest).await? {
+            self.summary.blobs_present += 1;
+            return Ok(());
+        }
+        let reader = self.layout.blob_reader(descriptor)?;
+        registry::upload_blob(repository, descriptor, reader).await?;
+        self.summary.blobs_uploaded += 1;
+        self.summary.bytes_uploaded += descriptor.size;
+        Ok(())
+    }
+
+    // config와 레이어를 먼저 올린 뒤 매니페스트를 PUT
+    async fn push_image_manifest(&mut self, repository: &str, reference: &str, descriptor: &Descriptor) -> Result<(), Box<dyn StdError>> {
+        let bytes = self.layout.read_blob(descriptor)?;
+        let manifest: Manifest = serde_json::from_slice(&bytes)?;
+        if let Some(config) = &manifest.config {
+            self.push_blob(repository, config).await?;
+        }
+        for layer in &manifest.layers {
+            self.push_blob(repository, layer).await?;
+        }
+        registry::push_manifest(repository, reference, &manifest_media_type(descriptor, &bytes), &bytes).await?;
+        self.summary.manifests_pushed += 1;
+        Ok(())
+    }
+
+    /// 최상위 매니페스트(또는 인덱스와 하위 매니페스트)를 지정한 이름으로 올립니다.
+    pub async fn push_root(&mut self, repository: &str, tag: &str, root: &Descriptor) -> Result<(), Box<dyn StdError>> {
+        // 태그가 이미 같은 다이제스트를 가리키면 올릴 것이 없음 (중단 후 재실행 시에도 이어서 진행)
+        if let Ok(existing) = registry::fetch_manifest_raw(repository, tag).await {
+            if existing.digest == root.digest {
+                self.summary.images_present += 1;
+                return Ok(());
+            }
+        }
+
+        let bytes = self.layout.read_blob(root)?;
+        let manifest: Manifest = serde_json::from_slice(&bytes)?;
+        if manifest.is_index() {
+            for child in &manifest.manifests {
+                if !self.layout.has_blob(&child.digest) {
+                    return Err(format!("manifest {} referenced by {} is missing from the layout", child.digest, root.digest).into());
+                }
+                self.push_image_manifest(repository, &child.digest, child).await?;
+            }
+            registry::push_manifest(repository, tag, &manifest_media_type(root, &bytes), &bytes).await?;
+            self.summary.manifests_pushed += 1;
+        } else {
+            self.push_image_manifest(repository, tag, root).await?;
+        }
+        self.summary.images += 1;
+        Ok(())
+    }
+}
+
+/// (저장소, 태그, 최상위 매니페스트)
+pub type ImportTarget = (String, String, Descriptor);
+
+/// 레이아웃의 이미지 이름에 저장소/태그 덮어쓰기 옵션을 적용합니다.
+pub fn resolve_targets(layout: &Layout, options: &ImportOptions) -> Result<Vec<ImportTarget>, Box<dyn StdError>> {
+    if options.tag.is_some() && layout.images.len() > 1 {
+        return Err(format!("--tag can only be used when the archive contains a single image ({} found)", layout.images.len()).into());
+    }
+
+    layout
+        .images
+        .iter()
+        .map(|image| {
+            let (repository, tag) = match (&image.name, &options.repository, &options.tag) {
+                (_, Some(repository), Some(tag)) => (repository.clone(), tag.clone()),
+                (Some((repo, tag)), repository, override_tag) => {
+                    (repository.clone().unwrap_or_else(|| repo.clone()), override_tag.clone().unwrap_or_else(|| tag.clone()))
+                }
+                (None, Some(repository), None) => match &image.tag {
+                    Some(tag) => (repository.clone(), tag.clone()),
+                    None => return Err(format!("image {} has no tag in the archive; use --tag", image.root.digest).into()),
+                },
+                (None, None, _) => match &image.tag {
+                    Some(tag) => {
+                        return Err(format!("image {} is tagged '{}' in the archive but has no repository; use --repository", image.root.digest, tag).into())
+                    }
+                    None => return Err(format!("image {} has no name in the archive; use --repository and --tag", image.root.digest).into()),
+                },
+            };
+            Ok((repository, tag, image.root.clone()))
+        })
+        .collect()
+}
+
+/// OCI 이미지 레이아웃 또는 docker-archive를 registry로 올립니다.
+pub async fn import_archive(path: &Path, options: &ImportOptions) -> Result<ImportSummary, Box<dyn StdError>> {
+    if !path.exists() {
+        return Err(format!("{} does not exist", path.display()).into());
+    }
+    let layout = Layout::open(path)?;
+    let targets = resolve_targets(&layout, options)?;
+
+    let mut importer = Importer::new(&layout);
+    for (repository, tag, root) in targets {
+        importer.push_root(&repository, &tag, &root).await?;
+        log::info!("imported {}:{}", repository, tag);
+    }
+    Ok(importer.summary)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(images: Vec<LayoutImage>) -> Layout {
+        Layout { source: LayoutSource::Dir(PathBuf::new()), images, blob_paths: HashMap::new(), synthetic: HashMap::new() }
+    }
+
+    fn image(name: Option<(&str, &str)>, tag: Option<&str>) -> LayoutImage {
+        LayoutImage {
+            name: name.map(|(r, t)| (r.to_string(), t.to_string())),
+            tag: tag.map(str::to_string),
+            root: Descriptor {
+                media_type: registry::MEDIA_TYPE_OCI_MANIFEST.to_string(),
+                digest: format!("sha256:{}", "0".repeat(64)),
+                size: 0,
+                platform: None,
+                artifact_type: None,
+                annotations: Default::default(),
+            },
+        }
+    }
+
+    fn options(repository: Option<&str>, tag: Option<&str>) -> ImportOptions {
+        ImportOptions { repository: repository.map(str::to_string), tag: tag.map(str::to_string) }
+    }
+
+    fn targets(layout: &Layout, options: &ImportOptions) -> Vec<(String, String)> {
+        resolve_targets(layout, options).unwrap().into_iter().map(|(r, t, _)| (r, t)).collect()
+    }
+
+    #[test]
+    fn bare_tags() {
+        assert!(is_bare_tag("1.0"));
+        assert!(is_bare_tag("latest"));
+        assert!(is_bare_tag("v1.2.3-rc_1"));
+        assert!(!is_bare_tag("app:1.0"));
+        assert!(!is_bare_tag("grp/app"));
+        assert!(!is_bare_tag(".hidden"));
+        assert!(!is_bare_tag(""));
+    }
+
+    #[test]
+    fn split_image_names() {
+        assert_eq!(split_image_name("docker.io/library/nginx:1.25", None), Some(("library/nginx".into(), "1.25".into())));
+        assert_eq!(split_image_name("localhost:5000/app:1", None), Some(("app".into(), "1".into())));
+        assert_eq!(split_image_name("grp/app:1.0", None), Some(("grp/app".into(), "1.0".into())));
+        // 내보낸 registry가 기록돼 있으면 그 주소만 떼어 냄: 점이 든 그룹 이름은 저장소의 일부
+        let host = Some("registry.example:5000");
+        assert_eq!(split_image_name("registry.example:5000/grp/app:1.0", host), Some(("grp/app".into(), "1.0".into())));
+        assert_eq!(split_image_name("my.team/app:1", host), Some(("my.team/app".into(), "1".into())));
+        assert_eq!(split_image_name("igloo.airgap.registry/my.team/app:1", Some("igloo.airgap.registry")), Some(("my.team/app".into(), "1".into())));
+    }
+
+    #[test]
+    fn tag_only_ref_name_uses_repository_option() {
+        let layout = layout(vec![image(None, Some("1.0"))]);
+        assert_eq!(targets(&layout, &options(Some("grp/app"), None)), vec![("grp/app".into(), "1.0".into())]);
+        assert_eq!(targets(&layout, &options(Some("grp/app"), Some("2.0"))), vec![("grp/app".into(), "2.0".into())]);
+        let err = resolve_targets(&layout, &options(None, None)).unwrap_err().to_string();
+        assert!(err.contains("--repository"), "{}", err);
+    }
+
+    #[test]
+    fn named_images_accept_overrides() {
+        let layout = layout(vec![image(Some(("grp/app", "1.0")), None), image(Some(("grp/db", "2.0")), None)]);
+        assert_eq!(targets(&layout, &options(None, None)), vec![("grp/app".into(), "1.0".into()), ("grp/db".into(), "2.0".into())]);
+        assert_eq!(targets(&layout, &options(Some("mirror/x"), None)), vec![("mirror/x".into(), "1.0".into()), ("mirror/x".into(), "2.0".into())]);
+        assert!(resolve_targets(&layout, &options(None, Some("3.0"))).is_err());
+    }
+
+    #[test]
+    fn unnamed_images_need_repository_and_tag() {
+        let layout = layout(vec![image(None, None)]);
+        assert!(resolve_targets(&layout, &options(Some("grp/app"), None)).is_err());
+        assert_eq!(targets(&layout, &options(Some("grp/app"), Some("1.0"))), vec![("grp/app".into(), "1.0".into())]);
+    }
+}
@@ -0,0 +1,175 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::error::Error as StdError;
+
+use crate::layers;
+use crate::metadiff::{self, ImageMetadata};
+use crate::provenance;
+use crate::registry::{self, Manifest, Platform};
+use crate::ui::{CompatibilityRow, LayerInfo};
+
+/// `inspect` 명령의 출력: 매니페스트와 config 요약
+#[derive(Debug, Clone, Serialize)]
+pub struct Inspection {
+    pub digest: String,
+    pub media_type: String,
+    /// 태그가 인덱스면 자식 매니페스트 설명
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub manifests: Vec<String>,
+    /// 인덱스에서 고른 플랫폼 매니페스트 다이제스트
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform_digest: Option<String>,
+    pub platform: Option<String>,
+    pub created: Option<String>,
+    pub config_digest: Option<String>,
+    pub size: u64,
+    #[serde(flatten)]
+    pub metadata: ImageMetadata,
+}
+
+/// 태그가 가리키는 이미지의 config blob을 JSON으로 읽습니다. (인덱스면 `platform`의 이미지)
+pub async fn fetch_config(image: &str, reference: &str, platform: &Platform) -> Result<Value, Box<dyn StdError>> {
+    let (_, manifest) = registry::fetch_image_manifest(image, reference, platform).await?;
+    let descriptor = manifest.config.as_ref().ok_or_else(|| format!("{}:{} has no config blob", image, reference))?;
+    Ok(serde_json::from_slice(&registry::fetch_blob(image, descriptor, Vec::new()).await?)?)
+}
+
+/// config의 history 중 레이어를 만든 항목 (empty_layer가 아닌 것)
+fn layer_history(config: &Value) -> Vec<&Value> {
+    let history = config.get("history").and_then(|h| h.as_array()).map(|h| h.iter()).into_iter().flatten();
+    history.filter(|h| !h.get("empty_layer").and_then(|e| e.as_bool()).unwrap_or(false)).collect()
+}
+
+/// 레이어마다 크기와 그 레이어를 만든 명령
+pub fn layer_infos(manifest: &Manifest, config: &Value) -> Vec<LayerInfo> {
+    let history = layer_history(config);
+    manifest
+        .layers
+        .iter()
+        .enumerate()
+        .map(|(i, layer)| LayerInfo {
+            blob_sum: layer.digest.clone(),
+            media_type: layer.media_type.clone(),
+            size: layers::format_size(layer.size),
+            bytes: layer.size,
+            command: history.get(i).and_then(|h| h.get("created_by")).and_then(|c| c.as_str()).unwrap_or_default().to_string(),
+        })
+        .collect()
+}
+
+pub async fn fetch_layers(image: &str, reference: &str, platform: &Platform) -> Result<Vec<LayerInfo>, Box<dyn StdError>> {
+    let (_, manifest) = registry::fetch_image_manifest(image, reference, platform).await?;
+    let config = match &manifest.config {
+        Some(descriptor) => serde_json::from_slice(&registry::fetch_blob(image, descriptor, Vec::new()).await?)?,
+        None => Value::Null,
+    };
+    Ok(layer_infos(&manifest, &config))
+}
+
+/// schema2/OCI config의 history를 schema1 v1Compatibility와 같은 행으로
+fn history_rows(config: &Value) -> Vec<CompatibilityRow> {
+    let text = |value: &Value, key: &str| value.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let os = text(config, "os");
+    let empty = vec![];
+    config
+        .get("history")
+        .and_then(|h| h.as_array())
+        .unwrap_or(&empty)
+        .iter()
+        .map(|entry| CompatibilityRow {
+            id: String::new(),
+            parent: String::new(),
+            os: os.clone(),
+            created: text(entry, "created"),
+            cmd: text(entry, "created_by"),
+            config: String::new(),
+        })
+        .collect()
+}
+
+/// 이미지 빌드 이력. schema1 매니페스트는 v1Compatibility, 그 외에는 config의 history
+pub async fn fetch_history(image: &str, reference: &str, platform: &Platform) -> Result<Vec<CompatibilityRow>, Box<dyn StdError>> {
+    let top: Value = serde_json::from_str(&registry::fetch_manifest(image, reference).await?)?;
+    if top.get("schemaVersion").and_then(|v| v.as_u64()) == Some(1) {
+        return Ok(registry::parse_v1compatibility_fields(&top).0);
+    }
+    Ok(history_rows(&fetch_config(image, reference, platform).await?))
+}
+
+pub async fn inspect(image: &str, reference: &str, platform: &Platform) -> Result<Inspection, Box<dyn StdError>> {
+    let top = registry::fetch_manifest_raw(image, reference).await?;
+    let top_manifest = top.parse()?;
+    let manifests = top_manifest.manifests.iter().map(provenance::index_entry_label).collect();
+
+    let (raw, manifest) = registry::fetch_image_manifest(image, reference, platform).await?;
+    let config = match &manifest.config {
+        Some(descriptor) => serde_json::from_slice(&registry::fetch_blob(image, descriptor, Vec::new()).await?)?,
+        None => Value::Null,
+    };
+    let text = |key: &str| config.get(key).and_then(|v| v.as_str()).map(|v| v.to_string());
+    let platform = match (text("os"), text("architecture")) {
+        (Some(os), Some(arch)) => Some(match text("variant") {
+            Some(variant) => format!("{}/{}/{}", os, arch, variant),
+            None => format!("{}/{}", os, arch),
+        }),
+        _ => None,
+    };
+    Ok(Inspection {
+        digest: top.digest.clone(),
+        media_type: top.media_type.clone(),
+        manifests,
+        platform_digest: (raw.digest != top.digest).then(|| raw.digest.clone()),
+        platform,
+        created: text("created"),
+        config_digest: manifest.config.as_ref().map(|c| c.digest.clone()),
+        size: manifest.layers.iter().map(|l| l.size).sum(),
+        metadata: metadiff::ImageMetadata::from_config(format!("{}:{}", image, reference), &manifest, &config)?,
+    })
+}
+
+/// 사람이 읽는 형식의 줄 목록
+pub fn lines(inspection: &Inspection) -> Vec<String> {
+    let field = |name: &str, value: &str| format!("{:<14}{}", format!("{}:", name), value);
+    let mut lines = vec![
+        field("Reference", &inspection.metadata.reference),
+        field("Digest", &inspection.digest),
+        field("Media type", &inspection.media_type),
+    ];
+    if !inspection.manifests.is_empty() {
+        lines.push("Manifests:".to_string());
+        lines.extend(inspection.manifests.iter().map(|m| format!("  {}", m)));
+    }
+    if let Some(digest) = &inspection.platform_digest {
+        lines.push(field("Image digest", digest));
+    }
+    let metadata = &inspection.metadata;
+    let optional = [
+        ("Platform", inspection.platform.clone()),
+        ("Created", inspection.created.clone()),
+        ("Config", inspection.config_digest.clone()),
+        ("User", Some(metadata.user.clone()).filter(|u| !u.is_empty())),
+        ("Entrypoint", (!metadata.entrypoint.is_empty()).then(|| metadata.entrypoint.join(" "))),
+        ("Cmd", (!metadata.cmd.is_empty()).then(|| metadata.cmd.join(" "))),
+        ("Ports", (!metadata.exposed_ports.is_empty()).then(|| metadata.exposed_ports.join(", "))),
+    ];
+    for (name, value) in optional {
+        if let Some(value) = value {
+            lines.push(field(name, &value));
+        }
+    }
+    lines.push(field("Size", &format!("{} in {} layers", layers::format_size(inspection.size), metadata.layers.len())));
+    if !metadata.env.is_empty() {
+        lines.push("Env:".to_string());
+        lines.extend(metadata.env.iter().map(|e| format!("  {}", e)));
+    }
+    if !metadata.labels.is_empty() {
+        lines.push("Labels:".to_string());
+        lines.extend(metadata.labels.iter().map(|(k, v)| format!("  {}={}", k, v)));
+    }
+    lines.push("Layers:".to_string());
+    for layer in &metadata.layers {
+        let size = layer.size.map(layers::format_size).unwrap_or_else(|| "-".to_string());
+        lines.push(format!("  {}  {:>10}", layer.digest, size));
+    }
+    lines
+}
@@ -0,0 +1,348 @@
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::error::Error as StdError;
+use std::io::{self, Read};
+
+use crate::layers;
+use crate::registry::{self, Platform};
+use crate::sbom::{self, Package};
+use crate::tar;
+use crate::zip;
+
+/// 이보다 큰 메타데이터 파일/jar는 읽지 않음
+const MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;
+/// 중첩 jar (Spring Boot fat jar 등)를 따라 들어가는 깊이
+const MAX_JAR_DEPTH: usize = 2;
+
+pub const SOURCE_LAYER_SCAN: &str = "layer scan";
+
+/// 패키지 정보를 담고 있어 내용을 읽어야 하는 경로인지
+fn is_package_metadata(path: &str) -> bool {
+    path == "var/lib/dpkg/status"
+        || (path.starts_with("var/lib/dpkg/status.d/") && !path.ends_with(".md5sums"))
+        || path == "lib/apk/db/installed"
+        || path.ends_with(".dist-info/METADATA")
+        || path.ends_with(".egg-info/PKG-INFO")
+        || path.ends_with(".egg-info")
+        || path.ends_with(".jar")
+        || path.ends_with(".war")
+        || path == "package.json"
+        || path.ends_with("/package.json")
+        || path == "etc/os-release"
+        || path == "usr/lib/os-release"
+}
+
+/// 레이어 하나에서 찾은 변경 사항
+#[derive(Default)]
+struct LayerFindings {
+    files: Vec<(String, Vec<u8>)>,
+    /// 메타데이터가 아닌 항목으로 덮어쓴 경로 (심볼릭 링크, 너무 큰 파일 등)
+    shadowed: Vec<String>,
+    whiteouts: Vec<String>,
+    opaque_dirs: Vec<String>,
+}
+
+fn scan_layer(reader: &mut dyn Read) -> io::Result<LayerFindings> {
+    let stream = layers::decompressed(reader)?;
+    let mut archive = tar::Archive::new(stream);
+    let mut findings = LayerFindings::default();
+    while let Some(header) = archive.next_entry()? {
+        let path = header.path.trim_end_matches('/').to_string();
+        if path.is_empty() {
+            continue;
+        }
+        match layers::whiteout_target(&path) {
+            Some((dir, None)) => findings.opaque_dirs.push(dir),
+            Some((dir, Some(name))) => findings.whiteouts.push(layers::join_path(&dir, &name)),
+            None if header.is_file() && header.size <= MAX_FILE_SIZE && is_package_metadata(&path) => {
+                let mut data = Vec::with_capacity(header.size as usize);
+                archive.data().read_to_end(&mut data)?;
+                findings.files.push((path, data));
+            }
+            // 디렉터리는 하위 레이어와 합쳐지므로 파일만 가림
+            None if header.entry_type != tar::TYPE_DIRECTORY => findings.shadowed.push(path),
+            None => {}
+        }
+    }
+    io::copy(&mut archive.into_inner(), &mut io::sink())?;
+    Ok(findings)
+}
+
+/// 레이어를 순서대로 겹쳐 최종 이미지에 남는 메타데이터 파일만 모읍니다.
+pub async fn collect_metadata_files(image: &str, reference: &str, platform: &Platform) -> Result<BTreeMap<String, Vec<u8>>, Box<dyn StdError>> {
+    let (_, manifest) = registry::fetch_image_manifest(image, reference, platform).await?;
+    let mut files: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+    for layer in &manifest.layers {
+        let findings = registry::read_blob_with(image, layer, scan_layer).await?;
+        for dir in &findings.opaque_dirs {
+            layers::remove_descendants(&mut files, dir);
+        }
+        for path in findings.whiteouts.iter().chain(&findings.shadowed) {
+            files.remove(path);
+            layers::remove_descendants(&mut files, path);
+        }
+        files.extend(findings.files);
+    }
+    Ok(files)
+}
+
+/// "Key: value" 형식 문단을 읽습니다. 공백으로 시작하는 줄은 앞 값에 이어 붙임
+fn parse_stanza(text: &str) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for line in text.lines() {
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some((_, value)) = fields.last_mut() {
+                value.push('\n');
+                value.push_str(line.trim());
+            }
+        } else if let Some((key, value)) = line.split_once(':') {
+            fields.push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    fields
+}
+
+fn field<'a>(fields: &'a [(String, String)], key: &str) -> &'a str {
+    fields.iter().find(|(k, _)| k.eq_ignore_ascii_case(key)).map(|(_, v)| v.as_str()).unwrap_or("")
+}
+
+fn package(kind: &str, name: &str, version: &str, license: &str, purl: String) -> Package {
+    Package { name: name.to_string(), version: version.to_string(), kind: kind.to_string(), license: license.to_string(), purl }
+}
+
+/// 배포판 패키지의 purl 네임스페이스와 qualifier
+struct Distro {
+    /// os-release의 ID
+    id: String,
+    /// os-release의 VERSION_ID
+    version: Option<String>,
+}
+
+impl Distro {
+    fn from_files(files: &BTreeMap<String, Vec<u8>>, default_id: &str) -> Distro {
+        let text = files.get("etc/os-release").or_else(|| files.get("usr/lib/os-release")).map(|data| String::from_utf8_lossy(data).into_owned()).unwrap_or_default();
+        let value = |key: &str| text.lines().find_map(|line| line.strip_prefix(key)).map(|v| v.trim_matches('"').to_string()).filter(|v| !v.is_empty());
+        Distro { id: value("ID=").unwrap_or_else(|| default_id.to_string()), version: value("VERSION_ID=") }
+    }
+
+    /// "pkg:deb/debian/curl@7.88.1-10?arch=amd64&distro=debian-12". distro는 배포판 릴리스별 advisory를 고르는 데 씀
+    fn purl(&self, kind: &str, name: &str, version: &str, arch: &str) -> String {
+        let mut qualifiers = Vec::new();
+        if !arch.is_empty() {
+            qualifiers.push(format!("arch={}", arch));
+        }
+        if let Some(release) = &self.version {
+            qualifiers.push(format!("distro={}-{}", self.id, release));
+        }
+        let purl = format!("pkg:{}/{}/{}@{}", kind, self.id, name, version);
+        if qualifiers.is_empty() { purl } else { format!("{}?{}", purl, qualifiers.join("&")) }
+    }
+}
+
+fn dpkg_packages(text: &str, distro: &Distro, out: &mut Vec<Package>) {
+    for stanza in text.split("\n\n") {
+        let fields = parse_stanza(stanza);
+        let name = field(&fields, "Package");
+        let status = field(&fields, "Status");
+        // distroless의 status.d 파일에는 Status가 없음
+        if name.is_empty() || (!status.is_empty() && !status.ends_with(" installed")) {
+            continue;
+        }
+        let version = field(&fields, "Version");
+        let arch = field(&fields, "Architecture");
+        out.push(package("deb", name, version, "", distro.purl("deb", name, version, arch)));
+    }
+}
+
+fn apk_packages(text: &str, distro: &Distro, out: &mut Vec<Package>) {
+    for stanza in text.split("\n\n") {
+        let mut name = "";
+        let mut version = "";
+        let mut arch = "";
+        let mut license = "";
+        for line in stanza.lines() {
+            match line.split_once(':') {
+                Some(("P", value)) => name = value,
+                Some(("V", value)) => version = value,
+                Some(("A", value)) => arch = value,
+                Some(("L", value)) => license = value,
+                _ => {}
+            }
+        }
+        if name.is_empty() {
+            continue;
+        }
+        out.push(package("apk", name, version, license, distro.purl("apk", name, version, arch)));
+    }
+}
+
+/// dist-info METADATA / egg-info PKG-INFO (헤더 부분만 사용)
+fn python_package(text: &str) -> Option<Package> {
+    let header = text.split("\n\n").next().unwrap_or("");
+    let fields = parse_stanza(header);
+    let name = field(&fields, "Name");
+    if name.is_empty() {
+        return None;
+    }
+    let version = field(&fields, "Version");
+    let mut license = field(&fields, "License-Expression").to_string();
+    if license.is_empty() {
+        license = field(&fields, "License").lines().next().unwrap_or("").to_string();
+    }
+    if license.is_empty() || license == "UNKNOWN" {
+        license = fields
+            .iter()
+            .filter(|(k, v)| k == "Classifier" && v.starts_with("License ::"))
+            .filter_map(|(_, v)| v.rsplit(" :: ").next())
+            .collect::<Vec<_>>()
+            .join(" AND ");
+    }
+    let purl_name = name.to_lowercase().replace('_', "-");
+    Some(package("python", name, version, &license, format!("pkg:pypi/{}@{}", purl_name, version)))
+}
+
+fn npm_package(data: &[u8]) -> Option<Package> {
+    let json: Value = serde_json::from_slice(data).ok()?;
+    let name = json.get("name")?.as_str()?;
+    let version = json.get("version")?.as_str()?;
+    let license = match json.get("license") {
+        Some(Value::String(license)) => license.clone(),
+        Some(Value::Object(license)) => license.get("type").and_then(|t| t.as_str()).unwrap_or("").to_string(),
+        _ => json
+            .get("licenses")
+            .and_then(|l| l.as_array())
+            .map(|licenses| licenses.iter().filter_map(|l| l.get("type").and_then(|t| t.as_str())).collect::<Vec<_>>().join(" OR "))
+            .unwrap_or_default(),
+    };
+    let purl_name = name.replacen('@', "%40", 1);
+    Some(package("npm", name, version, &license, format!("pkg:npm/{}@{}", purl_name, version)))
+}
+
+/// 파일 이름에서 "artifact-1.2.3" 형태의 이름과 버전을 나눕니다.
+fn split_jar_name(path: &str) -> (String, String) {
+    let file = path.rsplit('/').next().unwrap_or(path);
+    let stem = file.rsplit_once('.').map(|(stem, _)| stem).unwrap_or(file);
+    let split = stem.match_indices('-').find(|(i, _)| stem[i + 1..].starts_with(|c: char| c.is_ascii_digit()));
+    match split {
+        Some((i, _)) => (stem[..i].to_string(), stem[i + 1..].to_string()),
+        None => (stem.to_string(), String::new()),
+    }
+}
+
+/// jar 안의 pom.properties를 우선 사용하고, 없으면 MANIFEST.MF와 파일 이름으로 추정합니다.
+fn jar_packages(path: &str, data: &[u8], depth: usize, out: &mut Vec<Package>) {
+    let Some(entries) = zip::entries(data) else { return };
+    let mut found = false;
+    let mut manifest = None;
+    for entry in &entries {
+        let name = &entry.name;
+        if name.starts_with("META-INF/maven/") && name.ends_with("/pom.properties") {
+            let Some(content) = zip::read(data, entry, MAX_FILE_SIZE) else { continue };
+            let properties: HashMap<&str, &str> = std::str::from_utf8(&content)
+                .unwrap_or("")
+                .lines()
+                .filter(|l| !l.starts_with('#'))
+                .filter_map(|l| l.split_once('='))
+                .map(|(k, v)| (k.trim(), v.trim()))
+                .collect();
+            if let (Some(group), Some(artifact)) = (properties.get("groupId"), properties.get("artifactId")) {
+                let version = properties.get("version").copied().unwrap_or("");
+                let purl = format!("pkg:maven/{}/{}@{}", group, artifact, version);
+                out.push(package("java", &format!("{}:{}", group, artifact), version, "", purl));
+                found = true;
+            }
+        } else if name == "META-INF/MANIFEST.MF" {
+            manifest = zip::read(data, entry, MAX_FILE_SIZE);
+        } else if depth < MAX_JAR_DEPTH && name.ends_with(".jar") {
+            if let Some(nested) = zip::read(data, entry, MAX_FILE_SIZE) {
+                jar_packages(name, &nested, depth + 1, out);
+            }
+        }
+    }
+    if found {
+        return;
+    }
+
+    let text = manifest.map(|m| String::from_utf8_lossy(&m).replace("\r\n", "\n")).unwrap_or_default();
+    // MANIFEST.MF는 72바이트마다 줄을 접고 공백 한 칸으로 이어 씀
+    let fields = parse_stanza(&text.replace("\n ", ""));
+    let (file_name, file_version) = split_jar_name(path);
+    let name = [field(&fields, "Implementation-Title"), field(&fields, "Bundle-SymbolicName")]
+        .into_iter()
+        .find(|v| !v.is_empty())
+        .map(|v| v.split(';').next().unwrap_or(v).to_string())
+        .unwrap_or(file_name);
+    let version = [field(&fields, "Implementation-Version"), field(&fields, "Bundle-Version")]
+        .into_iter()
+        .find(|v| !v.is_empty())
+        .map(|v| v.to_string())
+        .unwrap_or(file_version);
+    let license = field(&fields, "Bundle-License");
+    out.push(package("java", &name, &version, license, format!("pkg:maven/{}@{}", name, version)));
+}
+
+/// 모은 메타데이터 파일에서 패키지 목록을 만듭니다.
+pub fn packages_from_files(files: &BTreeMap<String, Vec<u8>>) -> Vec<Package> {
+    let mut packages = Vec::new();
+    for (path, data) in files {
+        if path == "var/lib/dpkg/status" || path.starts_with("var/lib/dpkg/status.d/") {
+            dpkg_packages(&String::from_utf8_lossy(data), &Distro::from_files(files, "debian"), &mut packages);
+        } else if path == "lib/apk/db/installed" {
+            apk_packages(&String::from_utf8_lossy(data), &Distro::from_files(files, "alpine"), &mut packages);
+        } else if path.ends_with("/METADATA") || path.ends_with("/PKG-INFO") || path.ends_with(".egg-info") {
+            packages.extend(python_package(&String::from_utf8_lossy(data)));
+        } else if path.ends_with(".jar") || path.ends_with(".war") {
+            jar_packages(path, data, 0, &mut packages);
+        } else if path.ends_with("package.json") {
+            packages.extend(npm_package(data));
+        }
+    }
+    let mut seen = HashSet::new();
+    packages.retain(|p| seen.insert((p.name.clone(), p.version.clone(), p.purl.clone())));
+    packages.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.version.cmp(&b.version)));
+    packages
+}
+
+/// SBOM이 없는 이미지를 위해 레이어 내용만으로 패키지 목록을 만듭니다.
+pub async fn scan_image(image: &str, reference: &str, platform: &Platform) -> Result<Vec<Package>, Box<dyn StdError>> {
+    let files = collect_metadata_files(image, reference, platform).await?;
+    Ok(packages_from_files(&files))
+}
+
+/// 태그의 패키지 목록과 출처. 첨부된 SBOM이 있으면 SBOM을, 없으면 레이어 스캔 결과를 씁니다.
+pub async fn image_packages(image: &str, reference: &str, platform: &Platform) -> Result<(Vec<Package>, Vec<String>), Box<dyn StdError>> {
+    let sboms = sbom::fetch_sboms(image, reference, platform).await?;
+    if sboms.is_empty() {
+        return Ok((scan_image(image, reference, platform).await?, vec![SOURCE_LAYER_SCAN.to_string()]));
+    }
+    Ok((sbom::merge_packages(&sboms), sboms.into_iter().map(|s| s.source).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(entries: &[(&str, &str)]) -> BTreeMap<String, Vec<u8>> {
+        entries.iter().map(|(path, text)| (path.to_string(), text.as_bytes().to_vec())).collect()
+    }
+
+    #[test]
+    fn tags_distribution_packages_with_release() {
+        let packages = packages_from_files(&files(&[
+            ("etc/os-release", "PRETTY_NAME=\"Debian GNU/Linux 12 (bookworm)\"\nID=debian\nVERSION_ID=\"12\"\n"),
+            (
+                "var/lib/dpkg/status",
+                "Package: curl\nStatus: install ok installed\nArchitecture: amd64\nVersion: 7.88.1-10\n\nPackage: gone\nStatus: deinstall ok config-files\nVersion: 1\n",
+            ),
+        ]));
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].purl, "pkg:deb/debian/curl@7.88.1-10?arch=amd64&distro=debian-12");
+    }
+
+    #[test]
+    fn reads_apk_database_without_os_release() {
+        let packages = packages_from_files(&files(&[("lib/apk/db/installed", "P:musl\nV:1.2.4-r2\nA:x86_64\nL:MIT\n\n")]));
+        assert_eq!(packages[0].purl, "pkg:apk/alpine/musl@1.2.4-r2?arch=x86_64");
+        assert_eq!(packages[0].license, "MIT");
+    }
+}
@@ -0,0 +1,317 @@
+use std::collections::BTreeMap;
+use std::error::Error as StdError;
+use std::io::{self, BufRead, BufReader, Read};
+
+use flate2::read::MultiGzDecoder;
+use crate::digest::DigestReader;
+use crate::registry::{self, Descriptor};
+use crate::tar;
+
+const WHITEOUT_PREFIX: &str = ".wh.";
+const OPAQUE_WHITEOUT: &str = ".wh..wh..opq";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    File,
+    Dir,
+    Symlink,
+    Hardlink,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileEntry {
+    pub path: String,
+    pub kind: FileKind,
+    pub size: u64,
+    pub mode: u32,
+    pub owner: String,
+    pub link_target: Option<String>,
+    /// 이 파일이 들어 있는 레이어 번호 (0부터)
+    pub layer: usize,
+    /// 일반 파일 내용의 sha256 (scan_layer_hashed로 읽었을 때만)
+    pub content_digest: Option<String>,
+}
+
+/// 레이어 하나의 파일 목록과 whiteout 정보
+#[derive(Debug, Clone)]
+pub struct LayerFiles {
+    pub digest: String,
+    pub size: u64,
+    pub entries: Vec<FileEntry>,
+    /// 하위 레이어에서 삭제된 경로
+    pub whiteouts: Vec<String>,
+    /// 하위 레이어의 내용을 가리는(opaque) 디렉터리
+    pub opaque_dirs: Vec<String>,
+}
+
+impl LayerFiles {
+    /// 레이어를 다시 내려받을 때 쓰는 descriptor
+    pub fn descriptor(&self) -> Descriptor {
+        Descriptor {
+            media_type: String::new(),
+            digest: self.digest.clone(),
+            size: self.size,
+            platform: None,
+            artifact_type: None,
+            annotations: Default::default(),
+        }
+    }
+}
+
+/// 압축 형식을 매직 바이트로 판별해 tar 스트림으로 풀어 줍니다.
+pub fn decompressed<'a>(reader: &'a mut dyn Read) -> io::Result<Box<dyn Read + 'a>> {
+    let mut reader = BufReader::new(reader);
+    let magic = reader.fill_buf()?;
+    if magic.starts_with(&[0x1f, 0x8b]) {
+        // 병렬 압축 도구(pigz 등)는 gzip 멤버를 여러 개 이어 붙임
+        Ok(Box::new(MultiGzDecoder::new(reader)))
+    } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Ok(Box::new(zstd::stream::read::Decoder::with_buffer(reader)?))
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+fn file_kind(entry_type: u8) -> FileKind {
+    match entry_type {
+        tar::TYPE_REGULAR | 0 => FileKind::File,
+        tar::TYPE_DIRECTORY => FileKind::Dir,
+        tar::TYPE_SYMLINK => FileKind::Symlink,
+        tar::TYPE_HARDLINK => FileKind::Hardlink,
+        _ => FileKind::Other,
+    }
+}
+
+pub fn file_entry(header: &tar::EntryHeader, layer: usize) -> FileEntry {
+    let kind = file_kind(header.entry_type);
+    let owner = format!(
+        "{}:{}",
+        if header.uname.is_empty() { header.uid.to_string() } else { header.uname.clone() },
+        if header.gname.is_empty() { header.gid.to_string() } else { header.gname.clone() }
+    );
+    FileEntry {
+        path: header.path.trim_end_matches('/').to_string(),
+        kind,
+        size: header.size,
+        mode: header.mode,
+        owner,
+        link_target: matches!(kind, FileKind::Symlink | FileKind::Hardlink).then(|| header.link_name.clone()),
+        layer,
+        content_digest: None,
+    }
+}
+
+/// whiteout 파일이면 (상위 디렉터리, 가려지는 이름)을 반환합니다. opaque는 이름이 None
+pub fn whiteout_target(path: &str) -> Option<(String, Option<String>)> {
+    let (dir, name) = match path.rsplit_once('/') {
+        Some((dir, name)) => (dir.to_string(), name),
+        None => (String::new(), path),
+    };
+    if name == OPAQUE_WHITEOUT {
+        return Some((dir, None));
+    }
+    name.strip_prefix(WHITEOUT_PREFIX).map(|hidden| (dir, Some(hidden.to_string())))
+}
+
+pub fn join_path(dir: &str, name: &str) -> String {
+    if dir.is_empty() { name.to_string() } else { format!("{}/{}", dir, name) }
+}
+
+/// 레이어 tar 스트림을 끝까지 읽어 파일 목록을 만듭니다.
+pub fn scan_layer(reader: &mut dyn Read, descriptor: &Descriptor, layer: usize) -> io::Result<LayerFiles> {
+    scan_entries(reader, descriptor, layer, false)
+}
+
+/// scan_layer와 같지만 일반 파일 내용의 sha256도 함께 계산합니다.
+pub fn scan_layer_hashed(reader: &mut dyn Read, descriptor: &Descriptor, layer: usize) -> io::Result<LayerFiles> {
+    scan_entries(reader, descriptor, layer, true)
+}
+
+fn scan_entries(reader: &mut dyn Read, descriptor: &Descriptor, layer: usize, hash_contents: bool) -> io::Result<LayerFiles> {
+    let stream = decompressed(reader)?;
+    let mut archive = tar::Archive::new(stream);
+    let mut files = LayerFiles {
+        digest: descriptor.digest.clone(),
+        size: descriptor.size,
+        entries: Vec::new(),
+        whiteouts: Vec::new(),
+        opaque_dirs: Vec::new(),
+    };
+
+    while let Some(header) = archive.next_entry()? {
+        let path = header.path.trim_end_matches('/');
+        if path.is_empty() {
+            continue;
+        }
+        match whiteout_target(path) {
+            Some((dir, None)) => files.opaque_dirs.push(dir),
+            Some((dir, Some(name))) => files.whiteouts.push(join_path(&dir, &name)),
+            None => {
+                let mut entry = file_entry(&header, layer);
+                if hash_contents && entry.kind == FileKind::File {
+                    // 건너뛸 데이터를 어차피 읽으므로 그대로 해시에 흘려 보냄
+                    let mut data = DigestReader::new(archive.data());
+                    io::copy(&mut data, &mut io::sink())?;
+                    entry.content_digest = Some(data.finish().1);
+                }
+                files.entries.push(entry);
+            }
+        }
+    }
+    // 뒤따르는 패딩까지 소비해야 blob 다이제스트를 검증할 수 있음
+    io::copy(&mut archive.into_inner(), &mut io::sink())?;
+    Ok(files)
+}
+
+pub async fn fetch_layer_files(image: &str, descriptor: &Descriptor, layer: usize) -> Result<LayerFiles, Box<dyn StdError>> {
+    let owned = descriptor.clone();
+    registry::read_blob_with(image, descriptor, move |reader| scan_layer(reader, &owned, layer)).await
+}
+
+pub async fn fetch_layer_files_hashed(image: &str, descriptor: &Descriptor, layer: usize) -> Result<LayerFiles, Box<dyn StdError>> {
+    let owned = descriptor.clone();
+    registry::read_blob_with(image, descriptor, move |reader| scan_layer_hashed(reader, &owned, layer)).await
+}
+
+/// `dir` 아래의 모든 경로를 지우고 지운 값을 돌려줍니다. ('/' 다음 문자가 '0'이므로 "dir/" ~ "dir0" 범위)
+pub fn remove_descendants<V>(map: &mut BTreeMap<String, V>, dir: &str) -> Vec<V> {
+    let keys: Vec<String> = if dir.is_empty() {
+        map.keys().cloned().collect()
+    } else {
+        map.range(format!("{}/", dir)..format!("{}0", dir)).map(|(k, _)| k.clone()).collect()
+    };
+    keys.iter().filter_map(|key| map.remove(key)).collect()
+}
+
+/// 레이어를 순서대로 겹쳐 최종 파일 시스템을 만듭니다. (whiteout/opaque 적용)
+pub fn merge_layers<'a>(layers: impl IntoIterator<Item = &'a LayerFiles>) -> BTreeMap<String, FileEntry> {
+    let mut merged: BTreeMap<String, FileEntry> = BTreeMap::new();
+    for layer in layers {
+        for dir in &layer.opaque_dirs {
+            remove_descendants(&mut merged, dir);
+        }
+        for removed in &layer.whiteouts {
+            merged.remove(removed);
+            remove_descendants(&mut merged, removed);
+        }
+        for entry in &layer.entries {
+            // 파일이 디렉터리를 대체하면 하위 항목도 사라짐
+            if entry.kind != FileKind::Dir && merged.get(&entry.path).is_some_and(|e| e.kind == FileKind::Dir) {
+                remove_descendants(&mut merged, &entry.path);
+            }
+            merged.insert(entry.path.clone(), entry.clone());
+        }
+    }
+    merged
+}
+
+pub fn format_mode(kind: FileKind, mode: u32) -> String {
+    let type_char = match kind {
+        FileKind::Dir => 'd',
+        FileKind::Symlink => 'l',
+        FileKind::Hardlink => 'h',
+        FileKind::Other => '?',
+        FileKind::File => '-',
+    };
+    let mut text = String::with_capacity(10);
+    text.push(type_char);
+    for shift in [6, 3, 0] {
+        let bits = (mode >> shift) & 7;
+        text.push(if bits & 4 != 0 { 'r' } else { '-' });
+        text.push(if bits & 2 != 0 { 'w' } else { '-' });
+        text.push(if bits & 1 != 0 { 'x' } else { '-' });
+    }
+    text
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 { format!("{} B", bytes) } else { format!("{:.1} {}", value, UNITS[unit]) }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn layer_tar(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (path, data) in files {
+            builder.append_bytes(path, data).unwrap();
+        }
+        builder.finish().unwrap()
+    }
+
+    fn scan(bytes: &[u8], layer: usize) -> io::Result<LayerFiles> {
+        let descriptor = LayerFiles { digest: String::new(), size: 0, entries: vec![], whiteouts: vec![], opaque_dirs: vec![] }.descriptor();
+        scan_layer(&mut &bytes[..], &descriptor, layer)
+    }
+
+    fn paths(files: &LayerFiles) -> Vec<&str> {
+        files.entries.iter().map(|e| e.path.as_str()).collect()
+    }
+
+    #[test]
+    fn decompresses_plain_gzip_and_zstd_layers() {
+        let tar = layer_tar(&[("etc/os-release", b"ID=alpine\n"), ("bin/sh", b"#!")]);
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(&tar).unwrap();
+        let gzip = gzip.finish().unwrap();
+        let zstd = zstd::stream::encode_all(&tar[..], 3).unwrap();
+
+        for bytes in [&tar, &gzip, &zstd] {
+            let files = scan(bytes, 0).unwrap();
+            assert_eq!(paths(&files), ["etc/os-release", "bin/sh"]);
+            assert_eq!(files.entries[0].size, 10);
+        }
+    }
+
+    #[test]
+    fn rejects_gzip_with_bad_checksum() {
+        let tar = layer_tar(&[("a", b"hello")]);
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(&tar).unwrap();
+        let mut gzip = gzip.finish().unwrap();
+        // 트레일러의 CRC32를 망가뜨림
+        let crc = gzip.len() - 8;
+        gzip[crc] ^= 0xff;
+        assert!(scan(&gzip, 0).is_err());
+    }
+
+    #[test]
+    fn parses_whiteouts() {
+        assert_eq!(whiteout_target("etc/.wh.passwd"), Some(("etc".to_string(), Some("passwd".to_string()))));
+        assert_eq!(whiteout_target(".wh.tmp"), Some((String::new(), Some("tmp".to_string()))));
+        assert_eq!(whiteout_target("var/cache/.wh..wh..opq"), Some(("var/cache".to_string(), None)));
+        assert_eq!(whiteout_target("etc/passwd"), None);
+    }
+
+    #[test]
+    fn merges_layers_with_whiteouts_and_opaque_dirs() {
+        let base = scan(&layer_tar(&[("etc/passwd", b"root"), ("var/cache/a", b"1"), ("var/cache/b", b"2"), ("tmp/x", b"")]), 0).unwrap();
+        let top = scan(&layer_tar(&[("etc/.wh.passwd", b""), ("var/cache/.wh..wh..opq", b""), ("var/cache/c", b"3"), (".wh.tmp", b"")]), 1).unwrap();
+        assert_eq!(top.whiteouts, ["etc/passwd", "tmp"]);
+        assert_eq!(top.opaque_dirs, ["var/cache"]);
+
+        let merged = merge_layers(&[base, top]);
+        let names: Vec<&str> = merged.keys().map(|k| k.as_str()).collect();
+        assert_eq!(names, ["var/cache/c"]);
+        assert_eq!(merged["var/cache/c"].layer, 1);
+    }
+
+    #[test]
+    fn formats_modes_and_sizes() {
+        assert_eq!(format_mode(FileKind::Dir, 0o755), "drwxr-xr-x");
+        assert_eq!(format_mode(FileKind::File, 0o640), "-rw-r-----");
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+    }
+}
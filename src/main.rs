@@ -1,61 +1,974 @@
+mod attestation;
+mod bundle;
+mod cache;
+mod config;
+mod diff;
+mod digest;
+mod efficiency;
+mod export;
+mod extract;
+mod filebrowser;
+mod fsdiff;
+mod history;
+mod import;
+mod inspect;
+mod inventory;
+mod layers;
+mod metadiff;
+mod output;
+mod picker;
+mod provenance;
 mod registry;
+mod sbom;
+mod snapshot;
+mod tar;
+mod time;
+mod treeprint;
 mod ui;
+mod verify;
+mod vuln;
+mod watch;
+mod zip;
 
 use crossterm::terminal::{enable_raw_mode, disable_raw_mode};
 use std::{io, process};
+use std::io::Write;
+use std::path::Path;
 use tui::backend::CrosstermBackend;
 use tui::Terminal;
 use clap::{Command, Arg, ArgAction};
 
 
+/// 모든 명령의 종료 코드. 오류는 exit_code로 분류함
+const EXIT_NOT_FOUND: i32 = 1;
+const EXIT_ERROR: i32 = 2;
+const EXIT_AUTH: i32 = 3;
+const EXIT_NETWORK: i32 = 4;
+/// 비교/검증 명령에서 차이가 있거나 검증을 통과하지 못함. 찾지 못함(1)과 구분함
+const EXIT_CHECK_FAILED: i32 = 5;
+const PROFILES_HELP: &str = "\
+Profiles are read from $XDG_CONFIG_HOME/repo-tree/config.toml (~/.config/repo-tree/config.toml):
+
+  default_profile = \"airgap\"
+
+  [profiles.airgap]
+  url = \"https://igloo.airgap.registry\"
+  username = \"ci\"
+  password_env = \"AIRGAP_PASSWORD\"   # or password_file = \"~/.secrets/airgap\", token_env for a bearer token
+  ca_file = \"~/.config/repo-tree/airgap-ca.pem\"
+  insecure = false
+  connect_timeout = 5                 # seconds
+  timeout = 60                        # seconds without data before a request fails
+  concurrency = 8                     # parallel requests for tags and digests
+  repository_filter = \"team/*\"
+  tag_filter = \"v*\"";
+
+const SCRIPT_EXIT_CODES: &str = "\
+Exit codes:
+  0  success
+  1  image, tag or profile not found
+  2  other error
+  3  authentication failure
+  4  network error
+  5  check failed: registries or images differ, signatures unverified, no attestations, vulnerabilities found";
+
+fn script_help() -> String {
+    format!("{}\n\n{}", output::FORMATS_HELP, SCRIPT_EXIT_CODES)
+}
+
 #[tokio::main]
 async fn main() -> Result<(), io::Error> {
     env_logger::init();
     let matches = Command::new("repo-tree")
         .version("1.0")
         .about("Docker Registry Tree Viewer")
+        .after_help(SCRIPT_EXIT_CODES)
         .arg(
             Arg::new("registry")
                 .short('r')
                 .long("registry")
                 .value_name("URL")
-                .help("Sets the Docker registry URL")
-                .action(ArgAction::Set)
-                .default_value("http://igloo.airgap.registry"),
+                .help("Sets the Docker registry URL [default: the profile's url, or http://igloo.airgap.registry]; repeat to switch between registries with R in the tree view")
+                .action(ArgAction::Append)
+                .global(true),
+        )
+        .arg(
+            Arg::new("profile")
+                .short('p')
+                .long("profile")
+                .value_name("NAME")
+                .help("Registry profile from $XDG_CONFIG_HOME/repo-tree/config.toml [default: default_profile]")
+                .global(true),
+        )
+        .arg(
+            Arg::new("no-cache")
+                .long("no-cache")
+                .help("Neither read nor write the on-disk cache")
+                .action(ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("offline")
+                .long("offline")
+                .help("Browse the last cached state of the registry without network access; pushing is disabled")
+                .action(ArgAction::SetTrue)
+                .conflicts_with("no-cache")
+                .global(true),
+        )
+        .arg(
+            Arg::new("repository-filter")
+                .long("repository-filter")
+                .value_name("PATTERN")
+                .help("Only show repositories matching PATTERN (* and ?) [default: the profile's repository_filter]"),
+        )
+        .arg(
+            Arg::new("tag-filter")
+                .long("tag-filter")
+                .value_name("PATTERN")
+                .help("Only show tags matching PATTERN (* and ?) [default: the profile's tag_filter]"),
+        )
+        .arg(
+            Arg::new("referrers")
+                .long("referrers")
+                .help("Load signatures, SBOMs and attestations of every tag when the tree opens")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("verify")
+                .long("verify")
+                .help("Verify cosign signatures of every tag when the tree opens")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("key")
+                .long("key")
+                .value_name("PATH")
+                .help("Trusted cosign public key (PEM file or directory of *.pub files); may be repeated")
+                .action(ArgAction::Append)
+                .global(true),
+        )
+        .arg(
+            Arg::new("vulns")
+                .long("vulns")
+                .help("Match every tag against the vulnerability database when the tree opens")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("vuln-db")
+                .long("vuln-db")
+                .value_name("DIR")
+                .help("Directory of OSV advisories (*.json files or osv.dev *.zip dumps) [default: $XDG_DATA_HOME/repo-tree/osv]")
+                .global(true),
+        )
+        .arg(
+            Arg::new("platform")
+                .long("platform")
+                .value_name("OS/ARCH[/VARIANT]")
+                .help("Platform to show for multi-arch images in the tag details [default: linux/amd64, or the image's only platform]"),
+        )
+        .arg(
+            Arg::new("refresh")
+                .long("refresh")
+                .value_name("SECS")
+                .help("Re-read the registry every SECS seconds and mark new repositories, new tags and re-pointed tags with a \"new\" badge")
+                .value_parser(clap::value_parser!(u64).range(1..)),
+        )
+        .subcommand(
+            Command::new("print")
+                .about("Print the repository tree to stdout instead of opening the TUI")
+                .arg(
+                    Arg::new("depth")
+                        .long("depth")
+                        .value_name("N")
+                        .help("1 = top-level groups, 2 = repositories, 3 = tags")
+                        .value_parser(clap::value_parser!(u8).range(1..=3))
+                        .default_value("3"),
+                )
+                .arg(
+                    Arg::new("max-tags-per-repo")
+                        .long("max-tags-per-repo")
+                        .value_name("N")
+                        .help("Show at most N tags per repository and summarize the rest")
+                        .value_parser(clap::value_parser!(usize)),
+                )
+                .arg(
+                    Arg::new("ascii")
+                        .long("ascii")
+                        .help("Draw the tree with ASCII characters instead of box-drawing glyphs")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("columns")
+                        .long("columns")
+                        .value_name("COLUMNS")
+                        .help("Extra columns after each tag, comma separated")
+                        .value_parser(["digest", "size", "created"])
+                        .value_delimiter(','),
+                )
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .value_name("OS/ARCH[/VARIANT]")
+                        .help("Platform used for the size and created columns of multi-arch images [default: linux/amd64, or the image's only platform]"),
+                )
+                .arg(
+                    Arg::new("repository-filter")
+                        .long("repository-filter")
+                        .value_name("PATTERN")
+                        .help("Only show repositories matching PATTERN (* and ?) [default: the profile's repository_filter]"),
+                )
+                .arg(
+                    Arg::new("tag-filter")
+                        .long("tag-filter")
+                        .value_name("PATTERN")
+                        .help("Only show tags matching PATTERN (* and ?) [default: the profile's tag_filter]"),
+                )
+                .after_help(SCRIPT_EXIT_CODES),
+        )
+        .subcommand(
+            Command::new("cache")
+                .about("Show or purge the on-disk cache of catalogs, tag lists and manifests")
+                .subcommand_required(true)
+                .subcommand(Command::new("size").about("Show the cache size of each registry"))
+                .subcommand(
+                    Command::new("purge")
+                        .about("Delete the cache of the selected registry (--registry/--profile)")
+                        .arg(
+                            Arg::new("all")
+                                .long("all")
+                                .help("Delete the cache of every registry")
+                                .action(ArgAction::SetTrue),
+                        ),
+                )
+                .subcommand(Command::new("path").about("Print the cache directory"))
+                .after_help("The cache lives in $XDG_CACHE_HOME/repo-tree (~/.cache/repo-tree). Manifests are stored by digest;\ncatalogs and tag lists are revalidated with ETags on every use."),
+        )
+        .subcommand(
+            Command::new("profiles")
+                .about("List the registry profiles of the config file")
+                .after_help(PROFILES_HELP),
+        )
+        .subcommand(
+            Command::new("repos")
+                .about("List repositories in the registry")
+                .args(output::format_args("table"))
+                .after_help(script_help()),
+        )
+        .subcommand(
+            Command::new("tags")
+                .about("List tags of a repository")
+                .arg(Arg::new("repository").value_name("REPO").help("Repository whose tags to list").required(true))
+                .arg(
+                    Arg::new("digests")
+                        .long("digests")
+                        .help("Also resolve the manifest digest of every tag")
+                        .action(ArgAction::SetTrue),
+                )
+                .args(output::format_args("table"))
+                .after_help(script_help()),
+        )
+        .subcommand(
+            Command::new("inspect")
+                .about("Show digest, platforms, config and layers of an image")
+                .arg(Arg::new("reference").value_name("REPO:TAG").help("Image to inspect").required(true))
+                .args(output::format_args("table"))
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .value_name("OS/ARCH[/VARIANT]")
+                        .help("Platform to pick from multi-arch images [default: linux/amd64, or the image's only platform]"),
+                )
+                .after_help(script_help()),
+        )
+        .subcommand(
+            Command::new("layers")
+                .about("List the layers of an image with their sizes and the commands that created them")
+                .arg(Arg::new("reference").value_name("REPO:TAG").help("Image whose layers to list").required(true))
+                .args(output::format_args("table"))
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .value_name("OS/ARCH[/VARIANT]")
+                        .help("Platform to pick from multi-arch images [default: linux/amd64, or the image's only platform]"),
+                )
+                .after_help(script_help()),
+        )
+        .subcommand(
+            Command::new("history")
+                .about("Show the build history of an image (v1Compatibility for schema1 manifests)")
+                .arg(Arg::new("reference").value_name("REPO:TAG").help("Image whose history to show").required(true))
+                .args(output::format_args("table"))
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .value_name("OS/ARCH[/VARIANT]")
+                        .help("Platform to pick from multi-arch images [default: linux/amd64, or the image's only platform]"),
+                )
+                .after_help(script_help()),
+        )
+        .subcommand(
+            Command::new("tag-history")
+                .about("Show which digests a tag pointed to over time, as observed by earlier runs")
+                .arg(Arg::new("reference").value_name("REPO:TAG").help("Tag whose history to show").required(true))
+                .args(output::format_args("table"))
+                .arg(
+                    Arg::new("at")
+                        .long("at")
+                        .value_name("TIME")
+                        .help("Only show the digest the tag pointed to at TIME (UTC), e.g. 2024-05-01 or 2024-05-01T12:00:00Z"),
+                )
+                .arg(
+                    Arg::new("restore")
+                        .long("restore")
+                        .value_name("DIGEST")
+                        .help("Point the tag back at DIGEST by pushing that manifest again (use with --at to pick the digest)")
+                        .num_args(0..=1)
+                        .default_missing_value(""),
+                )
+                .after_help(format!(
+                    "History is recorded in $XDG_DATA_HOME/repo-tree/history (~/.local/share/repo-tree/history)\nwhenever a tag's digest is read from the registry.\n\n{}",
+                    script_help()
+                )),
+        )
+        .subcommand(
+            Command::new("manifest")
+                .about("Print the manifest (or index) a tag points to")
+                .arg(Arg::new("reference").value_name("REPO:TAG").help("Image whose manifest to print").required(true))
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .value_name("OS/ARCH[/VARIANT]")
+                        .help("Print the image manifest for this platform instead of the index"),
+                )
+                .after_help(SCRIPT_EXIT_CODES),
+        )
+        .subcommand(
+            Command::new("config")
+                .about("Print the config blob of an image")
+                .arg(Arg::new("reference").value_name("REPO:TAG").help("Image whose config to print").required(true))
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .value_name("OS/ARCH[/VARIANT]")
+                        .help("Platform to pick from multi-arch images [default: linux/amd64, or the image's only platform]"),
+                )
+                .after_help(SCRIPT_EXIT_CODES),
+        )
+        .subcommand(
+            Command::new("digest")
+                .about("Print the manifest digest a tag points to")
+                .arg(Arg::new("reference").value_name("REPO:TAG").help("Image whose digest to print").required(true))
+                .after_help(SCRIPT_EXIT_CODES),
+        )
+        .subcommand(
+            Command::new("export")
+                .about("Export images to an OCI image layout directory or a docker-archive tarball")
+                .arg(
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .value_name("FORMAT")
+                        .help("Output format: oci (image layout directory), oci-archive (layout tarball) or docker (docker load tarball)")
+                        .value_parser(["oci", "oci-layout", "oci-archive", "docker", "docker-archive"])
+                        .default_value("oci"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("PATH")
+                        .help("Output directory (oci) or tar file (oci-archive, docker)")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .value_name("OS/ARCH[/VARIANT]")
+                        .help("Platform to pick from multi-arch images for docker archives [default: linux/amd64, or the image's only platform]"),
+                )
+                .arg(
+                    Arg::new("references")
+                        .value_name("REPO:TAG")
+                        .help("Images to export, e.g. group/app:1.0 or group/app@sha256:...")
+                        .num_args(1..)
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("import")
+                .about("Import an OCI image layout or docker-archive tarball into the registry")
+                .arg(
+                    Arg::new("input")
+                        .value_name("PATH")
+                        .help("OCI layout directory, OCI layout tar or `docker save` tarball")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("repository")
+                        .long("repository")
+                        .value_name("NAME")
+                        .help("Push all images to this repository instead of the names in the archive"),
+                )
+                .arg(
+                    Arg::new("tag")
+                        .long("tag")
+                        .value_name("TAG")
+                        .help("Push the image with this tag (archives with a single image only)"),
+                ),
+        )
+        .subcommand(
+            Command::new("bundle")
+                .about("Export or import a deduplicated bundle of many images for air-gap sync")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("export")
+                        .about("Bundle every image of a group and/or matching a tag filter")
+                        .arg(
+                            Arg::new("group")
+                                .short('g')
+                                .long("group")
+                                .value_name("NAME")
+                                .help("Top-level group (first path segment of the repository)"),
+                        )
+                        .arg(
+                            Arg::new("tag-filter")
+                                .short('t')
+                                .long("tag-filter")
+                                .value_name("PATTERN")
+                                .help("Only include tags matching this wildcard pattern, e.g. 'v1.*'"),
+                        )
+                        .arg(
+                            Arg::new("archive")
+                                .long("archive")
+                                .help("Write a single tar file instead of a layout directory")
+                                .action(ArgAction::SetTrue),
+                        )
+                        .arg(
+                            Arg::new("output")
+                                .short('o')
+                                .long("output")
+                                .value_name("PATH")
+                                .help("Bundle directory or tar file")
+                                .required(true),
+                        ),
+                )
+                .subcommand(
+                    Command::new("import")
+                        .about("Import a bundle into the registry, skipping what is already present")
+                        .arg(Arg::new("input").value_name("PATH").help("Bundle directory or tar file").required(true)),
+                ),
+        )
+        .subcommand(
+            Command::new("diff-registries")
+                .about("Compare two registries and report missing or divergent tags (exit code 5 when out of sync)")
+                .arg(
+                    Arg::new("registry_a")
+                        .value_name("A")
+                        .help("First registry: a URL such as https://upstream.example, or a profile name")
+                        .required_unless_present("profile-a"),
+                )
+                .arg(
+                    Arg::new("registry_b")
+                        .value_name("B")
+                        .help("Second registry: a URL such as http://igloo.airgap.registry, or a profile name")
+                        .required_unless_present_any(["profile-a", "profile-b"]),
+                )
+                .arg(
+                    Arg::new("profile-a")
+                        .long("profile-a")
+                        .value_name("NAME")
+                        .help("Profile whose credentials and CA are used for A [default: A itself if it names a profile]"),
+                )
+                .arg(
+                    Arg::new("profile-b")
+                        .long("profile-b")
+                        .value_name("NAME")
+                        .help("Profile whose credentials and CA are used for B [default: B itself if it names a profile]"),
+                )
+                .args(output::format_args("table"))
+                .after_help(script_help())
+                .arg(
+                    Arg::new("tui")
+                        .long("tui")
+                        .help("Browse the out-of-sync tags in the tree view instead of printing a report")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("snapshot")
+                .about("Record the state of the registry into a file, or compare two recorded states")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("save")
+                        .about("Record repositories, tags, digests, sizes and created dates into FILE")
+                        .arg(Arg::new("file").value_name("FILE").help("Snapshot file to write (JSON)").required(true))
+                        .arg(
+                            Arg::new("platform")
+                                .long("platform")
+                                .value_name("OS/ARCH[/VARIANT]")
+                                .help("Platform used for the size and created date of multi-arch images [default: linux/amd64, or the image's only platform]"),
+                        )
+                        .arg(
+                            Arg::new("repository-filter")
+                                .long("repository-filter")
+                                .value_name("PATTERN")
+                                .help("Only record repositories matching PATTERN (* and ?) [default: the profile's repository_filter]"),
+                        )
+                        .arg(
+                            Arg::new("tag-filter")
+                                .long("tag-filter")
+                                .value_name("PATTERN")
+                                .help("Only record tags matching PATTERN (* and ?) [default: the profile's tag_filter]"),
+                        )
+                        .after_help(SCRIPT_EXIT_CODES),
+                )
+                .subcommand(
+                    Command::new("diff")
+                        .about("Show what was pushed, updated, retagged or deleted between two snapshots (exit code 5 when they differ)")
+                        .arg(Arg::new("before").value_name("BEFORE").help("Older snapshot file").required(true))
+                        .arg(Arg::new("after").value_name("AFTER").help("Newer snapshot file").required(true))
+                        .args(output::format_args("table"))
+                        .arg(
+                            Arg::new("tui")
+                                .long("tui")
+                                .help("Browse the changed tags in the tree view instead of printing a report")
+                                .action(ArgAction::SetTrue),
+                        )
+                        .after_help(format!(
+                            "{}\n\nChanges:\n  pushed    new tag pointing at an image that was not in the repository\n  updated   existing tag now pointing at an image that was not in the repository\n  retagged  tag (new or moved) pointing at an image that was already in the repository\n  deleted   tag no longer present\n\n{}",
+                            output::FORMATS_HELP,
+                            SCRIPT_EXIT_CODES
+                        )),
+                ),
+        )
+        .subcommand(
+            Command::new("watch")
+                .about("Poll the registry and print new repositories, new tags and re-pointed tags as JSON lines")
+                .arg(
+                    Arg::new("interval")
+                        .long("interval")
+                        .value_name("SECS")
+                        .help("Seconds between polls")
+                        .value_parser(clap::value_parser!(u64).range(1..))
+                        .default_value("60"),
+                )
+                .arg(
+                    Arg::new("repository-filter")
+                        .long("repository-filter")
+                        .value_name("PATTERN")
+                        .help("Only watch repositories matching PATTERN (* and ?) [default: the profile's repository_filter]"),
+                )
+                .arg(
+                    Arg::new("tag-filter")
+                        .long("tag-filter")
+                        .value_name("PATTERN")
+                        .help("Only watch tags matching PATTERN (* and ?) [default: the profile's tag_filter]"),
+                )
+                .after_help(format!(
+                    "The first poll is the baseline; every later change is printed as one JSON object per line:\n  time, event, repository, tag, digest, previous_digest\n\nEvents:\n  new_repository      repository appeared (followed by new_tag for each of its tags)\n  new_tag             tag appeared\n  repointed_tag       existing tag now points at another digest\n  deleted_tag         tag disappeared\n  deleted_repository  repository disappeared\n\nFailed polls are reported on stderr and retried at the next interval.\n\n{}",
+                    SCRIPT_EXIT_CODES
+                )),
+        )
+        .subcommand(
+            Command::new("diff-files")
+                .about("Compare the filesystems of two images (exit code 5 when they differ)")
+                .arg(Arg::new("reference_a").value_name("REPO:TAG_A").help("Base image").required(true))
+                .arg(Arg::new("reference_b").value_name("REPO:TAG_B").help("Image to compare against the base").required(true))
+                .args(output::format_args("table"))
+                .after_help(script_help())
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .value_name("OS/ARCH[/VARIANT]")
+                        .help("Platform to pick from multi-arch images [default: linux/amd64, or the image's only platform]"),
+                ),
+        )
+        .subcommand(
+            Command::new("diff-config")
+                .about("Compare the layer lists and image configs of two tags (exit code 5 when they differ)")
+                .arg(Arg::new("reference_a").value_name("REPO:TAG_A").help("Base image").required(true))
+                .arg(Arg::new("reference_b").value_name("REPO:TAG_B").help("Image to compare against the base").required(true))
+                .args(output::format_args("json"))
+                .after_help(script_help())
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .value_name("OS/ARCH[/VARIANT]")
+                        .help("Platform to pick from multi-arch images [default: linux/amd64, or the image's only platform]"),
+                ),
+        )
+        .subcommand(
+            Command::new("efficiency")
+                .about("Report space wasted by files that later layers overwrite or delete")
+                .arg(Arg::new("reference").value_name("REPO:TAG").help("Image to analyze").required(true))
+                .arg(
+                    Arg::new("min-efficiency")
+                        .long("min-efficiency")
+                        .value_name("PERCENT")
+                        .help("Exit with code 1 when the efficiency score is below this value, e.g. 95")
+                        .value_parser(clap::value_parser!(f64)),
+                )
+                .arg(
+                    Arg::new("top")
+                        .long("top")
+                        .value_name("N")
+                        .help("Number of biggest offenders to show per layer")
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value("5"),
+                )
+                .args(output::format_args("table"))
+                .after_help(output::FORMATS_HELP)
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .value_name("OS/ARCH[/VARIANT]")
+                        .help("Platform to pick from multi-arch images [default: linux/amd64, or the image's only platform]"),
+                ),
+        )
+        .subcommand(
+            Command::new("referrers")
+                .about("List artifacts (signatures, SBOMs, attestations) attached to an image")
+                .arg(Arg::new("reference").value_name("REPO:TAG").help("Image whose referrers to list").required(true))
+                .args(output::format_args("table"))
+                .after_help(output::FORMATS_HELP),
+        )
+        .subcommand(
+            Command::new("verify")
+                .about("Verify cosign signatures of images offline (exit code 5 unless all are verified)")
+                .arg(
+                    Arg::new("references")
+                        .value_name("REPO:TAG")
+                        .help("Images to verify")
+                        .required(true)
+                        .num_args(1..),
+                )
+                .args(output::format_args("table"))
+                .after_help(script_help()),
+        )
+        .subcommand(
+            Command::new("provenance")
+                .about("Show SLSA build provenance and other in-toto attestations of an image (exit code 5 if there are none)")
+                .arg(Arg::new("reference").value_name("REPO:TAG").help("Image whose attestations to show").required(true))
+                .args(output::format_args("table"))
+                .after_help(script_help())
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .value_name("OS/ARCH[/VARIANT]")
+                        .help("Platform to pick from multi-arch images [default: linux/amd64, or the image's only platform]"),
+                ),
+        )
+        .subcommand(
+            Command::new("vulns")
+                .about("Match the packages of an image against a local OSV vulnerability database (exit code 5 if any are found)")
+                .arg(Arg::new("reference").value_name("REPO:TAG").help("Image to check").required(true))
+                .arg(
+                    Arg::new("fail-on")
+                        .long("fail-on")
+                        .value_name("SEVERITY")
+                        .help("Only exit with 1 for vulnerabilities of at least this severity")
+                        .value_parser(["unknown", "low", "medium", "high", "critical"]),
+                )
+                .args(output::format_args("table"))
+                .after_help(script_help())
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .value_name("OS/ARCH[/VARIANT]")
+                        .help("Platform to pick from multi-arch images [default: linux/amd64, or the image's only platform]"),
+                ),
+        )
+        .subcommand(
+            Command::new("sbom")
+                .about("List packages from SPDX/CycloneDX SBOMs attached to an image, or export them as one SBOM")
+                .long_about(
+                    "List packages from SPDX/CycloneDX SBOMs attached to an image, or export them as one SBOM.\n\
+                     Images without an SBOM are inventoried from their layers instead: dpkg and apk databases, \
+                     Python dist-info/egg-info metadata, Java jars and package.json files.",
+                )
+                .arg(Arg::new("reference").value_name("REPO:TAG").help("Image whose SBOMs to read").required(true))
+                .arg(
+                    Arg::new("scan-layers")
+                        .long("scan-layers")
+                        .help("Inventory packages from the image layers even if an SBOM is attached")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("search")
+                        .short('s')
+                        .long("search")
+                        .value_name("TEXT")
+                        .help("Only list packages whose name, version, type, license or PURL contains TEXT"),
+                )
+                .arg(
+                    Arg::new("export")
+                        .long("export")
+                        .value_name("FILE")
+                        .help("Write the merged SBOM to FILE instead of listing packages"),
+                )
+                .arg(
+                    Arg::new("format")
+                        .short('f')
+                        .long("format")
+                        .value_name("FORMAT")
+                        .help("Format of the exported SBOM")
+                        .value_parser(["cyclonedx", "spdx"])
+                        .default_value("cyclonedx"),
+                )
+                .args(output::format_args("table"))
+                .after_help(output::FORMATS_HELP)
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .value_name("OS/ARCH[/VARIANT]")
+                        .help("Platform to pick from multi-arch images [default: linux/amd64, or the image's only platform]"),
+                ),
+        )
+        .subcommand(
+            Command::new("extract")
+                .about("Extract a file or directory from an image without pulling it")
+                .arg(Arg::new("reference").value_name("REPO:TAG").help("Image to read from").required(true))
+                .arg(Arg::new("path").value_name("PATH").help("Path inside the image, e.g. /etc/os-release").required(true))
+                .arg(
+                    Arg::new("output")
+                        .short('o')
+                        .long("output")
+                        .value_name("DEST")
+                        .help("Local destination (defaults to the base name of PATH in the current directory)"),
+                )
+                .arg(
+                    Arg::new("no-follow")
+                        .long("no-follow")
+                        .help("Extract symbolic links as links instead of following them")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("preserve-setuid")
+                        .long("preserve-setuid")
+                        .help("Keep setuid, setgid and sticky bits of extracted files (dropped by default)")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("platform")
+                        .long("platform")
+                        .value_name("OS/ARCH[/VARIANT]")
+                        .help("Platform to pick from multi-arch images [default: linux/amd64, or the image's only platform]"),
+                ),
         )
         .get_matches();
 
-    // 인수로 받은 registry URL을 사용
-    let registry_url = matches.get_one::<String>("registry").unwrap();
-    registry::set_registry_url(registry_url);
+    let config = config::Config::load_default().unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(EXIT_ERROR);
+    });
+    // --registry만 주면 다른 registry이므로 기본 프로필을 쓰지 않음
+    let profile_name = matches.get_one::<String>("profile").map(|s| s.as_str());
+    let profile = if profile_name.is_none() && matches.contains_id("registry") {
+        None
+    } else {
+        config.profile(profile_name).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            process::exit(EXIT_ERROR);
+        })
+    };
+    // --registry가 프로필의 url보다 우선
+    let registry_url = matches
+        .get_one::<String>("registry")
+        .cloned()
+        .or_else(|| profile.and_then(|p| p.url.clone()))
+        .unwrap_or_else(|| config::DEFAULT_REGISTRY_URL.to_string());
+    registry::set_registry_url(&registry_url);
+    cache::set_enabled(!matches.get_flag("no-cache"));
+    cache::set_offline(matches.get_flag("offline"));
+    // 오프라인에서는 registry에 쓰는 명령을 막음
+    let push_command = match matches.subcommand() {
+        Some(("import", _)) => Some("import"),
+        Some(("bundle", sub)) if sub.subcommand_name() == Some("import") => Some("bundle import"),
+        Some(("tag-history", sub)) if sub.contains_id("restore") => Some("tag-history --restore"),
+        _ => None,
+    };
+    if let (true, Some(command)) = (cache::is_offline(), push_command) {
+        eprintln!("Error: '{}' pushes to the registry and is disabled in --offline mode", command);
+        process::exit(EXIT_ERROR);
+    }
+    let settings = profile.map(|p| p.client_settings()).transpose().unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(EXIT_ERROR);
+    });
+    if let Some((profile, settings)) = profile.zip(settings.as_ref()) {
+        if settings.auth.is_some() && !registry::same_registry(&registry_url, &profile.url_or_default()) {
+            eprintln!("Warning: profile '{}' credentials are only sent to {}, not to {}", profile.name, profile.url_or_default(), registry_url);
+        }
+    }
+    if let Err(e) = registry::configure_client(&settings.clone().unwrap_or_default()) {
+        eprintln!("Error: {}", e);
+        process::exit(EXIT_ERROR);
+    }
+
+    match matches.subcommand() {
+        Some(("print", sub)) => {
+            run_print(sub, &tree_filter(sub, profile)).await;
+            return Ok(());
+        }
+        Some(("cache", sub)) => {
+            run_cache(sub, &registry_url);
+            return Ok(());
+        }
+        Some(("profiles", _)) => {
+            run_profiles(&config);
+            return Ok(());
+        }
+        Some(("repos", sub)) => {
+            run_repos(sub).await;
+            return Ok(());
+        }
+        Some(("tags", sub)) => {
+            run_tags(sub).await;
+            return Ok(());
+        }
+        Some(("inspect", sub)) => {
+            run_inspect(sub).await;
+            return Ok(());
+        }
+        Some(("layers", sub)) => {
+            run_layers(sub).await;
+            return Ok(());
+        }
+        Some(("history", sub)) => {
+            run_history(sub).await;
+            return Ok(());
+        }
+        Some(("tag-history", sub)) => {
+            run_tag_history(sub).await;
+            return Ok(());
+        }
+        Some(("manifest", sub)) => {
+            run_manifest(sub).await;
+            return Ok(());
+        }
+        Some(("config", sub)) => {
+            run_config(sub).await;
+            return Ok(());
+        }
+        Some(("digest", sub)) => {
+            run_digest(sub).await;
+            return Ok(());
+        }
+        Some(("export", sub)) => {
+            run_export(sub).await;
+            return Ok(());
+        }
+        Some(("import", sub)) => {
+            run_import(sub).await;
+            return Ok(());
+        }
+        Some(("bundle", sub)) => {
+            run_bundle(sub).await;
+            return Ok(());
+        }
+        Some(("extract", sub)) => {
+            run_extract(sub).await;
+            return Ok(());
+        }
+        Some(("diff-registries", sub)) => {
+            return run_diff_registries(sub, &config).await;
+        }
+        Some(("snapshot", sub)) => match sub.subcommand() {
+            Some(("save", save)) => {
+                run_snapshot_save(save, &tree_filter(save, profile)).await;
+                return Ok(());
+            }
+            Some(("diff", diff)) => return run_snapshot_diff(diff).await,
+            _ => unreachable!("subcommand_required"),
+        },
+        Some(("watch", sub)) => {
+            run_watch(sub, &tree_filter(sub, profile)).await;
+            return Ok(());
+        }
+        Some(("provenance", sub)) => {
+            run_provenance(sub).await;
+            return Ok(());
+        }
+        Some(("vulns", sub)) => {
+            run_vulns(sub).await;
+            return Ok(());
+        }
+        Some(("sbom", sub)) => {
+            run_sbom(sub).await;
+            return Ok(());
+        }
+        Some(("verify", sub)) => {
+            run_verify(sub).await;
+            return Ok(());
+        }
+        Some(("referrers", sub)) => {
+            run_referrers(sub).await;
+            return Ok(());
+        }
+        Some(("efficiency", sub)) => {
+            run_efficiency(sub).await;
+            return Ok(());
+        }
+        Some(("diff-config", sub)) => {
+            run_diff_config(sub).await;
+            return Ok(());
+        }
+        Some(("diff-files", sub)) => {
+            run_diff_files(sub).await;
+            return Ok(());
+        }
+        _ => {}
+    }
 
-    let images = registry::fetch_images().await.unwrap_or_else(|_| vec![]);
+    // 캐시된 트리가 있으면 바로 보여주고 registry는 백그라운드에서 다시 읽음
+    let filter = tree_filter(&matches, profile);
+    let cached = registry::cached_repository_tags(&filter).filter(|repositories| !repositories.is_empty());
+    let repositories = match &cached {
+        Some(repositories) => repositories.clone(),
+        None if cache::is_offline() => {
+            eprintln!("Error: there is no cached snapshot of '{}'; open it once while online first.", registry_url);
+            process::exit(EXIT_NOT_FOUND);
+        }
+        None => registry::fetch_repository_tags(&filter).await.unwrap_or_default(),
+    };
 
-    if images.is_empty() {
+    if repositories.is_empty() {
         eprintln!("Warning: Could not connect to the registry at '{}'.", registry_url);
         eprintln!("Please check the registry URL or add the '--registry <URL>' option to specify a valid Docker registry.");
-        process::exit(1);
+        process::exit(EXIT_NETWORK);
     } else {
         // app_items이 비어 있지 않을 경우의 로직 처리
         println!("Registry items loaded successfully.");
     }
 
-    let grouped_images = registry::group_images_by_depth(images);
-
-    // 각 2뎁스 이미지에 대해 태그를 불러와 3뎁스 추가
-    let mut app_items: Vec<(String, Vec<(String, Vec<String>)>)> = Vec::new();
-
-    for (depth1, depth2_list) in grouped_images {
-        let mut depth2_with_tags = Vec::new();
-        for depth2 in depth2_list {
-            let tags = registry::fetch_tags(&format!("{}/{}", depth1, depth2))
-                .await
-                .unwrap_or_else(|_| vec![]);
-            depth2_with_tags.push((depth2, tags));
-        }
-        app_items.push((depth1, depth2_with_tags));
+    let mut app = ui::App::new(registry::tree_items_from_tags(&repositories));
+    app.platform = parse_platform(&matches);
+    app.registries = registry_choices(&matches, &config, profile, &registry_url, settings);
+    app.title = app.registry_title();
+    if let Some(cached) = cached.filter(|_| !cache::is_offline()) {
+        app.refresh_in_background(cached, filter);
+    }
+    // 오프라인에서는 다시 읽어도 바뀔 것이 없음
+    if let Some(secs) = matches.get_one::<u64>("refresh").filter(|_| !cache::is_offline()) {
+        app.auto_refresh = Some(ui::AutoRefresh::new(std::time::Duration::from_secs(*secs)));
+    }
+    if matches.get_flag("referrers") {
+        app.load_all_referrers().await;
+    }
+    if matches.get_flag("verify") {
+        app.trusted_keys = load_trusted_keys(&matches);
+        app.verify_all().await;
+    }
+    if matches.get_flag("vulns") || matches.contains_id("vuln-db") {
+        app.vuln_db = Some(std::sync::Arc::new(load_vuln_db(&matches)));
     }
+    if matches.get_flag("vulns") {
+        app.scan_all_vulns().await;
+    }
+    run_tui(app).await
+}
 
+async fn run_tui(app: ui::App) -> Result<(), io::Error> {
     // 터미널 설정
     enable_raw_mode()?;
     let stdout = io::stdout();
@@ -63,7 +976,6 @@ async fn main() -> Result<(), io::Error> {
     let mut terminal = Terminal::new(backend)?;
 
     // UI 실행
-    let app = ui::App::new(app_items);
     let res = ui::run_app(&mut terminal, app).await;
 
     // 종료 후 터미널 복구
@@ -71,4 +983,937 @@ async fn main() -> Result<(), io::Error> {
     terminal.show_cursor()?;
 
     res
-}
\ No newline at end of file
+}
+
+async fn run_export(matches: &clap::ArgMatches) {
+    let format = export::ExportFormat::parse(matches.get_one::<String>("format").unwrap()).unwrap();
+    let output = Path::new(matches.get_one::<String>("output").unwrap());
+    let references: Vec<String> = matches.get_many::<String>("references").unwrap().cloned().collect();
+
+    let platform = parse_platform(matches);
+
+    match export::export_images(&references, format, output, platform.as_ref()).await {
+        Ok(summary) => {
+            println!(
+                "Exported {} image(s) to {}: {} blob(s) written ({} bytes), {} shared, {} already complete from a previous run.",
+                summary.images,
+                output.display(),
+                summary.blobs_written,
+                summary.bytes_written,
+                summary.blobs_skipped,
+                summary.blobs_reused
+            );
+        }
+        Err(e) => {
+            eprintln!("Error: export failed: {}", e);
+            process::exit(exit_code(e.as_ref()));
+        }
+    }
+}
+
+async fn run_import(matches: &clap::ArgMatches) {
+    let input = Path::new(matches.get_one::<String>("input").unwrap());
+    let options = import::ImportOptions {
+        repository: matches.get_one::<String>("repository").cloned(),
+        tag: matches.get_one::<String>("tag").cloned(),
+    };
+
+    match import::import_archive(input, &options).await {
+        Ok(summary) => {
+            println!(
+                "Imported {} image(s), {} already up to date: {} blob(s) uploaded ({} bytes), {} already present, {} manifest(s) pushed.",
+                summary.images, summary.images_present, summary.blobs_uploaded, summary.bytes_uploaded, summary.blobs_present, summary.manifests_pushed
+            );
+        }
+        Err(e) => {
+            eprintln!("Error: import failed: {}", e);
+            process::exit(exit_code(e.as_ref()));
+        }
+    }
+}
+
+async fn run_bundle(matches: &clap::ArgMatches) {
+    match matches.subcommand() {
+        Some(("export", sub)) => {
+            let selection = bundle::BundleSelection {
+                group: sub.get_one::<String>("group").cloned(),
+                tag_filter: sub.get_one::<String>("tag-filter").cloned(),
+            };
+            let output = Path::new(sub.get_one::<String>("output").unwrap());
+            match bundle::export_bundle(&selection, sub.get_flag("archive"), output).await {
+                Ok((index, summary)) => {
+                    println!(
+                        "Bundled {} image(s) into {}: {} blob(s) ({} bytes), {} shared blob(s) deduplicated, {} already complete from a previous run.",
+                        index.images.len(),
+                        output.display(),
+                        summary.blobs_written,
+                        summary.bytes_written,
+                        summary.blobs_skipped,
+                        summary.blobs_reused
+                    );
+                }
+                Err(e) => {
+                    eprintln!("Error: bundle export failed: {}", e);
+                    process::exit(exit_code(e.as_ref()));
+                }
+            }
+        }
+        Some(("import", sub)) => {
+            let input = Path::new(sub.get_one::<String>("input").unwrap());
+            match bundle::import_bundle(input).await {
+                Ok(summary) => {
+                    for reference in &summary.added {
+                        println!("+ {}", reference);
+                    }
+                    for reference in &summary.present {
+                        println!("= {}", reference);
+                    }
+                    println!(
+                        "{} image(s) added, {} already present; {} blob(s) uploaded ({} bytes), {} blob(s) already present.",
+                        summary.added.len(),
+                        summary.present.len(),
+                        summary.transfer.blobs_uploaded,
+                        summary.transfer.bytes_uploaded,
+                        summary.transfer.blobs_present
+                    );
+                }
+                Err(e) => {
+                    eprintln!("Error: bundle import failed: {}", e);
+                    eprintln!("Re-run the same command to resume; completed blobs and tags are skipped.");
+                    process::exit(exit_code(e.as_ref()));
+                }
+            }
+        }
+        _ => unreachable!("clap enforces a bundle subcommand"),
+    }
+}
+
+/// diff-registries의 한쪽. 값이 프로필 이름이면 그 프로필, 아니면 URL (--profile-a/-b의 인증 정보와 CA를 씀)
+/// 인증 정보는 프로필의 url과 같은 registry로 가는 요청에만 붙음
+fn comparison_side(matches: &clap::ArgMatches, config: &config::Config, side: &str) -> picker::RegistryChoice {
+    // `--profile-a NAME B`처럼 위치 인자가 하나뿐이면 프로필이 없는 쪽의 것으로 봄
+    let positional = |name: &str| matches.get_one::<String>(name);
+    let value = match (side, positional("registry_b")) {
+        ("a", None) if matches.contains_id("profile-a") && !matches.contains_id("profile-b") => None,
+        ("a", _) => positional("registry_a"),
+        ("b", None) if matches.contains_id("profile-a") && !matches.contains_id("profile-b") => positional("registry_a"),
+        _ => positional("registry_b"),
+    };
+    let named = value.filter(|v| config.profiles.contains_key(v.as_str()));
+    let profile = match matches.get_one::<String>(&format!("profile-{}", side)).or(named) {
+        Some(name) => config.profile(Some(name)).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            process::exit(EXIT_ERROR);
+        }),
+        None => None,
+    };
+    let url = match (value, profile) {
+        (Some(value), _) if named.is_none() => value.clone(),
+        (_, Some(profile)) => profile.url_or_default(),
+        _ => {
+            eprintln!("Error: give registry {} as a URL or profile name, or use --profile-{}", side.to_uppercase(), side);
+            process::exit(EXIT_ERROR);
+        }
+    };
+    picker::RegistryChoice {
+        name: profile.map(|p| p.name.clone()).unwrap_or_else(|| url.clone()),
+        url,
+        settings: profile.map_or_else(|| Ok(registry::ClientSettings::default()), |p| p.client_settings().map_err(|e| e.to_string())),
+        filter: registry::TreeFilter::default(),
+    }
+}
+
+async fn run_diff_registries(matches: &clap::ArgMatches, config: &config::Config) -> Result<(), io::Error> {
+    let side_a = comparison_side(matches, config, "a");
+    let side_b = comparison_side(matches, config, "b");
+
+    let (state_a, state_b) = match diff::collect_states(&side_a, &side_b).await {
+        Ok(states) => states,
+        Err(e) => exit_with_error(e.as_ref()),
+    };
+    let diffs = diff::compare(&state_a, &state_b);
+
+    if matches.get_flag("tui") {
+        if diffs.is_empty() {
+            println!("Registries are in sync.");
+            return Ok(());
+        }
+        let title = format!("Out of sync: A={} B={}", side_a.url, side_b.url);
+        return run_tui(diff::diff_app(&diffs, &title, vec![side_a, side_b])).await;
+    }
+
+    let format = output_format(matches);
+    if format != output::OutputFormat::Table {
+        output::print_records(&format, &diffs);
+    } else if diffs.is_empty() {
+        println!("Registries are in sync.");
+    } else {
+        let mut table = tabled::Table::new(&diffs);
+        table.with(tabled::settings::Style::modern());
+        println!("{}", table);
+        for failure in diffs.iter().filter(|d| d.status == diff::DiffStatus::Failed) {
+            eprintln!("Could not read {}:{}: {}", failure.repository, failure.tag, failure.error.as_deref().unwrap_or_default());
+        }
+        let count = |status| diffs.iter().filter(|d| d.status == status).count();
+        println!(
+            "{} only in A, {} only in B, {} with different digests, {} not compared.",
+            count(diff::DiffStatus::OnlyInA),
+            count(diff::DiffStatus::OnlyInB),
+            count(diff::DiffStatus::Differs),
+            count(diff::DiffStatus::Failed)
+        );
+    }
+
+    if !diffs.is_empty() {
+        process::exit(EXIT_CHECK_FAILED);
+    }
+    Ok(())
+}
+
+async fn run_snapshot_save(matches: &clap::ArgMatches, filter: &registry::TreeFilter) {
+    let path = Path::new(matches.get_one::<String>("file").unwrap());
+    let platform = parse_platform(matches);
+    let snapshot = snapshot::record(filter, platform.as_ref()).await.unwrap_or_else(|e| exit_with_error(e.as_ref()));
+    if let Err(e) = snapshot.save(path) {
+        eprintln!("Error: {}: {}", path.display(), e);
+        process::exit(EXIT_ERROR);
+    }
+    println!(
+        "Saved {} repositories and {} tags of {} to {}",
+        snapshot.repositories.len(),
+        snapshot.tag_count(),
+        snapshot.registry,
+        path.display()
+    );
+}
+
+async fn run_watch(matches: &clap::ArgMatches, filter: &registry::TreeFilter) {
+    if cache::is_offline() {
+        eprintln!("Error: watch polls the registry and cannot run with --offline");
+        process::exit(EXIT_ERROR);
+    }
+    let interval = std::time::Duration::from_secs(*matches.get_one::<u64>("interval").unwrap());
+    let mut state = watch::poll(filter).await.unwrap_or_else(|e| exit_with_error(e.as_ref()));
+    eprintln!(
+        "Watching {}: {} repositories, {} tags; polling every {}s",
+        registry::get_registry_url().trim_end_matches("/v2/"),
+        state.len(),
+        state.values().map(|tags| tags.len()).sum::<usize>(),
+        interval.as_secs()
+    );
+    loop {
+        tokio::time::sleep(interval).await;
+        let current = match watch::poll(filter).await {
+            Ok(current) => current,
+            Err(e) => {
+                eprintln!("Warning: poll failed: {}", e);
+                continue;
+            }
+        };
+        let mut stdout = io::stdout().lock();
+        for event in watch::changes(&state, &current) {
+            // 읽는 쪽이 닫히면 (| head 등) 조용히 끝냄
+            if writeln!(stdout, "{}", serde_json::to_string(&event).unwrap()).and_then(|_| stdout.flush()).is_err() {
+                return;
+            }
+        }
+        state = current;
+    }
+}
+
+async fn run_snapshot_diff(matches: &clap::ArgMatches) -> Result<(), io::Error> {
+    let load = |name: &str| {
+        snapshot::Snapshot::load(Path::new(matches.get_one::<String>(name).unwrap())).unwrap_or_else(|e| {
+            eprintln!("Error: {}", e);
+            process::exit(EXIT_ERROR);
+        })
+    };
+    let (before, after) = (load("before"), load("after"));
+    let diff = snapshot::compare(&before, &after);
+    let changed = !diff.changes.is_empty() || !diff.repositories.is_empty();
+
+    if matches.get_flag("tui") {
+        if !changed {
+            println!("Snapshots are identical.");
+            return Ok(());
+        }
+        registry::set_registry_url(&after.registry);
+        let title = format!("Changes: {} → {}", before.taken_at, after.taken_at);
+        return run_tui(snapshot::diff_app(&diff, &title)).await;
+    }
+
+    let format = output_format(matches);
+    if format != output::OutputFormat::Table {
+        output::print_document(&format, &diff, &diff.changes);
+    } else if !changed {
+        println!("Snapshots are identical.");
+    } else {
+        for repository in &diff.repositories {
+            println!("{} repository {}", if repository.added { "New" } else { "Deleted" }, repository.repository);
+        }
+        if !diff.changes.is_empty() {
+            let mut table = tabled::Table::new(&diff.changes);
+            table.with(tabled::settings::Style::modern());
+            println!("{}", table);
+        }
+        println!(
+            "{} pushed, {} updated, {} retagged, {} deleted between {} and {}.",
+            diff.count(snapshot::ChangeKind::Pushed),
+            diff.count(snapshot::ChangeKind::Updated),
+            diff.count(snapshot::ChangeKind::Retagged),
+            diff.count(snapshot::ChangeKind::Deleted),
+            before.taken_at,
+            after.taken_at
+        );
+    }
+
+    if changed {
+        process::exit(EXIT_CHECK_FAILED);
+    }
+    Ok(())
+}
+
+async fn run_diff_files(matches: &clap::ArgMatches) {
+    let reference_a = matches.get_one::<String>("reference_a").unwrap();
+    let reference_b = matches.get_one::<String>("reference_b").unwrap();
+    // 두 이미지를 같은 플랫폼으로 비교 (TUI와 같이 A에서 고름)
+    let platform = reference_platform(matches, reference_a).await;
+
+    let diff = match fsdiff::diff_images(reference_a, reference_b, &platform).await {
+        Ok(diff) => diff,
+        Err(e) => {
+            exit_with_error(e.as_ref())
+        }
+    };
+
+    let format = output_format(matches);
+    if format != output::OutputFormat::Table {
+        output::print_document(&format, &diff, &diff.changes);
+    } else {
+        if !diff.changes.is_empty() {
+            let mut table = tabled::Table::new(&diff.changes);
+            table.with(tabled::settings::Style::modern());
+            println!("{}", table);
+        }
+        println!("{}", diff.summary());
+    }
+
+    if !diff.changes.is_empty() {
+        process::exit(EXIT_CHECK_FAILED);
+    }
+}
+
+async fn run_diff_config(matches: &clap::ArgMatches) {
+    let reference_a = matches.get_one::<String>("reference_a").unwrap();
+    let reference_b = matches.get_one::<String>("reference_b").unwrap();
+    // 두 이미지를 같은 플랫폼으로 비교 (TUI와 같이 A에서 고름)
+    let platform = reference_platform(matches, reference_a).await;
+
+    let diff = match metadiff::diff_images(reference_a, reference_b, &platform).await {
+        Ok(diff) => diff,
+        Err(e) => {
+            exit_with_error(e.as_ref())
+        }
+    };
+
+    let format = output_format(matches);
+    if format != output::OutputFormat::Table {
+        output::print_document(&format, &diff, &diff.changes);
+    } else {
+        if !diff.changes.is_empty() {
+            let mut table = tabled::Table::new(&diff.changes);
+            table.with(tabled::settings::Style::modern());
+            println!("{}", table);
+        }
+        println!("{}", diff.summary());
+    }
+
+    if !diff.is_same() {
+        process::exit(EXIT_CHECK_FAILED);
+    }
+}
+
+async fn run_efficiency(matches: &clap::ArgMatches) {
+    let reference = matches.get_one::<String>("reference").unwrap();
+    let top = *matches.get_one::<usize>("top").unwrap();
+    let platform = reference_platform(matches, reference).await;
+
+    let report = match efficiency::analyze_image(reference, &platform, top).await {
+        Ok(report) => report,
+        Err(e) => {
+            exit_with_error(e.as_ref())
+        }
+    };
+
+    let format = output_format(matches);
+    if format != output::OutputFormat::Table {
+        output::print_document(&format, &report, &report.offenders());
+    } else {
+        let offenders = report.offenders();
+        if !offenders.is_empty() {
+            let mut table = tabled::Table::new(offenders);
+            table.with(tabled::settings::Style::modern());
+            println!("{}", table);
+        }
+        for layer in &report.layers {
+            println!(
+                "L{} {}  {} files, {} wasted",
+                layer.index + 1,
+                layer.digest,
+                layers::format_size(layer.file_bytes),
+                layers::format_size(layer.wasted_bytes)
+            );
+        }
+        println!("{}", report.summary());
+    }
+
+    if let Some(minimum) = matches.get_one::<f64>("min-efficiency") {
+        let score = report.efficiency * 100.0;
+        if score < *minimum {
+            eprintln!("Efficiency {:.2}% is below the required {:.2}%.", score, minimum);
+            process::exit(EXIT_CHECK_FAILED);
+        }
+    }
+}
+
+async fn run_referrers(matches: &clap::ArgMatches) {
+    let reference = matches.get_one::<String>("reference").unwrap();
+    let result = async {
+        let (image, tag) = registry::parse_reference(reference)?;
+        let digest = registry::fetch_manifest_digest(&image, &tag).await?;
+        let referrers = registry::fetch_referrers(&image, &digest).await?;
+        Ok::<_, Box<dyn std::error::Error>>((digest, referrers))
+    }
+    .await;
+    let (digest, referrers) = result.unwrap_or_else(|e| {
+        exit_with_error(e.as_ref())
+    });
+
+    let format = output_format(matches);
+    if format != output::OutputFormat::Table {
+        output::print_records(&format, &referrers);
+        return;
+    }
+    println!("{} ({})", reference, digest);
+    if referrers.is_empty() {
+        println!("  (no referrers)");
+    }
+    for (i, descriptor) in referrers.iter().enumerate() {
+        let branch = if i + 1 == referrers.len() { "└──" } else { "├──" };
+        println!("  {} {}", branch, ui::artifact_label(descriptor));
+    }
+}
+
+async fn run_sbom(matches: &clap::ArgMatches) {
+    let reference = matches.get_one::<String>("reference").unwrap();
+    let platform = reference_platform(matches, reference).await;
+
+    let scan_layers = matches.get_flag("scan-layers");
+    let result = async {
+        let (image, tag) = registry::parse_reference(reference)?;
+        let sboms = if scan_layers { Vec::new() } else { sbom::fetch_sboms(&image, &tag, &platform).await? };
+        Ok::<_, Box<dyn std::error::Error>>((image, tag, sboms))
+    }
+    .await;
+    let (image, tag, sboms) = result.unwrap_or_else(|e| {
+        exit_with_error(e.as_ref())
+    });
+    let (mut packages, sources) = if sboms.is_empty() {
+        if !scan_layers {
+            eprintln!("No SPDX or CycloneDX SBOM is attached to {}:{}, scanning layers.", image, tag);
+        }
+        let packages = inventory::scan_image(&image, &tag, &platform).await.unwrap_or_else(|e| {
+            exit_with_error(e.as_ref())
+        });
+        if packages.is_empty() {
+            eprintln!("No packages found in {}:{}.", image, tag);
+            process::exit(EXIT_NOT_FOUND);
+        }
+        (packages, vec![inventory::SOURCE_LAYER_SCAN.to_string()])
+    } else {
+        (sbom::merge_packages(&sboms), sboms.iter().map(|s| s.source.clone()).collect::<Vec<_>>())
+    };
+    if let Some(query) = matches.get_one::<String>("search") {
+        packages.retain(|p| p.matches(query));
+    }
+
+    if let Some(path) = matches.get_one::<String>("export") {
+        let format = sbom::SbomFormat::parse(matches.get_one::<String>("format").unwrap()).unwrap();
+        let document = sbom::export_document(format, &image, &tag, &packages);
+        if let Err(e) = std::fs::write(path, serde_json::to_string_pretty(&document).unwrap()) {
+            eprintln!("Error: could not write {}: {}", path, e);
+            process::exit(EXIT_ERROR);
+        }
+        println!("Wrote {} SBOM with {} packages to {}.", format, packages.len(), path);
+        return;
+    }
+
+    let format = output_format(matches);
+    if format != output::OutputFormat::Table {
+        output::print_records(&format, &packages);
+    } else {
+        let mut table = tabled::Table::new(&packages);
+        table.with(tabled::settings::Style::modern());
+        println!("{}", table);
+        println!("{} packages from {}", packages.len(), sources.join(", "));
+    }
+}
+
+fn load_trusted_keys(matches: &clap::ArgMatches) -> Vec<verify::PublicKey> {
+    let paths: Vec<std::path::PathBuf> = matches.get_many::<String>("key").unwrap_or_default().map(Into::into).collect();
+    verify::load_keys(&paths).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(EXIT_ERROR);
+    })
+}
+
+fn output_format(matches: &clap::ArgMatches) -> output::OutputFormat {
+    output::from_matches(matches).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(EXIT_ERROR);
+    })
+}
+
+/// 오류 종류에 맞는 종료 코드
+fn exit_code(error: &(dyn std::error::Error + 'static)) -> i32 {
+    match registry::classify_error(error) {
+        registry::FailureKind::NotFound => EXIT_NOT_FOUND,
+        registry::FailureKind::Auth => EXIT_AUTH,
+        registry::FailureKind::Network => EXIT_NETWORK,
+        registry::FailureKind::Other => EXIT_ERROR,
+    }
+}
+
+/// 오류 종류에 맞는 종료 코드로 끝냅니다.
+fn exit_with_error(error: &(dyn std::error::Error + 'static)) -> ! {
+    eprintln!("Error: {}", error);
+    process::exit(exit_code(error))
+}
+
+fn parse_platform(matches: &clap::ArgMatches) -> Option<registry::Platform> {
+    let platform_arg = matches.get_one::<String>("platform")?;
+    Some(registry::Platform::parse(platform_arg).unwrap_or_else(|| {
+        eprintln!("Error: invalid platform '{}', expected os/arch[/variant]", platform_arg);
+        process::exit(EXIT_ERROR);
+    }))
+}
+
+/// 이미지를 읽을 플랫폼. --platform이 없으면 TUI처럼 `registry::image_platform`으로 고름
+async fn resolve_platform(matches: &clap::ArgMatches, image: &str, tag: &str) -> registry::Platform {
+    registry::image_platform(image, tag, parse_platform(matches).as_ref()).await
+}
+
+/// `resolve_platform`과 같지만 참조 문자열을 받음. 해석하지 못하는 참조는 명령이 오류로 보고함
+async fn reference_platform(matches: &clap::ArgMatches, reference: &str) -> registry::Platform {
+    match registry::parse_reference(reference) {
+        Ok((image, tag)) => resolve_platform(matches, &image, &tag).await,
+        Err(_) => registry::pick_platform(&[], parse_platform(matches).as_ref()),
+    }
+}
+
+fn parse_script_reference(matches: &clap::ArgMatches) -> (String, String) {
+    registry::parse_reference(matches.get_one::<String>("reference").unwrap()).unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(EXIT_ERROR);
+    })
+}
+
+/// --repository-filter/--tag-filter, 없으면 프로필의 기본 필터
+fn tree_filter(matches: &clap::ArgMatches, profile: Option<&config::Profile>) -> registry::TreeFilter {
+    registry::TreeFilter {
+        repository: matches.get_one::<String>("repository-filter").cloned().or_else(|| profile.and_then(|p| p.repository_filter.clone())),
+        tag: matches.get_one::<String>("tag-filter").cloned().or_else(|| profile.and_then(|p| p.tag_filter.clone())),
+    }
+}
+
+/// TUI의 registry 선택기 목록: 시작한 registry, 나머지 --registry, 설정 파일의 프로필 순
+fn registry_choices(
+    matches: &clap::ArgMatches,
+    config: &config::Config,
+    profile: Option<&config::Profile>,
+    registry_url: &str,
+    settings: Option<registry::ClientSettings>,
+) -> Vec<picker::RegistryChoice> {
+    let mut choices = vec![picker::RegistryChoice {
+        name: profile.filter(|_| !matches.contains_id("registry")).map(|p| p.name.clone()).unwrap_or_else(|| registry_url.to_string()),
+        url: registry_url.to_string(),
+        settings: Ok(settings.unwrap_or_default()),
+        filter: tree_filter(matches, profile),
+    }];
+    for url in matches.get_many::<String>("registry").unwrap_or_default().skip(1) {
+        choices.push(picker::RegistryChoice {
+            name: url.clone(),
+            url: url.clone(),
+            settings: Ok(registry::ClientSettings::default()),
+            filter: registry::TreeFilter::default(),
+        });
+    }
+    for (name, other) in &config.profiles {
+        if profile.is_some_and(|p| p.name == *name) && !matches.contains_id("registry") {
+            continue;
+        }
+        choices.push(picker::RegistryChoice {
+            name: name.clone(),
+            url: other.url_or_default(),
+            settings: other.client_settings().map_err(|e| e.to_string()),
+            filter: other.tree_filter(),
+        });
+    }
+    choices
+}
+
+fn run_profiles(config: &config::Config) {
+    if config.profiles.is_empty() {
+        match config::default_config_path() {
+            Some(path) => eprintln!("No profiles are defined; create {}.", path.display()),
+            None => eprintln!("No profiles are defined."),
+        }
+        eprintln!("{}", PROFILES_HELP);
+        process::exit(EXIT_NOT_FOUND);
+    }
+    for (name, profile) in &config.profiles {
+        let marker = if config.default_profile.as_deref() == Some(name.as_str()) { "*" } else { " " };
+        println!("{} {:<16} {}", marker, name, profile.url.as_deref().unwrap_or(config::DEFAULT_REGISTRY_URL));
+    }
+}
+
+fn run_cache(matches: &clap::ArgMatches, registry_url: &str) {
+    let Some(root) = cache::cache_root() else {
+        eprintln!("Error: neither XDG_CACHE_HOME nor HOME is set");
+        process::exit(EXIT_ERROR);
+    };
+    let result = match matches.subcommand() {
+        Some(("size", _)) => cache::sizes(&root).map(|sizes| {
+            let current = cache::registry_key(registry_url);
+            for (registry, size) in &sizes {
+                let marker = if *registry == current { "*" } else { " " };
+                println!("{} {:<40} {:>10}", marker, registry, layers::format_size(*size));
+            }
+            println!("  {:<40} {:>10}", "total", layers::format_size(sizes.iter().map(|(_, size)| size).sum()));
+        }),
+        Some(("purge", sub)) => {
+            let registry = if sub.get_flag("all") { None } else { Some(registry_url) };
+            cache::purge(&root, registry).map(|size| println!("Deleted {} from {}", layers::format_size(size), root.display()))
+        }
+        _ => {
+            println!("{}", root.display());
+            Ok(())
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        process::exit(EXIT_ERROR);
+    }
+}
+
+async fn run_print(matches: &clap::ArgMatches, filter: &registry::TreeFilter) {
+    let options = treeprint::PrintOptions {
+        depth: *matches.get_one::<u8>("depth").unwrap() as usize,
+        max_tags_per_repo: matches.get_one::<usize>("max-tags-per-repo").copied(),
+        ascii: matches.get_flag("ascii"),
+        columns: matches.get_many::<String>("columns").unwrap_or_default().filter_map(|c| treeprint::Column::parse(c)).collect(),
+        platform: parse_platform(matches),
+    };
+    let repositories = registry::fetch_repository_tags(filter).await.unwrap_or_else(|e| exit_with_error(e.as_ref()));
+    // `| head`처럼 출력이 먼저 닫혀도 조용히 끝냄
+    let mut stdout = io::stdout().lock();
+    for line in treeprint::render(&repositories, &options).await {
+        if writeln!(stdout, "{}", line).is_err() {
+            break;
+        }
+    }
+}
+
+async fn run_repos(matches: &clap::ArgMatches) {
+    let format = output_format(matches);
+    let mut repositories = registry::fetch_images().await.unwrap_or_else(|e| exit_with_error(e.as_ref()));
+    repositories.sort();
+    if format != output::OutputFormat::Table {
+        let entries: Vec<registry::RepositoryEntry> = repositories.into_iter().map(|name| registry::RepositoryEntry { name }).collect();
+        output::print_records(&format, &entries);
+        return;
+    }
+    for repository in repositories {
+        println!("{}", repository);
+    }
+}
+
+async fn run_tags(matches: &clap::ArgMatches) {
+    let format = output_format(matches);
+    let repository = matches.get_one::<String>("repository").unwrap();
+    let mut tags = registry::fetch_tags(repository).await.unwrap_or_else(|e| exit_with_error(e.as_ref()));
+    tags.sort();
+    let mut entries = Vec::new();
+    for tag in tags {
+        let digest = if matches.get_flag("digests") {
+            Some(registry::fetch_manifest_digest(repository, &tag).await.unwrap_or_else(|e| exit_with_error(e.as_ref())))
+        } else {
+            None
+        };
+        entries.push(registry::TagEntry { repository: repository.clone(), tag, digest });
+    }
+    if format != output::OutputFormat::Table {
+        output::print_records(&format, &entries);
+        return;
+    }
+    for entry in entries {
+        match entry.digest {
+            Some(digest) => println!("{}\t{}", entry.tag, digest),
+            None => println!("{}", entry.tag),
+        }
+    }
+}
+
+async fn run_layers(matches: &clap::ArgMatches) {
+    let format = output_format(matches);
+    let (image, tag) = parse_script_reference(matches);
+    let platform = resolve_platform(matches, &image, &tag).await;
+    let layers = inspect::fetch_layers(&image, &tag, &platform).await.unwrap_or_else(|e| exit_with_error(e.as_ref()));
+    if format != output::OutputFormat::Table {
+        output::print_records(&format, &layers);
+        return;
+    }
+    let mut table = tabled::Table::new(&layers);
+    table.with(tabled::settings::Style::modern());
+    println!("{}", table);
+}
+
+async fn run_history(matches: &clap::ArgMatches) {
+    let format = output_format(matches);
+    let (image, tag) = parse_script_reference(matches);
+    let platform = resolve_platform(matches, &image, &tag).await;
+    let history = inspect::fetch_history(&image, &tag, &platform).await.unwrap_or_else(|e| exit_with_error(e.as_ref()));
+    if format != output::OutputFormat::Table {
+        output::print_records(&format, &history);
+        return;
+    }
+    let mut table = tabled::Table::new(&history);
+    table.with(tabled::settings::Style::modern());
+    println!("{}", table);
+}
+
+async fn run_tag_history(matches: &clap::ArgMatches) {
+    let (image, tag) = parse_script_reference(matches);
+    let mut observations = history::timeline(&registry::get_registry_url(), &image, &tag);
+    if let Some(text) = matches.get_one::<String>("at") {
+        let Some(at) = time::parse_utc(text) else {
+            eprintln!("Error: invalid time '{}', expected YYYY-MM-DD or YYYY-MM-DDTHH:MM:SSZ", text);
+            process::exit(EXIT_ERROR);
+        };
+        observations = match (history::at(&observations, at), observations.first()) {
+            (Some(observation), _) => vec![observation.clone()],
+            (None, Some(first)) => {
+                eprintln!("Error: {}:{} was first seen at {}, after {}", image, tag, time::format_unix(first.first_seen), time::format_unix(at));
+                process::exit(EXIT_NOT_FOUND);
+            }
+            (None, None) => Vec::new(),
+        };
+    }
+
+    if let Some(digest) = matches.get_one::<String>("restore") {
+        // --restore만 주면 --at으로 고른 다이제스트를 씀
+        let digest = match (digest.is_empty(), observations.as_slice()) {
+            (false, _) => digest.clone(),
+            (true, [observation]) if matches.contains_id("at") => observation.digest.clone(),
+            _ => {
+                eprintln!("Error: --restore needs a DIGEST, or --at TIME to pick one from the history");
+                process::exit(EXIT_ERROR);
+            }
+        };
+        let result = async {
+            let raw = registry::fetch_manifest_raw(&image, &digest).await?;
+            registry::push_manifest(&image, &tag, &raw.media_type, &raw.bytes).await
+        }
+        .await;
+        match result {
+            Ok(pushed) => println!("{}:{} now points to {}", image, tag, pushed),
+            Err(e) => exit_with_error(e.as_ref()),
+        }
+        return;
+    }
+
+    if observations.is_empty() {
+        eprintln!("Error: no history recorded for {}:{}", image, tag);
+        process::exit(EXIT_NOT_FOUND);
+    }
+    let format = output_format(matches);
+    if format != output::OutputFormat::Table {
+        output::print_records(&format, &observations);
+        return;
+    }
+    let mut table = tabled::Table::new(observations.iter().rev());
+    table.with(tabled::settings::Style::modern());
+    println!("{}", table);
+}
+
+async fn run_inspect(matches: &clap::ArgMatches) {
+    let (image, tag) = parse_script_reference(matches);
+    let platform = resolve_platform(matches, &image, &tag).await;
+    let inspection = inspect::inspect(&image, &tag, &platform).await.unwrap_or_else(|e| exit_with_error(e.as_ref()));
+    let format = output_format(matches);
+    if format != output::OutputFormat::Table {
+        output::print_document(&format, &inspection, &[&inspection]);
+    } else {
+        for line in inspect::lines(&inspection) {
+            println!("{}", line);
+        }
+    }
+}
+
+/// 매니페스트 원문을 그대로 출력 (다이제스트가 바뀌지 않도록 다시 포맷하지 않음)
+async fn run_manifest(matches: &clap::ArgMatches) {
+    let (image, tag) = parse_script_reference(matches);
+    let result = match parse_platform(matches) {
+        Some(platform) => registry::fetch_image_manifest(&image, &tag, &platform).await.map(|(raw, _)| raw),
+        None => registry::fetch_manifest_raw(&image, &tag).await,
+    };
+    let raw = result.unwrap_or_else(|e| exit_with_error(e.as_ref()));
+    println!("{}", String::from_utf8_lossy(&raw.bytes));
+}
+
+async fn run_config(matches: &clap::ArgMatches) {
+    let (image, tag) = parse_script_reference(matches);
+    let platform = resolve_platform(matches, &image, &tag).await;
+    let config = inspect::fetch_config(&image, &tag, &platform).await.unwrap_or_else(|e| exit_with_error(e.as_ref()));
+    println!("{}", serde_json::to_string_pretty(&config).unwrap());
+}
+
+async fn run_digest(matches: &clap::ArgMatches) {
+    let (image, tag) = parse_script_reference(matches);
+    let digest = registry::fetch_manifest_digest(&image, &tag).await.unwrap_or_else(|e| exit_with_error(e.as_ref()));
+    println!("{}", digest);
+}
+
+async fn run_provenance(matches: &clap::ArgMatches) {
+    let reference = matches.get_one::<String>("reference").unwrap();
+    let platform = reference_platform(matches, reference).await;
+
+    let result = async {
+        let (image, tag) = registry::parse_reference(reference)?;
+        provenance::fetch_attestations(&image, &tag, &platform).await
+    }
+    .await;
+    let attestations = result.unwrap_or_else(|e| {
+        exit_with_error(e.as_ref())
+    });
+
+    let format = output_format(matches);
+    if format != output::OutputFormat::Table {
+        output::print_records(&format, &attestations);
+    } else {
+        for line in provenance::report_lines(&attestations) {
+            println!("{}", line);
+        }
+    }
+    if attestations.is_empty() {
+        process::exit(EXIT_CHECK_FAILED);
+    }
+}
+
+fn load_vuln_db(matches: &clap::ArgMatches) -> vuln::VulnDb {
+    let result = match matches.get_one::<String>("vuln-db") {
+        Some(path) => vuln::VulnDb::load(std::path::Path::new(path)),
+        None => vuln::VulnDb::load_default(),
+    };
+    result.unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        process::exit(EXIT_ERROR);
+    })
+}
+
+async fn run_vulns(matches: &clap::ArgMatches) {
+    let reference = matches.get_one::<String>("reference").unwrap();
+    let platform = reference_platform(matches, reference).await;
+    let db = load_vuln_db(matches);
+
+    let result = async {
+        let (image, tag) = registry::parse_reference(reference)?;
+        vuln::scan_image(&db, &image, &tag, &platform).await
+    }
+    .await;
+    let report = result.unwrap_or_else(|e| {
+        exit_with_error(e.as_ref())
+    });
+
+    let format = output_format(matches);
+    if format != output::OutputFormat::Table {
+        output::print_document(&format, &report, &report.findings);
+    } else {
+        if !report.findings.is_empty() {
+            let mut table = tabled::Table::new(&report.findings);
+            table.with(tabled::settings::Style::modern());
+            println!("{}", table);
+        }
+        println!("{}", report.summary());
+        println!("Matched against {} advisories in {}", db.advisory_count(), db.path.display());
+    }
+
+    let threshold = matches.get_one::<String>("fail-on").and_then(|s| vuln::Severity::parse(s)).unwrap_or(vuln::Severity::Unknown);
+    if report.findings.iter().any(|f| f.severity >= threshold) {
+        process::exit(EXIT_CHECK_FAILED);
+    }
+}
+
+async fn run_verify(matches: &clap::ArgMatches) {
+    let keys = load_trusted_keys(matches);
+    let mut results = Vec::new();
+    for reference in matches.get_many::<String>("references").unwrap() {
+        match verify::verify_reference(reference, &keys).await {
+            Ok(result) => results.push(result),
+            Err(e) => {
+                eprintln!("Error: {}: {}", reference, e);
+                process::exit(exit_code(e.as_ref()));
+            }
+        }
+    }
+
+    let format = output_format(matches);
+    if format != output::OutputFormat::Table {
+        output::print_records(&format, &results);
+    } else {
+        for result in &results {
+            let detail = match &result.status {
+                verify::VerifyStatus::Verified { key, .. } => format!(" (key {})", key),
+                verify::VerifyStatus::Unverified { reason } => format!(" ({})", reason),
+                verify::VerifyStatus::NoSignature => String::new(),
+            };
+            println!("{}@{}: {}{}", result.reference, result.digest, result.status.label(), detail);
+        }
+    }
+
+    if results.iter().any(|r| !matches!(r.status, verify::VerifyStatus::Verified { .. })) {
+        process::exit(EXIT_CHECK_FAILED);
+    }
+}
+
+async fn run_extract(matches: &clap::ArgMatches) {
+    extract::set_preserve_special_bits(matches.get_flag("preserve-setuid"));
+    let reference = matches.get_one::<String>("reference").unwrap();
+    let path = matches.get_one::<String>("path").unwrap();
+    let platform = reference_platform(matches, reference).await;
+    let output = match matches.get_one::<String>("output") {
+        Some(output) => output.clone(),
+        None => path.trim_end_matches('/').rsplit('/').next().filter(|n| !n.is_empty()).unwrap_or("rootfs").to_string(),
+    };
+
+    match extract::extract(reference, &platform, path, Path::new(&output), !matches.get_flag("no-follow")).await {
+        Ok(result) => {
+            let layers: Vec<String> = result.layers.iter().map(|l| (l + 1).to_string()).collect();
+            println!(
+                "Extracted /{} to {}: {} file(s), {} bytes (layer {}).",
+                result.source_path,
+                output,
+                result.files,
+                result.bytes,
+                layers.join(", ")
+            );
+            for link in &result.missing_links {
+                eprintln!("Warning: hard link {} was not extracted: its target is not in the layer.", link);
+            }
+        }
+        Err(e) => {
+            eprintln!("Error: extract failed: {}", e);
+            process::exit(exit_code(e.as_ref()));
+        }
+    }
+}
@@ -0,0 +1,313 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error as StdError;
+use tabled::Tabled;
+use tui::style::Color;
+
+use crate::fsdiff::{self, ChangeStatus};
+use crate::layers;
+use crate::registry::{self, Platform};
+
+/// 이미지 config의 `config` 부분 중 비교에 쓰는 필드
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContainerConfig {
+    #[serde(default)]
+    env: Option<Vec<String>>,
+    #[serde(default)]
+    entrypoint: Option<Vec<String>>,
+    #[serde(default)]
+    cmd: Option<Vec<String>>,
+    #[serde(default)]
+    labels: Option<BTreeMap<String, String>>,
+    #[serde(default)]
+    user: Option<String>,
+    #[serde(default)]
+    exposed_ports: Option<BTreeMap<String, Value>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LayerRef {
+    pub digest: String,
+    /// schema1 매니페스트에는 레이어 크기가 없음
+    pub size: Option<u64>,
+}
+
+/// 비교에 필요한 매니페스트/config 요약
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageMetadata {
+    pub reference: String,
+    pub layers: Vec<LayerRef>,
+    pub env: Vec<String>,
+    pub entrypoint: Vec<String>,
+    pub cmd: Vec<String>,
+    pub labels: BTreeMap<String, String>,
+    pub user: String,
+    pub exposed_ports: Vec<String>,
+}
+
+impl ImageMetadata {
+    fn new(reference: String, layers: Vec<LayerRef>, config: ContainerConfig) -> ImageMetadata {
+        ImageMetadata {
+            reference,
+            layers,
+            env: config.env.unwrap_or_default(),
+            entrypoint: config.entrypoint.unwrap_or_default(),
+            cmd: config.cmd.unwrap_or_default(),
+            labels: config.labels.unwrap_or_default(),
+            user: config.user.unwrap_or_default(),
+            exposed_ports: config.exposed_ports.map(|p| p.into_keys().collect()).unwrap_or_default(),
+        }
+    }
+
+    /// OCI/Docker v2 매니페스트와 이미 읽은 config blob으로 만듭니다.
+    pub fn from_config(reference: String, manifest: &registry::Manifest, config: &Value) -> Result<ImageMetadata, Box<dyn StdError>> {
+        let layers = manifest.layers.iter().map(|l| LayerRef { digest: l.digest.clone(), size: Some(l.size) }).collect();
+        let container_config = config.get("config").cloned().map(serde_json::from_value).transpose()?.unwrap_or_default();
+        Ok(ImageMetadata::new(reference, layers, container_config))
+    }
+
+    /// 크기를 모르는 레이어가 있으면 None
+    pub fn total_size(&self) -> Option<u64> {
+        self.layers.iter().map(|l| l.size).sum()
+    }
+
+    /// `fetch_manifest`가 돌려주는 schema1 매니페스트에서 읽습니다. (fsLayers는 위쪽 레이어가 먼저)
+    fn from_schema1(reference: String, manifest: &Value) -> ImageMetadata {
+        let layers = manifest
+            .get("fsLayers")
+            .and_then(|l| l.as_array())
+            .map(|list| {
+                list.iter()
+                    .rev()
+                    .filter_map(|l| l.get("blobSum").and_then(|b| b.as_str()))
+                    .map(|digest| LayerRef { digest: digest.to_string(), size: None })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let config = manifest
+            .get("history")
+            .and_then(|h| h.get(0))
+            .and_then(|h| h.get("v1Compatibility"))
+            .and_then(|v| v.as_str())
+            .and_then(|v| serde_json::from_str::<Value>(v).ok())
+            .and_then(|v| v.get("config").cloned())
+            .and_then(|c| serde_json::from_value(c).ok())
+            .unwrap_or_default();
+        ImageMetadata::new(reference, layers, config)
+    }
+}
+
+pub async fn fetch_metadata(reference: &str, platform: &Platform) -> Result<ImageMetadata, Box<dyn StdError>> {
+    let (image, tag) = registry::parse_reference(reference)?;
+    let name = format!("{}:{}", image, tag);
+
+    let raw = registry::fetch_manifest_raw(&image, &tag).await?;
+    let value: Value = serde_json::from_slice(&raw.bytes)?;
+    if value.get("schemaVersion").and_then(|v| v.as_u64()) == Some(1) {
+        return Ok(ImageMetadata::from_schema1(name, &value));
+    }
+
+    let (_, manifest) = registry::fetch_image_manifest(&image, &tag, platform).await?;
+    let config = match &manifest.config {
+        Some(descriptor) => serde_json::from_slice(&registry::fetch_blob(&image, descriptor, Vec::new()).await?)?,
+        None => Value::Null,
+    };
+    ImageMetadata::from_config(name, &manifest, &config)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LayerStatus {
+    Shared,
+    Removed,
+    Added,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LayerChange {
+    pub status: LayerStatus,
+    pub digest: String,
+    pub size: Option<u64>,
+}
+
+fn display_value(value: &Option<String>) -> String {
+    value.clone().unwrap_or_else(|| "-".to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Tabled)]
+pub struct FieldChange {
+    #[tabled(rename = "Field")]
+    pub field: String,
+    #[tabled(rename = "Status")]
+    pub status: ChangeStatus,
+    #[tabled(rename = "A", display_with = "display_value")]
+    pub a: Option<String>,
+    #[tabled(rename = "B", display_with = "display_value")]
+    pub b: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MetaDiff {
+    pub image_a: String,
+    pub image_b: String,
+    pub layers: Vec<LayerChange>,
+    pub size_a: Option<u64>,
+    pub size_b: Option<u64>,
+    pub size_delta: Option<i64>,
+    pub changes: Vec<FieldChange>,
+}
+
+impl MetaDiff {
+    pub fn is_same(&self) -> bool {
+        self.changes.is_empty() && self.layers.iter().all(|l| l.status == LayerStatus::Shared)
+    }
+
+    pub fn summary(&self) -> String {
+        let count = |status| self.layers.iter().filter(|l| l.status == status).count();
+        let size = match self.size_delta {
+            Some(delta) => fsdiff::format_delta(delta),
+            None => "unknown".to_string(),
+        };
+        format!(
+            "layers: {} shared, {} removed, {} added; size delta {}; {} config change(s)",
+            count(LayerStatus::Shared),
+            count(LayerStatus::Removed),
+            count(LayerStatus::Added),
+            size,
+            self.changes.len()
+        )
+    }
+}
+
+fn env_map(env: &[String]) -> BTreeMap<String, String> {
+    env.iter()
+        .map(|e| match e.split_once('=') {
+            Some((k, v)) => (k.to_string(), v.to_string()),
+            None => (e.clone(), String::new()),
+        })
+        .collect()
+}
+
+/// 키별로 비교해 `prefix.key` 이름의 변경 목록을 만듭니다.
+fn compare_maps(prefix: &str, a: &BTreeMap<String, String>, b: &BTreeMap<String, String>, changes: &mut Vec<FieldChange>) {
+    let keys: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+    for key in keys {
+        compare_values(&format!("{}.{}", prefix, key), a.get(key).cloned(), b.get(key).cloned(), changes);
+    }
+}
+
+fn compare_values(field: &str, a: Option<String>, b: Option<String>, changes: &mut Vec<FieldChange>) {
+    let status = match (&a, &b) {
+        (None, Some(_)) => ChangeStatus::Added,
+        (Some(_), None) => ChangeStatus::Removed,
+        (Some(x), Some(y)) if x != y => ChangeStatus::Modified,
+        _ => return,
+    };
+    changes.push(FieldChange { field: field.to_string(), status, a, b });
+}
+
+fn join_args(args: &[String]) -> Option<String> {
+    (!args.is_empty()).then(|| serde_json::to_string(args).unwrap_or_default())
+}
+
+pub fn compare(a: &ImageMetadata, b: &ImageMetadata) -> MetaDiff {
+    let digests_a: BTreeSet<&String> = a.layers.iter().map(|l| &l.digest).collect();
+    let digests_b: BTreeSet<&String> = b.layers.iter().map(|l| &l.digest).collect();
+    let mut layers = Vec::new();
+    for layer in &a.layers {
+        let status = if digests_b.contains(&layer.digest) { LayerStatus::Shared } else { LayerStatus::Removed };
+        layers.push(LayerChange { status, digest: layer.digest.clone(), size: layer.size });
+    }
+    for layer in b.layers.iter().filter(|l| !digests_a.contains(&l.digest)) {
+        layers.push(LayerChange { status: LayerStatus::Added, digest: layer.digest.clone(), size: layer.size });
+    }
+
+    let mut changes = Vec::new();
+    compare_maps("Env", &env_map(&a.env), &env_map(&b.env), &mut changes);
+    compare_values("Entrypoint", join_args(&a.entrypoint), join_args(&b.entrypoint), &mut changes);
+    compare_values("Cmd", join_args(&a.cmd), join_args(&b.cmd), &mut changes);
+    compare_maps("Labels", &a.labels, &b.labels, &mut changes);
+    let user = |u: &String| (!u.is_empty()).then(|| u.clone());
+    compare_values("User", user(&a.user), user(&b.user), &mut changes);
+    let ports_a: BTreeSet<&String> = a.exposed_ports.iter().collect();
+    let ports_b: BTreeSet<&String> = b.exposed_ports.iter().collect();
+    for port in ports_a.union(&ports_b) {
+        let present = |ports: &BTreeSet<&String>| ports.contains(port).then(|| "exposed".to_string());
+        compare_values(&format!("ExposedPorts.{}", port), present(&ports_a), present(&ports_b), &mut changes);
+    }
+
+    let (size_a, size_b) = (a.total_size(), b.total_size());
+    MetaDiff {
+        image_a: a.reference.clone(),
+        image_b: b.reference.clone(),
+        layers,
+        size_a,
+        size_b,
+        size_delta: size_a.zip(size_b).map(|(x, y)| y as i64 - x as i64),
+        changes,
+    }
+}
+
+pub async fn diff_images(reference_a: &str, reference_b: &str, platform: &Platform) -> Result<MetaDiff, Box<dyn StdError>> {
+    let a = fetch_metadata(reference_a, platform).await?;
+    let b = fetch_metadata(reference_b, platform).await?;
+    Ok(compare(&a, &b))
+}
+
+/// 화면 한 줄: (텍스트, 색)
+pub type PaneLine = (String, Color);
+
+/// 두 창에 나란히 그릴 줄 목록을 만듭니다. 같은 번호의 줄이 서로 대응합니다.
+pub fn side_by_side(diff: &MetaDiff) -> (Vec<PaneLine>, Vec<PaneLine>) {
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    let mut push = |l: PaneLine, r: PaneLine| {
+        left.push(l);
+        right.push(r);
+    };
+    let heading = |text: &str| (text.to_string(), Color::Cyan);
+    let blank = || (String::new(), Color::Reset);
+
+    let size = |s: Option<u64>| s.map(layers::format_size).unwrap_or_else(|| "unknown".to_string());
+    push(heading(&format!("A: {}", diff.image_a)), heading(&format!("B: {}", diff.image_b)));
+    push(
+        (format!("Total size: {}", size(diff.size_a)), Color::Gray),
+        (
+            format!(
+                "Total size: {} ({})",
+                size(diff.size_b),
+                diff.size_delta.map(fsdiff::format_delta).unwrap_or_else(|| "?".to_string())
+            ),
+            Color::Gray,
+        ),
+    );
+    push(blank(), blank());
+
+    push(heading("Layers"), heading("Layers"));
+    for layer in &diff.layers {
+        let text = format!("{}  {}", layer.digest.chars().take(19).collect::<String>(), size(layer.size));
+        match layer.status {
+            LayerStatus::Shared => push((format!("  {}", text), Color::Gray), (format!("  {}", text), Color::Gray)),
+            LayerStatus::Removed => push((format!("- {}", text), Color::Red), blank()),
+            LayerStatus::Added => push(blank(), (format!("+ {}", text), Color::Green)),
+        }
+    }
+    push(blank(), blank());
+
+    push(heading("Config"), heading("Config"));
+    if diff.changes.is_empty() {
+        push(("  (no changes)".to_string(), Color::Gray), ("  (no changes)".to_string(), Color::Gray));
+    }
+    for change in &diff.changes {
+        let color = change.status.color();
+        let modified = change.status == ChangeStatus::Modified;
+        let side = |value: &Option<String>, mark: char| match value {
+            Some(v) => (format!("{} {} = {}", if modified { '~' } else { mark }, change.field, v), color),
+            None => blank(),
+        };
+        push(side(&change.a, '-'), side(&change.b, '+'));
+    }
+    (left, right)
+}
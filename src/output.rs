@@ -0,0 +1,295 @@
+use clap::builder::PossibleValue;
+use clap::{Arg, ArgMatches};
+use serde::Serialize;
+use serde_json::Value;
+
+/// 목록 출력 형식에 대한 --help 설명. JSON 스키마는 필드 추가만 하고 기존 필드는 바꾸지 않음
+pub const FORMATS_HELP: &str = "\
+Output formats:
+  table     human readable table (default)
+  json      one JSON document
+  jsonl     one JSON object per line
+  yaml      the JSON document as YAML
+  csv       one row per record, nested values as JSON
+  template  --template TEXT per record, e.g. '{{.repository}}:{{.tag}}\\t{{.digest}}'
+
+JSON fields:
+  repos     name
+  tags      repository, tag, digest (with --digests)
+  layers    digest, media_type, size (bytes), command
+  history   id, parent, os, created, cmd, config
+  tag-history
+            digest, first_seen, last_seen (unix seconds)
+  snapshot diff
+            registry_before, registry_after, taken_before, taken_after,
+            repositories[{repository, added}], changes[{repository, tag, change,
+            digest_before, digest_after, size, created}] (rows: changes)
+  watch     time, event, repository, tag, digest, previous_digest (always jsonl)
+  inspect   digest, media_type, manifests, platform_digest, platform, created,
+            config_digest, size, reference, layers[{digest, size}], env, user,
+            entrypoint, cmd, exposed_ports, labels, ...
+Fields are only ever added, never renamed or removed.";
+
+/// 목록 출력 형식
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+    Jsonl,
+    Yaml,
+    Csv,
+    /// 레코드마다 채우는 `{{.field}}` 템플릿
+    Template(String),
+}
+
+/// `-o/--output`과 `--template` 인수. "text"는 예전 이름이라 숨겨서 받음
+pub fn format_args(default: &'static str) -> [Arg; 2] {
+    [
+        Arg::new("output")
+            .short('o')
+            .long("output")
+            .value_name("FORMAT")
+            .help("Output format")
+            .value_parser([
+                PossibleValue::new("table"),
+                PossibleValue::new("json"),
+                PossibleValue::new("jsonl"),
+                PossibleValue::new("yaml"),
+                PossibleValue::new("csv"),
+                PossibleValue::new("template"),
+                PossibleValue::new("text").hide(true),
+            ])
+            .default_value(default),
+        Arg::new("template")
+            .long("template")
+            .value_name("TEXT")
+            .help("Template for -o template, e.g. '{{.name}}'; fields as in -o json"),
+    ]
+}
+
+pub fn from_matches(matches: &ArgMatches) -> Result<OutputFormat, String> {
+    let template = matches.get_one::<String>("template");
+    let format = match matches.get_one::<String>("output").map(|s| s.as_str()).unwrap_or("table") {
+        "json" => OutputFormat::Json,
+        "jsonl" => OutputFormat::Jsonl,
+        "yaml" => OutputFormat::Yaml,
+        "csv" => OutputFormat::Csv,
+        "template" => {
+            let template = template.ok_or("-o template needs --template TEXT")?;
+            parse_template(template)?;
+            OutputFormat::Template(template.clone())
+        }
+        _ => OutputFormat::Table,
+    };
+    if template.is_some() && !matches!(format, OutputFormat::Template(_)) {
+        return Err("--template is only used with -o template".to_string());
+    }
+    Ok(format)
+}
+
+/// 레코드 목록을 출력합니다. 표는 호출하는 쪽에서 그림
+pub fn print_records<T: Serialize>(format: &OutputFormat, records: &[T]) {
+    let values: Vec<Value> = records.iter().map(|r| serde_json::to_value(r).unwrap_or(Value::Null)).collect();
+    match format {
+        OutputFormat::Table | OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&values).unwrap()),
+        OutputFormat::Yaml => print!("{}", to_yaml(&Value::Array(values))),
+        _ => print_rows(format, &values),
+    }
+}
+
+/// 문서 하나(보고서 등)를 출력합니다. json/yaml은 문서 전체, 나머지는 `records`를 한 줄씩
+pub fn print_document<D: Serialize, T: Serialize>(format: &OutputFormat, document: &D, records: &[T]) {
+    match format {
+        OutputFormat::Table | OutputFormat::Json => println!("{}", serde_json::to_string_pretty(document).unwrap()),
+        OutputFormat::Yaml => print!("{}", to_yaml(&serde_json::to_value(document).unwrap_or(Value::Null))),
+        _ => {
+            let values: Vec<Value> = records.iter().map(|r| serde_json::to_value(r).unwrap_or(Value::Null)).collect();
+            print_rows(format, &values);
+        }
+    }
+}
+
+fn print_rows(format: &OutputFormat, values: &[Value]) {
+    match format {
+        OutputFormat::Jsonl => {
+            for value in values {
+                println!("{}", value);
+            }
+        }
+        OutputFormat::Csv => print!("{}", to_csv(values)),
+        OutputFormat::Template(template) => {
+            let parts = parse_template(template).unwrap_or_default();
+            for value in values {
+                println!("{}", render(&parts, value));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 셀/템플릿에 넣을 스칼라 표현. 배열과 객체는 JSON 그대로
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// 첫 등장 순서대로 모은 키를 열로 하는 CSV (RFC 4180)
+fn to_csv(values: &[Value]) -> String {
+    let mut columns: Vec<String> = Vec::new();
+    for value in values {
+        if let Value::Object(map) = value {
+            for key in map.keys() {
+                if !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+        }
+    }
+    if columns.is_empty() {
+        // 스칼라 목록은 "value" 열 하나로
+        let mut out = "value\r\n".to_string();
+        for value in values {
+            out.push_str(&format!("{}\r\n", csv_field(&scalar(value))));
+        }
+        return out;
+    }
+    let mut out = columns.iter().map(|c| csv_field(c)).collect::<Vec<_>>().join(",");
+    out.push_str("\r\n");
+    for value in values {
+        let row: Vec<String> = columns.iter().map(|c| csv_field(&value.get(c).map(scalar).unwrap_or_default())).collect();
+        out.push_str(&row.join(","));
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// YAML에서 따옴표 없이 쓸 수 있는 문자열인지
+fn is_plain(text: &str) -> bool {
+    const RESERVED: [&str; 12] = ["", "~", "null", "Null", "NULL", "true", "True", "TRUE", "false", "False", "FALSE", "-"];
+    if RESERVED.contains(&text) || text.parse::<f64>().is_ok() || text.starts_with(' ') || text.ends_with(' ') {
+        return false;
+    }
+    let first = text.chars().next().unwrap();
+    !"-?:,[]{}#&*!|>'\"%@`".contains(first)
+        && !text.contains(": ")
+        && !text.contains(" #")
+        && !text.ends_with(':')
+        && text.chars().all(|c| !c.is_control())
+}
+
+fn yaml_scalar(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::String(s) if is_plain(s) => s.clone(),
+        // JSON 문자열은 YAML 큰따옴표 문자열로도 유효함
+        Value::String(s) => Value::String(s.clone()).to_string(),
+        Value::Array(a) if a.is_empty() => "[]".to_string(),
+        Value::Object(o) if o.is_empty() => "{}".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn is_collection(value: &Value) -> bool {
+    match value {
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(o) => !o.is_empty(),
+        _ => false,
+    }
+}
+
+fn write_yaml(value: &Value, indent: usize, out: &mut String) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Array(items) if !items.is_empty() => {
+            for item in items {
+                if is_collection(item) {
+                    // "- " 뒤에 첫 줄을 붙이고 나머지는 두 칸 더 들여 씀
+                    let mut nested = String::new();
+                    write_yaml(item, indent + 2, &mut nested);
+                    out.push_str(&format!("{}- {}", pad, &nested[indent + 2..]));
+                } else {
+                    out.push_str(&format!("{}- {}\n", pad, yaml_scalar(item)));
+                }
+            }
+        }
+        Value::Object(map) if !map.is_empty() => {
+            for (key, item) in map {
+                let key = yaml_scalar(&Value::String(key.clone()));
+                if is_collection(item) {
+                    out.push_str(&format!("{}{}:\n", pad, key));
+                    write_yaml(item, indent + 2, out);
+                } else {
+                    out.push_str(&format!("{}{}: {}\n", pad, key, yaml_scalar(item)));
+                }
+            }
+        }
+        other => out.push_str(&format!("{}{}\n", pad, yaml_scalar(other))),
+    }
+}
+
+pub fn to_yaml(value: &Value) -> String {
+    let mut out = String::new();
+    write_yaml(value, 0, &mut out);
+    out
+}
+
+enum Part {
+    Text(String),
+    /// `.a.b` 경로. `json`이면 값을 JSON으로 출력
+    Field { path: Vec<String>, json: bool },
+}
+
+/// `{{.field}}`, `{{.a.b}}`, `{{.}}`, `{{json .field}}`와 \t, \n 이스케이프를 지원
+fn parse_template(template: &str) -> Result<Vec<Part>, String> {
+    let unescape = |text: &str| text.replace("\\t", "\t").replace("\\n", "\n");
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        parts.push(Part::Text(unescape(&rest[..start])));
+        let end = rest[start..].find("}}").ok_or_else(|| format!("unclosed '{{{{' in template '{}'", template))? + start;
+        let action = rest[start + 2..end].trim();
+        let (json, field) = match action.strip_prefix("json ") {
+            Some(field) => (true, field.trim()),
+            None => (false, action),
+        };
+        let path = field.strip_prefix('.').ok_or_else(|| format!("template field '{}' must start with '.'", field))?;
+        let path = path.split('.').filter(|p| !p.is_empty()).map(|p| p.to_string()).collect();
+        parts.push(Part::Field { path, json });
+        rest = &rest[end + 2..];
+    }
+    parts.push(Part::Text(unescape(rest)));
+    Ok(parts)
+}
+
+fn render(parts: &[Part], value: &Value) -> String {
+    let mut out = String::new();
+    for part in parts {
+        match part {
+            Part::Text(text) => out.push_str(text),
+            Part::Field { path, json } => {
+                let field = path.iter().try_fold(value, |v, key| match v {
+                    Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+                    _ => v.get(key),
+                });
+                match (field, json) {
+                    (Some(field), true) => out.push_str(&field.to_string()),
+                    (Some(field), false) => out.push_str(&scalar(field)),
+                    (None, true) => out.push_str("null"),
+                    (None, false) => {}
+                }
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tui::backend::Backend;
+use tui::layout::Rect;
+use tui::style::{Color, Modifier, Style};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Clear, List, ListItem};
+use tui::Frame;
+
+use crate::cache;
+use crate::registry::{self, ClientSettings, Descriptor, TreeFilter};
+use crate::ui::TagMark;
+
+// 선택기를 열 때 registry마다 /v2/를 확인하는 제한 시간
+const PING_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 선택기에 보이는 registry 하나 (--registry로 준 URL 또는 설정 파일의 프로필)
+#[derive(Debug, Clone)]
+pub struct RegistryChoice {
+    pub name: String,
+    pub url: String,
+    /// 프로필의 비밀번호 환경 변수가 없는 경우 등은 Err
+    pub settings: Result<ClientSettings, String>,
+    pub filter: TreeFilter,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reachability {
+    Unknown,
+    Reachable(Duration),
+    /// 응답은 오지만 인증이 거부됨
+    Unauthorized(u16),
+    Unreachable(String),
+    /// --offline: 캐시가 마지막으로 확인된 시각 (캐시가 없으면 None)
+    Offline(Option<i64>),
+}
+
+impl Reachability {
+    pub fn label(&self) -> (String, Color) {
+        match self {
+            Reachability::Unknown => ("checking...".to_string(), Color::Gray),
+            Reachability::Reachable(elapsed) => (format!("reachable ({} ms)", elapsed.as_millis()), Color::Green),
+            Reachability::Unauthorized(status) => (format!("authentication failed ({})", status), Color::Yellow),
+            Reachability::Unreachable(error) => (format!("unreachable: {}", error), Color::Red),
+            Reachability::Offline(Some(time)) => (format!("offline, snapshot from {}", crate::time::format_unix(*time)), Color::Yellow),
+            Reachability::Offline(None) => ("offline, not cached".to_string(), Color::Red),
+        }
+    }
+}
+
+/// registry의 /v2/ 엔드포인트로 접속 가능 여부를 확인합니다.
+pub async fn ping(choice: &RegistryChoice) -> Reachability {
+    if cache::is_offline() {
+        return Reachability::Offline(cache::snapshot_time(&choice.url));
+    }
+    let mut settings = match &choice.settings {
+        Ok(settings) => settings.clone(),
+        Err(error) => return Reachability::Unreachable(error.clone()),
+    };
+    settings.connect_timeout = Some(settings.connect_timeout.map_or(PING_TIMEOUT, |t| t.min(PING_TIMEOUT)));
+    let client = match registry::build_client(&settings) {
+        Ok(client) => client,
+        Err(e) => return Reachability::Unreachable(e.to_string()),
+    };
+    let started = Instant::now();
+    let url = format!("{}/v2/", choice.url.trim_end_matches('/'));
+    match client.get(&url).timeout(PING_TIMEOUT).send().await {
+        Ok(resp) if resp.status().is_success() => Reachability::Reachable(started.elapsed()),
+        Ok(resp) if matches!(resp.status().as_u16(), 401 | 403) => Reachability::Unauthorized(resp.status().as_u16()),
+        Ok(resp) => Reachability::Unreachable(format!("HTTP {}", resp.status())),
+        Err(e) if e.is_timeout() => Reachability::Unreachable("timed out".to_string()),
+        Err(e) if e.is_connect() => Reachability::Unreachable("connection failed".to_string()),
+        Err(e) => Reachability::Unreachable(e.to_string()),
+    }
+}
+
+/// `R`로 여는 registry 선택 팝업
+pub struct RegistryPicker {
+    pub selected: usize,
+    pub status: Vec<Reachability>,
+}
+
+impl RegistryPicker {
+    pub async fn open(choices: &[RegistryChoice], current: usize) -> RegistryPicker {
+        let status = futures::future::join_all(choices.iter().map(ping)).await;
+        RegistryPicker { selected: current, status }
+    }
+}
+
+/// registry를 바꿀 때 보관하는 트리 화면 상태 (펼친 referrer, 선택 위치, 배지)
+pub struct TreeState {
+    pub items: Vec<String>,
+    pub item_types: Vec<usize>,
+    pub full_image_names: Vec<String>,
+    pub selected_index: usize,
+    pub scroll_offset: usize,
+    pub marks: HashMap<String, TagMark>,
+    pub severities: HashMap<String, TagMark>,
+    pub artifacts: HashMap<String, Descriptor>,
+    pub compare_selection: Vec<String>,
+    pub row_registry: HashMap<String, usize>,
+}
+
+fn centered(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect { x: area.x + (area.width - width) / 2, y: area.y + (area.height - height) / 2, width, height }
+}
+
+pub fn render_picker<B: Backend>(f: &mut Frame<B>, picker: &RegistryPicker, choices: &[RegistryChoice], current: usize) {
+    let name_width = choices.iter().map(|c| c.name.chars().count()).max().unwrap_or(0);
+    let url_width = choices.iter().map(|c| c.url.chars().count()).max().unwrap_or(0);
+    let items: Vec<ListItem> = choices
+        .iter()
+        .enumerate()
+        .map(|(i, choice)| {
+            let marker = if i == current { "* " } else { "  " };
+            let (status, color) = picker.status.get(i).unwrap_or(&Reachability::Unknown).label();
+            let mut style = Style::default();
+            if i == picker.selected {
+                style = style.bg(Color::Yellow).fg(Color::Black).add_modifier(Modifier::BOLD);
+            }
+            ListItem::new(Spans::from(vec![
+                Span::styled(format!("{}{:<nw$}  {:<uw$}  ", marker, choice.name, choice.url, nw = name_width, uw = url_width), style),
+                Span::styled(status, Style::default().fg(color)),
+            ]))
+        })
+        .collect();
+    let status_width = picker.status.iter().map(|s| s.label().0.chars().count()).max().unwrap_or(0);
+    let width = (2 + name_width + 2 + url_width + 2 + status_width + 2) as u16;
+    let area = centered(width.max(50), choices.len() as u16 + 2, f.size());
+    let block = Block::default()
+        .title("Registries  (↑/↓, Enter: switch, Esc: close)")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    f.render_widget(Clear, area);
+    f.render_widget(List::new(items).block(block).style(Style::default().bg(Color::Black)), area);
+}
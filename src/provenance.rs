@@ -0,0 +1,239 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error as StdError;
+
+use crate::attestation::{self, AttachedDocument};
+use crate::registry::{self, Platform};
+use crate::sbom::SbomFormat;
+
+pub const PREDICATE_SLSA_V02: &str = "https://slsa.dev/provenance/v0.2";
+pub const PREDICATE_SLSA_V1: &str = "https://slsa.dev/provenance/v1";
+/// BuildKit이 provenance metadata 안에 VCS 정보를 넣는 키
+const BUILDKIT_METADATA: &str = "https://mobyproject.org/buildkit@v1#metadata";
+
+/// SLSA provenance에서 보여 줄 항목
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Provenance {
+    pub predicate_type: String,
+    pub builder: String,
+    pub build_type: String,
+    pub source_repo: Option<String>,
+    pub commit: Option<String>,
+    /// Dockerfile 경로 등 빌드 진입점
+    pub entry_point: Option<String>,
+    pub started_on: Option<String>,
+    pub finished_on: Option<String>,
+    /// 빌드 인자, frontend 등 (평탄화한 키 → 값)
+    pub parameters: BTreeMap<String, String>,
+    /// 빌드에 쓰인 베이스 이미지, 소스 등 ("uri@digest")
+    pub materials: Vec<String>,
+}
+
+/// 이미지에 붙은 in-toto attestation 하나의 요약
+#[derive(Debug, Clone, Serialize)]
+pub struct Attestation {
+    pub source: String,
+    pub predicate_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provenance: Option<Provenance>,
+    /// provenance가 아닌 문서의 한 줄 설명. 예: "SPDX SBOM, 12 packages"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+fn text(value: &Value, pointer: &str) -> Option<String> {
+    value.pointer(pointer).and_then(|v| v.as_str()).filter(|s| !s.is_empty()).map(|s| s.to_string())
+}
+
+/// {"sha1": "…"} 형태에서 첫 다이제스트 값을 "알고리즘:값"으로
+fn first_digest(value: Option<&Value>) -> Option<String> {
+    let (algorithm, digest) = value?.as_object()?.iter().next()?;
+    Some(format!("{}:{}", algorithm, digest.as_str()?))
+}
+
+/// 중첩된 파라미터를 "a.b" 키로 평탄화합니다.
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(&key, value, out);
+            }
+        }
+        Value::String(s) => {
+            out.insert(prefix.to_string(), s.clone());
+        }
+        Value::Null => {}
+        other => {
+            out.insert(prefix.to_string(), other.to_string());
+        }
+    }
+}
+
+fn materials(list: Option<&Value>, digest_key: &str) -> Vec<String> {
+    let empty = vec![];
+    list.and_then(|m| m.as_array())
+        .unwrap_or(&empty)
+        .iter()
+        .filter_map(|m| {
+            let uri = m.get("uri").and_then(|u| u.as_str())?;
+            Some(match first_digest(m.get(digest_key)) {
+                Some(digest) => format!("{}@{}", uri, digest),
+                None => uri.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// "https://github.com/acme/app.git#refs/heads/main" → 저장소 부분
+fn repo_from_uri(uri: &str) -> String {
+    uri.split('#').next().unwrap_or(uri).to_string()
+}
+
+fn parse_v02(predicate: &Value) -> Provenance {
+    let mut parameters = BTreeMap::new();
+    if let Some(params) = predicate.pointer("/invocation/parameters") {
+        flatten("", params, &mut parameters);
+    }
+    let vcs = predicate.pointer("/metadata").and_then(|m| m.get(BUILDKIT_METADATA)).and_then(|m| m.get("vcs"));
+    let config_uri = text(predicate, "/invocation/configSource/uri");
+    Provenance {
+        predicate_type: PREDICATE_SLSA_V02.to_string(),
+        builder: text(predicate, "/builder/id").unwrap_or_default(),
+        build_type: text(predicate, "/buildType").unwrap_or_default(),
+        source_repo: vcs.and_then(|v| text(v, "/source")).or_else(|| config_uri.as_deref().map(repo_from_uri)),
+        commit: vcs
+            .and_then(|v| text(v, "/revision"))
+            .or_else(|| first_digest(predicate.pointer("/invocation/configSource/digest")).map(|d| d.split_once(':').map(|(_, c)| c.to_string()).unwrap_or(d))),
+        entry_point: text(predicate, "/invocation/configSource/entryPoint"),
+        started_on: text(predicate, "/metadata/buildStartedOn"),
+        finished_on: text(predicate, "/metadata/buildFinishedOn"),
+        parameters,
+        materials: materials(predicate.get("materials"), "digest"),
+    }
+}
+
+fn parse_v1(predicate: &Value) -> Provenance {
+    let mut parameters = BTreeMap::new();
+    if let Some(params) = predicate.pointer("/buildDefinition/externalParameters") {
+        flatten("", params, &mut parameters);
+    }
+    let config_uri = text(predicate, "/buildDefinition/externalParameters/configSource/uri")
+        .or_else(|| text(predicate, "/buildDefinition/externalParameters/workflow/repository"));
+    let vcs = predicate.pointer("/runDetails/metadata").and_then(|m| m.get(BUILDKIT_METADATA)).and_then(|m| m.get("vcs"));
+    Provenance {
+        predicate_type: PREDICATE_SLSA_V1.to_string(),
+        builder: text(predicate, "/runDetails/builder/id").unwrap_or_default(),
+        build_type: text(predicate, "/buildDefinition/buildType").unwrap_or_default(),
+        source_repo: vcs.and_then(|v| text(v, "/source")).or_else(|| config_uri.as_deref().map(repo_from_uri)),
+        commit: vcs.and_then(|v| text(v, "/revision")).or_else(|| {
+            first_digest(predicate.pointer("/buildDefinition/externalParameters/configSource/digest"))
+                .map(|d| d.split_once(':').map(|(_, c)| c.to_string()).unwrap_or(d))
+        }),
+        entry_point: text(predicate, "/buildDefinition/externalParameters/configSource/path")
+            .or_else(|| text(predicate, "/buildDefinition/externalParameters/workflow/path")),
+        started_on: text(predicate, "/runDetails/metadata/startedOn"),
+        finished_on: text(predicate, "/runDetails/metadata/finishedOn"),
+        parameters,
+        materials: materials(predicate.pointer("/buildDefinition/resolvedDependencies"), "digest"),
+    }
+}
+
+pub fn parse(predicate_type: &str, predicate: &Value) -> Option<Provenance> {
+    if predicate_type == PREDICATE_SLSA_V02 {
+        Some(parse_v02(predicate))
+    } else if predicate_type.starts_with(PREDICATE_SLSA_V1) {
+        Some(parse_v1(predicate))
+    } else {
+        None
+    }
+}
+
+fn describe(document: &AttachedDocument) -> String {
+    if let Some(format) = SbomFormat::detect(&document.document) {
+        let packages = crate::sbom::parse_packages(format, &document.document).len();
+        return format!("{} SBOM, {} packages", format, packages);
+    }
+    match &document.document {
+        Value::Object(map) => {
+            let keys: Vec<&str> = map.keys().take(5).map(|k| k.as_str()).collect();
+            format!("fields: {}", keys.join(", "))
+        }
+        other => other.to_string().chars().take(60).collect(),
+    }
+}
+
+/// 첨부 문서 중 in-toto statement만 골라 요약합니다.
+pub fn attestations_from_documents(documents: &[AttachedDocument]) -> Vec<Attestation> {
+    documents
+        .iter()
+        .filter_map(|doc| {
+            let predicate_type = doc.predicate_type.clone()?;
+            let provenance = parse(&predicate_type, &doc.document);
+            let description = provenance.is_none().then(|| describe(doc));
+            Some(Attestation { source: doc.source.clone(), predicate_type, provenance, description })
+        })
+        .collect()
+}
+
+pub async fn fetch_attestations(image: &str, reference: &str, platform: &Platform) -> Result<Vec<Attestation>, Box<dyn StdError>> {
+    let documents = attestation::fetch_documents(image, reference, platform).await?;
+    Ok(attestations_from_documents(&documents))
+}
+
+/// provenance 상세를 "이름: 값" 줄로
+pub fn provenance_lines(provenance: &Provenance) -> Vec<String> {
+    let mut lines = vec![format!("  Builder:     {}", provenance.builder), format!("  Build type:  {}", provenance.build_type)];
+    let optional = [
+        ("Source repo", &provenance.source_repo),
+        ("Commit", &provenance.commit),
+        ("Entry point", &provenance.entry_point),
+        ("Started", &provenance.started_on),
+        ("Finished", &provenance.finished_on),
+    ];
+    for (name, value) in optional {
+        if let Some(value) = value {
+            lines.push(format!("  {:<12} {}", format!("{}:", name), value));
+        }
+    }
+    if !provenance.parameters.is_empty() {
+        lines.push("  Parameters:".to_string());
+        lines.extend(provenance.parameters.iter().map(|(k, v)| format!("    {} = {}", k, v)));
+    }
+    if !provenance.materials.is_empty() {
+        lines.push("  Materials:".to_string());
+        lines.extend(provenance.materials.iter().map(|m| format!("    {}", m)));
+    }
+    lines
+}
+
+/// 상세 팝업/CLI에 표시할 attestation 목록
+pub fn report_lines(attestations: &[Attestation]) -> Vec<String> {
+    if attestations.is_empty() {
+        return vec!["No in-toto attestations are attached to this image.".to_string()];
+    }
+    let mut lines = Vec::new();
+    for attestation in attestations {
+        lines.push(format!("{}  ({})", attestation.predicate_type, attestation.source));
+        match (&attestation.provenance, &attestation.description) {
+            (Some(provenance), _) => lines.extend(provenance_lines(provenance)),
+            (None, Some(description)) => lines.push(format!("  {}", description)),
+            (None, None) => {}
+        }
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// 인덱스의 자식 매니페스트 한 줄 설명. attestation 매니페스트는 대상 이미지를 표시
+pub fn index_entry_label(descriptor: &registry::Descriptor) -> String {
+    let digest = attestation::short_digest(&descriptor.digest);
+    let reference_type = descriptor.annotations.get(attestation::ANNOTATION_REFERENCE_TYPE);
+    if reference_type.map(|t| t.as_str()) == Some(attestation::REFERENCE_TYPE_ATTESTATION) {
+        let target = descriptor.annotations.get(attestation::ANNOTATION_REFERENCE_DIGEST).map(|d| attestation::short_digest(d)).unwrap_or_default();
+        return format!("{}  attestations for {}", digest, target);
+    }
+    let platform = descriptor.platform.as_ref().map(|p| p.to_string()).unwrap_or_else(|| "unknown platform".to_string());
+    format!("{}  {}", digest, platform)
+}
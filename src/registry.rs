@@ -1,14 +1,195 @@
-use reqwest::Error;
-use serde::Deserialize;
+use reqwest::header::{HeaderValue, ACCEPT, AUTHORIZATION, CONTENT_TYPE, ETAG, IF_NONE_MATCH};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::error::Error as StdError;
+use std::io::{Read, Write};
 use std::sync::Mutex;
+use std::time::Duration;
+use futures::StreamExt;
 use lazy_static::lazy_static;
-use std::collections::HashMap;
-use crate::ui::CompatibilityRow;
+use std::collections::{BTreeMap, HashMap};
+use crate::cache;
+use crate::digest::{self, DigestReader, DigestWriter};
+use crate::history;
+use crate::ui::{CompatibilityRow, TreeItems};
+
+pub const MEDIA_TYPE_OCI_INDEX: &str = "application/vnd.oci.image.index.v1+json";
+pub const MEDIA_TYPE_OCI_MANIFEST: &str = "application/vnd.oci.image.manifest.v1+json";
+pub const MEDIA_TYPE_DOCKER_LIST: &str = "application/vnd.docker.distribution.manifest.list.v2+json";
+pub const MEDIA_TYPE_DOCKER_MANIFEST: &str = "application/vnd.docker.distribution.manifest.v2+json";
+pub const MEDIA_TYPE_DOCKER_CONFIG: &str = "application/vnd.docker.container.image.v1+json";
+pub const MEDIA_TYPE_DOCKER_LAYER: &str = "application/vnd.docker.image.rootfs.diff.tar";
+pub const MEDIA_TYPE_DOCKER_LAYER_GZIP: &str = "application/vnd.docker.image.rootfs.diff.tar.gzip";
+
+// 청크 업로드 시 한 번에 보내는 크기
+const UPLOAD_CHUNK_SIZE: usize = 16 * 1024 * 1024;
+
+// 매니페스트 요청 시 registry가 schema1으로 변환하지 않도록 지원하는 타입을 모두 명시
+const MANIFEST_ACCEPT: &str = "application/vnd.oci.image.index.v1+json, \
+application/vnd.oci.image.manifest.v1+json, \
+application/vnd.docker.distribution.manifest.list.v2+json, \
+application/vnd.docker.distribution.manifest.v2+json";
+
+// 동시에 보내는 요청 수 기본값 (프로필의 concurrency로 바꿀 수 있음)
+const DEFAULT_CONCURRENCY: usize = 8;
 
 lazy_static! {
-    static ref REGISTRY_URL: Mutex<String> = Mutex::new("http://172.16.88.137:30353/v2/".to_string());
+    static ref REGISTRY_URL: Mutex<String> = Mutex::new(format!("{}/v2/", crate::config::DEFAULT_REGISTRY_URL));
+    static ref CLIENT: Mutex<Client> = Mutex::new(Client::default());
+    static ref CONCURRENCY: Mutex<usize> = Mutex::new(DEFAULT_CONCURRENCY);
+}
+
+/// registry 인증 방식
+#[derive(Debug, Clone)]
+pub enum Auth {
+    Basic { username: String, password: Option<String> },
+    Bearer(String),
+}
+
+/// 모든 registry 요청이 공유하는 클라이언트 설정
+#[derive(Debug, Clone, Default)]
+pub struct ClientSettings {
+    pub auth: Option<Auth>,
+    /// 인증 정보를 보낼 registry 주소. 다른 호스트로 가는 요청에는 붙이지 않음
+    pub auth_url: Option<String>,
+    /// 추가로 신뢰할 CA 인증서 (PEM, 여러 개 가능)
+    pub ca_pem: Option<Vec<u8>>,
+    pub insecure: bool,
+    pub connect_timeout: Option<Duration>,
+    /// 응답 데이터를 기다리는 최대 시간. 큰 레이어도 받을 수 있도록 요청 전체가 아니라 읽기마다 적용
+    pub read_timeout: Option<Duration>,
+    pub concurrency: Option<usize>,
+}
+
+/// registry 요청용 클라이언트. Authorization은 프로필의 registry로 가는 요청에만 붙임
+#[derive(Clone, Default)]
+pub struct Client {
+    http: reqwest::Client,
+    authorization: Option<(Origin, HeaderValue)>,
+}
+
+/// (scheme, host, port): 인증 정보를 보내도 되는 대상
+type Origin = (String, String, u16);
+
+fn origin(url: &str) -> Option<Origin> {
+    let url = reqwest::Url::parse(url).ok()?;
+    Some((url.scheme().to_string(), url.host_str()?.to_ascii_lowercase(), url.port_or_known_default()?))
+}
+
+/// 두 URL이 같은 registry(scheme, host, port)를 가리키는지
+pub fn same_registry(a: &str, b: &str) -> bool {
+    matches!((origin(a), origin(b)), (Some(a), Some(b)) if a == b)
+}
+
+impl Client {
+    pub fn get(&self, url: &str) -> reqwest::RequestBuilder {
+        self.request(reqwest::Method::GET, url)
+    }
+
+    pub fn head(&self, url: &str) -> reqwest::RequestBuilder {
+        self.request(reqwest::Method::HEAD, url)
+    }
+
+    pub fn post(&self, url: &str) -> reqwest::RequestBuilder {
+        self.request(reqwest::Method::POST, url)
+    }
+
+    pub fn put(&self, url: &str) -> reqwest::RequestBuilder {
+        self.request(reqwest::Method::PUT, url)
+    }
+
+    pub fn patch(&self, url: &str) -> reqwest::RequestBuilder {
+        self.request(reqwest::Method::PATCH, url)
+    }
+
+    fn request(&self, method: reqwest::Method, url: &str) -> reqwest::RequestBuilder {
+        let request = self.http.request(method, url);
+        match &self.authorization {
+            // 리다이렉트는 reqwest가 다른 호스트로 갈 때 Authorization을 빼서 따라감
+            Some((allowed, value)) if origin(url).as_ref() == Some(allowed) => request.header(AUTHORIZATION, value.clone()),
+            _ => request,
+        }
+    }
+}
+
+/// 설정대로 클라이언트를 만듭니다.
+pub fn build_client(settings: &ClientSettings) -> Result<Client, Box<dyn StdError>> {
+    let mut builder = reqwest::Client::builder();
+    let mut authorization = None;
+    if let Some(auth) = &settings.auth {
+        let value = match auth {
+            Auth::Basic { username, password } => {
+                let credentials = format!("{}:{}", username, password.as_deref().unwrap_or(""));
+                format!("Basic {}", openssl::base64::encode_block(credentials.as_bytes()))
+            }
+            Auth::Bearer(token) => format!("Bearer {}", token),
+        };
+        let mut header = HeaderValue::from_str(&value).map_err(|_| "credentials contain characters not allowed in an HTTP header")?;
+        header.set_sensitive(true);
+        let url = settings.auth_url.as_deref().ok_or("credentials need the registry URL they belong to")?;
+        let allowed = origin(url).ok_or_else(|| format!("invalid registry URL '{}'", url))?;
+        authorization = Some((allowed, header));
+    }
+    if let Some(pem) = &settings.ca_pem {
+        for certificate in reqwest::Certificate::from_pem_bundle(pem)? {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    if settings.insecure {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    if let Some(timeout) = settings.connect_timeout {
+        builder = builder.connect_timeout(timeout);
+    }
+    if let Some(timeout) = settings.read_timeout {
+        builder = builder.read_timeout(timeout);
+    }
+    Ok(Client { http: builder.build()?, authorization })
+}
+
+/// 한 작업 안에서만 쓰는 registry 주소와 클라이언트. diff-registries처럼 두 registry를 각자의 인증 정보로 읽을 때
+#[derive(Clone)]
+pub struct Endpoint {
+    url: String,
+    client: Client,
+    concurrency: usize,
+}
+
+tokio::task_local! {
+    static ENDPOINT: Endpoint;
+}
+
+impl Endpoint {
+    pub fn new(url: &str, settings: &ClientSettings) -> Result<Endpoint, Box<dyn StdError>> {
+        Ok(Endpoint {
+            url: format!("{}/v2/", url.trim_end_matches('/')),
+            client: build_client(settings)?,
+            concurrency: settings.concurrency.unwrap_or(DEFAULT_CONCURRENCY).max(1),
+        })
+    }
+
+    /// `future` 안의 registry 요청을 이 registry로 보냅니다. 전역 주소와 클라이언트는 그대로 둠
+    pub async fn scope<F: std::future::Future>(self, future: F) -> F::Output {
+        ENDPOINT.scope(self, future).await
+    }
+}
+
+/// 공유 클라이언트를 설정대로 다시 만듭니다.
+pub fn configure_client(settings: &ClientSettings) -> Result<(), Box<dyn StdError>> {
+    *CLIENT.lock().unwrap() = build_client(settings)?;
+    *CONCURRENCY.lock().unwrap() = settings.concurrency.unwrap_or(DEFAULT_CONCURRENCY).max(1);
+    Ok(())
+}
+
+/// 설정이 적용된 공유 클라이언트 (복제 비용이 작음)
+pub fn client() -> Client {
+    ENDPOINT.try_with(|endpoint| endpoint.client.clone()).unwrap_or_else(|_| CLIENT.lock().unwrap().clone())
+}
+
+pub fn concurrency() -> usize {
+    ENDPOINT.try_with(|endpoint| endpoint.concurrency).unwrap_or_else(|_| *CONCURRENCY.lock().unwrap())
 }
 
 pub fn set_registry_url(url: &str) {
@@ -18,55 +199,568 @@ pub fn set_registry_url(url: &str) {
 
 // 현재 설정된 URL을 반환하는 함수
 pub fn get_registry_url() -> String {
-    REGISTRY_URL.lock().unwrap().clone()
+    ENDPOINT.try_with(|endpoint| endpoint.url.clone()).unwrap_or_else(|_| REGISTRY_URL.lock().unwrap().clone())
 }
 
-#[derive(Debug, Deserialize)]
+/// 카탈로그/태그 목록 요청의 오류. 트리를 백그라운드 작업에서 다시 읽을 수 있도록 Send
+pub type FetchError = Box<dyn StdError + Send + Sync>;
+
+/// --offline이면 registry에 요청하는 대신 `what`을 알리는 오류를 반환
+fn ensure_online(what: &str) -> Result<(), cache::OfflineMiss> {
+    if cache::is_offline() {
+        return Err(cache::OfflineMiss(what.to_string()));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 struct CatalogResponse {
     repositories: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 struct TagsResponse {
     tags: Option<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Platform {
+    pub architecture: String,
+    pub os: String,
+    #[serde(rename = "os.version", default, skip_serializing_if = "Option::is_none")]
+    pub os_version: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+}
+
+impl Platform {
+    /// "linux/arm64/v8" 형식의 문자열을 파싱합니다.
+    pub fn parse(text: &str) -> Option<Platform> {
+        let mut parts = text.split('/');
+        let os = parts.next().filter(|s| !s.is_empty())?.to_string();
+        let architecture = parts.next().filter(|s| !s.is_empty())?.to_string();
+        let variant = parts.next().map(|s| s.to_string());
+        Some(Platform { architecture, os, os_version: None, variant })
+    }
+
+    /// variant가 지정되지 않은 경우 variant는 비교하지 않습니다.
+    pub fn matches(&self, wanted: &Platform) -> bool {
+        self.os == wanted.os
+            && self.architecture == wanted.architecture
+            && (wanted.variant.is_none() || self.variant == wanted.variant)
+    }
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.variant {
+            Some(variant) => write!(f, "{}/{}/{}", self.os, self.architecture, variant),
+            None => write!(f, "{}/{}", self.os, self.architecture),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Descriptor {
+    #[serde(default)]
+    pub media_type: String,
+    pub digest: String,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platform: Option<Platform>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_type: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+/// 이미지 매니페스트와 인덱스(manifest list)를 모두 표현하는 구조체
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub struct Manifest {
-    // Manifest 구조체에 필요한 필드를 정의하세요
-    // 예: schemaVersion, config, layers 등
+    pub schema_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub artifact_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<Descriptor>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<Descriptor>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub manifests: Vec<Descriptor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<Descriptor>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub annotations: BTreeMap<String, String>,
+}
+
+impl Manifest {
+    pub fn is_index(&self) -> bool {
+        matches!(self.media_type.as_deref(), Some(MEDIA_TYPE_OCI_INDEX) | Some(MEDIA_TYPE_DOCKER_LIST))
+            || (self.config.is_none() && !self.manifests.is_empty())
+    }
+}
+
+/// registry에서 받은 매니페스트 원본 바이트와 메타데이터
+/// 다이제스트는 원본 바이트 기준이므로 재직렬화하지 않고 그대로 보관합니다.
+pub struct RawManifest {
+    pub media_type: String,
+    pub digest: String,
+    pub bytes: Vec<u8>,
+}
+
+impl RawManifest {
+    pub fn parse(&self) -> Result<Manifest, Box<dyn StdError>> {
+        let manifest: Manifest = serde_json::from_slice(&self.bytes)?;
+        if manifest.schema_version != 2 {
+            return Err(format!("unsupported manifest schema version {} ({})", manifest.schema_version, self.digest).into());
+        }
+        Ok(manifest)
+    }
+}
+
+/// 이미지 참조("repo:tag", "repo@sha256:...", "repo")를 (repo, reference)로 분리합니다.
+/// 태그가 없으면 docker처럼 "latest"
+pub fn parse_reference(reference: &str) -> Result<(String, String), Box<dyn StdError>> {
+    if let Some((repo, digest)) = reference.split_once('@') {
+        if digest::split_digest(digest).is_none() {
+            return Err(format!("invalid digest in reference '{}'", reference).into());
+        }
+        return Ok((repo.to_string(), digest.to_string()));
+    }
+    // "host:5000/app"의 포트를 태그로 보지 않도록 마지막 경로 조각에서만 찾음
+    let last_segment = reference.rsplit('/').next().unwrap_or(reference);
+    let (repo, tag) = match last_segment.contains(':') {
+        true => reference.rsplit_once(':').unwrap(),
+        false => (reference, "latest"),
+    };
+    if repo.is_empty() || tag.is_empty() || repo.ends_with('/') {
+        return Err(format!("invalid image reference '{}', expected <repo>[:<tag>]", reference).into());
+    }
+    Ok((repo.to_string(), tag.to_string()))
+}
+
+/// registry URL에서 "host[:port]" 부분만 반환합니다.
+pub fn registry_host() -> String {
+    let url = get_registry_url();
+    let without_scheme = url.split_once("://").map(|(_, rest)| rest).unwrap_or(&url);
+    without_scheme.trim_end_matches("/v2/").trim_end_matches('/').to_string()
 }
 // JSON 데이터를 받아오는 manifest 구조체 정의
 pub async fn fetch_manifest(image: &str, tag: &str) -> Result<String, Box<dyn StdError>> {
+    // 오프라인이면 캐시된 매니페스트 원본으로 대신함
+    if cache::is_offline() {
+        let raw = fetch_manifest_raw(image, tag).await?;
+        return Ok(serde_json::to_string_pretty(&serde_json::from_slice::<Value>(&raw.bytes)?)?);
+    }
     let url = format!("{}{}/manifests/{}", get_registry_url(), image, tag);
-    let client = reqwest::Client::new();
+    let client = client();
 
     // API 호출
     let resp = client.get(&url).send().await?;
+    let success = resp.status().is_success();
+    let bytes = resp.bytes().await?;
 
     // JSON 데이터를 Value 형태로 직접 반환
-    let manifest: Value = resp.json().await?;
+    let manifest: Value = serde_json::from_slice(&bytes)?;
+    // 오프라인에서도 볼 수 있도록 캐시에 남김. schema1으로 변환된 응답은 태그의 다이제스트가 달라지므로 제외
+    if let (true, Some(media_type)) = (success, manifest.get("mediaType").and_then(|m| m.as_str())) {
+        let base = get_registry_url();
+        let digest = digest::sha256_digest(&bytes);
+        cache::write_manifest(&base, &digest, media_type, &bytes);
+        if !tag.starts_with("sha256:") {
+            history::observe(&base, image, tag, &digest);
+            cache::write_ref(&base, image, tag, &cache::TagRef { digest, etag: None });
+        }
+    }
     Ok(serde_json::to_string_pretty(&manifest)?) // 포맷된 JSON 문자열 반환
 }
 
 
-pub async fn fetch_images() -> Result<Vec<String>, Error> {
-    let url = format!("{}{}", get_registry_url(), "_catalog");
-    let client = reqwest::Client::new();
-    let resp = client.get(&url).send().await?;
+/// Accept 헤더를 지정하여 매니페스트 원본을 가져오고 다이제스트를 검증합니다.
+/// 다이제스트로 찾으면 캐시를 그대로 쓰고, 태그로 찾으면 마지막 다이제스트를 If-None-Match로 재검증합니다.
+pub async fn fetch_manifest_raw(image: &str, reference: &str) -> Result<RawManifest, Box<dyn StdError>> {
+    let base = get_registry_url();
+    let url = format!("{}{}/manifests/{}", base, image, reference);
+    let client = client();
+
+    if reference.starts_with("sha256:") {
+        if let Some((media_type, bytes)) = cache::read_manifest(&base, reference) {
+            return Ok(RawManifest { media_type, digest: reference.to_string(), bytes });
+        }
+    }
+    let cached = match cache::read_ref(&base, image, reference) {
+        Some(tag_ref) => cache::read_manifest(&base, &tag_ref.digest).map(|manifest| (tag_ref, manifest)),
+        None => None,
+    };
+    if cache::is_offline() {
+        let (tag_ref, (media_type, bytes)) = cached.ok_or_else(|| cache::OfflineMiss(format!("manifest {}:{}", image, reference)))?;
+        return Ok(RawManifest { media_type, digest: tag_ref.digest, bytes });
+    }
+
+    let mut request = client.get(&url).header(ACCEPT, MANIFEST_ACCEPT);
+    if let Some((tag_ref, _)) = &cached {
+        // registry가 ETag를 주지 않았으면 distribution과 같은 "다이제스트" 형식으로 보냄
+        let etag = tag_ref.etag.clone().unwrap_or_else(|| format!("\"{}\"", tag_ref.digest));
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    let resp = request.send().await?;
+    if resp.status() == StatusCode::NOT_MODIFIED {
+        if let Some((tag_ref, (media_type, bytes))) = cached {
+            history::observe(&base, image, reference, &tag_ref.digest);
+            return Ok(RawManifest { media_type, digest: tag_ref.digest, bytes });
+        }
+    }
+    let resp = resp.error_for_status()?;
+    let etag = resp.headers().get(ETAG).and_then(|v| v.to_str().ok()).map(|v| v.to_string());
+    let content_type = resp
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(';').next().unwrap_or(v).trim().to_string());
+    let bytes = resp.bytes().await?.to_vec();
+    let digest = digest::sha256_digest(&bytes);
+
+    if reference.starts_with("sha256:") && reference != digest {
+        return Err(format!("manifest digest mismatch for {}@{}: got {}", image, reference, digest).into());
+    }
+
+    // Content-Type이 없거나 일반 JSON이면 본문의 mediaType 필드를 사용
+    let media_type = match content_type {
+        Some(ct) if ct != "application/json" && !ct.is_empty() => ct,
+        _ => serde_json::from_slice::<Value>(&bytes)
+            .ok()
+            .and_then(|v| v.get("mediaType").and_then(|m| m.as_str()).map(|m| m.to_string()))
+            .unwrap_or_else(|| MEDIA_TYPE_OCI_MANIFEST.to_string()),
+    };
+
+    cache::write_manifest(&base, &digest, &media_type, &bytes);
+    if !reference.starts_with("sha256:") {
+        cache::write_ref(&base, image, reference, &cache::TagRef { digest: digest.clone(), etag });
+        history::observe(&base, image, reference, &digest);
+    }
+    Ok(RawManifest { media_type, digest, bytes })
+}
+
+/// 태그가 인덱스를 가리키면 지정한 플랫폼의 이미지 매니페스트로 내려갑니다.
+pub async fn fetch_image_manifest(image: &str, reference: &str, platform: &Platform) -> Result<(RawManifest, Manifest), Box<dyn StdError>> {
+    let raw = fetch_manifest_raw(image, reference).await?;
+    let manifest = raw.parse()?;
+    if !manifest.is_index() {
+        return Ok((raw, manifest));
+    }
+
+    let child = manifest
+        .manifests
+        .iter()
+        .find(|m| m.platform.as_ref().is_some_and(|p| p.matches(platform)))
+        .ok_or_else(|| format!("{}:{} has no manifest for platform {}", image, reference, platform))?;
+    let child_raw = fetch_manifest_raw(image, &child.digest).await?;
+    let child_manifest = child_raw.parse()?;
+    if child_manifest.is_index() {
+        return Err(format!("nested index in {}:{} is not supported", image, reference).into());
+    }
+    Ok((child_raw, child_manifest))
+}
+
+/// 플랫폼을 고르지 않았을 때 멀티 아키텍처 이미지에서 읽는 플랫폼
+pub const DEFAULT_PLATFORM: &str = "linux/amd64";
+
+/// 인덱스에서 읽을 플랫폼: `preferred`(--platform), 없으면 linux/amd64,
+/// 그것도 없으면 (arm64 전용 이미지 등) attestation이 아닌 첫 번째 이미지의 플랫폼
+pub fn pick_platform(children: &[Descriptor], preferred: Option<&Platform>) -> Platform {
+    if let Some(preferred) = preferred {
+        return preferred.clone();
+    }
+    let default = Platform::parse(DEFAULT_PLATFORM).unwrap();
+    let platforms: Vec<&Platform> = children.iter().filter_map(|c| c.platform.as_ref()).filter(|p| p.os != "unknown").collect();
+    if platforms.is_empty() || platforms.iter().any(|p| p.matches(&default)) {
+        return default;
+    }
+    platforms[0].clone()
+}
+
+/// 태그를 읽을 플랫폼 (`pick_platform`). 인덱스가 아니면 플랫폼은 쓰이지 않음
+pub async fn image_platform(image: &str, reference: &str, preferred: Option<&Platform>) -> Platform {
+    if preferred.is_none() {
+        if let Ok(manifest) = fetch_manifest_raw(image, reference).await.and_then(|raw| raw.parse()) {
+            return pick_platform(&manifest.manifests, None);
+        }
+    }
+    pick_platform(&[], preferred)
+}
+
+/// HEAD 요청으로 태그가 가리키는 매니페스트 다이제스트만 확인합니다.
+/// Docker-Content-Digest 헤더가 없는 registry는 본문을 받아 직접 계산합니다.
+pub async fn fetch_manifest_digest(image: &str, reference: &str) -> Result<String, Box<dyn StdError>> {
+    if cache::is_offline() {
+        return Ok(fetch_manifest_raw(image, reference).await?.digest);
+    }
+    let url = format!("{}{}/manifests/{}", get_registry_url(), image, reference);
+    let client = client();
+
+    let resp = client.head(&url).header(ACCEPT, MANIFEST_ACCEPT).send().await?.error_for_status()?;
+    if let Some(digest) = resp.headers().get("Docker-Content-Digest").and_then(|v| v.to_str().ok()) {
+        history::observe(&get_registry_url(), image, reference, digest);
+        return Ok(digest.to_string());
+    }
+    Ok(fetch_manifest_raw(image, reference).await?.digest)
+}
+
+/// blob을 스트리밍으로 받아 `out`에 쓰면서 크기와 다이제스트를 검증합니다.
+pub async fn fetch_blob<W: Write>(image: &str, descriptor: &Descriptor, out: W) -> Result<W, Box<dyn StdError>> {
+    ensure_online(&format!("blob {}", descriptor.digest))?;
+    let url = format!("{}{}/blobs/{}", get_registry_url(), image, descriptor.digest);
+    let client = client();
+
+    let mut resp = client.get(&url).send().await?.error_for_status()?;
+    let mut writer = DigestWriter::new(out);
+    let mut received = 0u64;
+    while let Some(chunk) = resp.chunk().await? {
+        // 기대 크기를 넘는 데이터는 출력(tar 등)을 깨뜨리므로 즉시 중단
+        received += chunk.len() as u64;
+        if received > descriptor.size {
+            return Err(format!("blob {} is larger than expected {} bytes", descriptor.digest, descriptor.size).into());
+        }
+        writer.write_all(&chunk)?;
+    }
+
+    let (out, written, digest) = writer.finish();
+    if written != descriptor.size {
+        return Err(format!("blob {} size mismatch: expected {} bytes, got {}", descriptor.digest, descriptor.size, written).into());
+    }
+    if digest != descriptor.digest {
+        return Err(format!("blob digest mismatch: expected {}, got {}", descriptor.digest, digest).into());
+    }
+    Ok(out)
+}
+
+/// 채널로 전달되는 blob 청크를 동기 Read로 읽게 해 주는 어댑터
+struct ChannelReader {
+    receiver: tokio::sync::mpsc::Receiver<Vec<u8>>,
+    chunk: Vec<u8>,
+    pos: usize,
+}
+
+impl Read for ChannelReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.receiver.blocking_recv() {
+                Some(chunk) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                None => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len() - self.pos);
+        buf[..n].copy_from_slice(&self.chunk[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+/// blob을 내려받으면서 동시에 `consume`(별도 스레드)에서 스트림으로 읽습니다.
+/// 끝까지 읽은 경우 크기와 다이제스트를 검증하고, `consume`이 일찍 끝나면 다운로드도 중단합니다.
+pub async fn read_blob_with<T, F>(image: &str, descriptor: &Descriptor, consume: F) -> Result<T, Box<dyn StdError>>
+where
+    F: FnOnce(&mut dyn Read) -> std::io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    ensure_online(&format!("blob {}", descriptor.digest))?;
+    let url = format!("{}{}/blobs/{}", get_registry_url(), image, descriptor.digest);
+    let client = client();
+    let mut resp = client.get(&url).send().await?.error_for_status()?;
+
+    let (sender, receiver) = tokio::sync::mpsc::channel::<Vec<u8>>(16);
+    let consumer = tokio::task::spawn_blocking(move || {
+        let mut reader = ChannelReader { receiver, chunk: Vec::new(), pos: 0 };
+        consume(&mut reader)
+    });
+
+    let mut hasher = DigestWriter::new(std::io::sink());
+    let mut complete = true;
+    while let Some(chunk) = resp.chunk().await? {
+        hasher.write_all(&chunk)?;
+        if sender.send(chunk.to_vec()).await.is_err() {
+            complete = false;
+            break;
+        }
+    }
+    drop(sender);
+
+    let result = consumer.await??;
+    if complete {
+        let (_, written, digest) = hasher.finish();
+        if written != descriptor.size || digest != descriptor.digest {
+            return Err(format!("blob digest mismatch: expected {}, got {} ({} bytes)", descriptor.digest, digest, written).into());
+        }
+    }
+    Ok(result)
+}
+
+pub async fn blob_exists(image: &str, digest: &str) -> Result<bool, Box<dyn StdError>> {
+    ensure_online("pushing blobs")?;
+    let url = format!("{}{}/blobs/{}", get_registry_url(), image, digest);
+    let client = client();
+    let resp = client.head(&url).send().await?;
+    match resp.status() {
+        s if s.is_success() => Ok(true),
+        reqwest::StatusCode::NOT_FOUND => Ok(false),
+        s => Err(format!("HEAD {} failed: {}", url, s).into()),
+    }
+}
+
+// Location 헤더는 상대 경로일 수 있으므로 registry URL 기준으로 해석
+fn upload_location(resp: &reqwest::Response) -> Result<reqwest::Url, Box<dyn StdError>> {
+    let location = resp
+        .headers()
+        .get(reqwest::header::LOCATION)
+        .and_then(|v| v.to_str().ok())
+        .ok_or("registry did not return an upload location")?;
+    Ok(reqwest::Url::parse(&get_registry_url())?.join(location)?)
+}
+
+/// blob을 청크 단위(PATCH)로 업로드하고 PUT으로 완료합니다.
+/// 읽은 데이터의 다이제스트가 descriptor와 다르면 업로드를 완료하지 않습니다.
+pub async fn upload_blob<R: Read>(image: &str, descriptor: &Descriptor, reader: R) -> Result<(), Box<dyn StdError>> {
+    ensure_online("pushing blobs")?;
+    let client = client();
+    let url = format!("{}{}/blobs/uploads/", get_registry_url(), image);
+    let resp = client.post(&url).send().await?.error_for_status()?;
+    let mut location = upload_location(&resp)?;
+
+    let mut reader = DigestReader::new(reader);
+    let mut offset = 0u64;
+    loop {
+        let mut chunk = Vec::with_capacity(UPLOAD_CHUNK_SIZE);
+        (&mut reader).take(UPLOAD_CHUNK_SIZE as u64).read_to_end(&mut chunk)?;
+        if chunk.is_empty() {
+            break;
+        }
+        let end = offset + chunk.len() as u64 - 1;
+        let resp = client
+            .patch(location.as_str())
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .header(reqwest::header::CONTENT_RANGE, format!("{}-{}", offset, end))
+            .body(chunk)
+            .send()
+            .await?
+            .error_for_status()?;
+        location = upload_location(&resp)?;
+        offset = end + 1;
+    }
+
+    let (written, digest) = reader.finish();
+    if written != descriptor.size || digest != descriptor.digest {
+        return Err(format!("blob {} does not match its content (got {}, {} bytes)", descriptor.digest, digest, written).into());
+    }
+
+    location.query_pairs_mut().append_pair("digest", &descriptor.digest);
+    client
+        .put(location.as_str())
+        .header(reqwest::header::CONTENT_LENGTH, 0)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(())
+}
+
+/// 매니페스트를 태그 또는 다이제스트로 PUT 하고 registry가 돌려준 다이제스트를 반환합니다.
+/// Docker-Content-Digest가 보낸 내용의 다이제스트와 다르면 오류. 헤더가 없으면 직접 계산한 값
+pub async fn push_manifest(image: &str, reference: &str, media_type: &str, bytes: &[u8]) -> Result<String, Box<dyn StdError>> {
+    ensure_online("pushing manifests")?;
+    let url = format!("{}{}/manifests/{}", get_registry_url(), image, reference);
+    let client = client();
+    let resp = client
+        .put(&url)
+        .header(CONTENT_TYPE, media_type)
+        .body(bytes.to_vec())
+        .send()
+        .await?;
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        return Err(format!("PUT {} failed: {} {}", url, status, body.trim()).into());
+    }
+    let local = digest::sha256_digest(bytes);
+    match resp.headers().get("Docker-Content-Digest").and_then(|v| v.to_str().ok()) {
+        Some(remote) if remote != local => {
+            Err(format!("PUT {}: registry reports digest {} but the pushed manifest is {}", url, remote, local).into())
+        }
+        Some(remote) => Ok(remote.to_string()),
+        None => Ok(local),
+    }
+}
+
+/// `repos` 목록의 레코드
+#[derive(Debug, Clone, Serialize)]
+pub struct RepositoryEntry {
+    pub name: String,
+}
+
+/// `tags` 목록의 레코드. digest는 요청했을 때만
+#[derive(Debug, Clone, Serialize)]
+pub struct TagEntry {
+    pub repository: String,
+    pub tag: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+}
+
+/// 캐시된 응답이 있으면 ETag로 재검증하는 GET. 304면 캐시된 본문을 쓰고, 새 응답은 `store`로 저장.
+/// 오프라인이면 캐시된 본문만 씀
+async fn get_revalidated<T: Serialize + DeserializeOwned>(
+    url: &str,
+    cached: Option<cache::Entry>,
+    store: impl FnOnce(&cache::Entry),
+) -> Result<T, FetchError> {
+    if cache::is_offline() {
+        let entry = cached.ok_or_else(|| cache::OfflineMiss(url.to_string()))?;
+        return Ok(serde_json::from_value(entry.body)?);
+    }
+    let cached = cached.and_then(|entry| Some((entry.etag?, entry.body)));
+    let mut request = client().get(url);
+    if let Some((etag, _)) = &cached {
+        request = request.header(IF_NONE_MATCH, etag.as_str());
+    }
+    let resp = request.send().await?;
+    if resp.status() == StatusCode::NOT_MODIFIED {
+        if let Some((etag, body)) = cached {
+            if let Ok(parsed) = serde_json::from_value(body.clone()) {
+                // 다시 저장해서 파일 시각을 마지막으로 확인한 시각으로 맞춤
+                store(&cache::Entry { etag: Some(etag), body });
+                return Ok(parsed);
+            }
+        }
+    }
+    let resp = resp.error_for_status()?;
+    let etag = resp.headers().get(ETAG).and_then(|v| v.to_str().ok()).map(|v| v.to_string());
+    let body: T = resp.json().await?;
+    if let Ok(value) = serde_json::to_value(&body) {
+        store(&cache::Entry { etag, body: value });
+    }
+    Ok(body)
+}
+
+pub async fn fetch_images() -> Result<Vec<String>, FetchError> {
+    let base = get_registry_url();
+    let url = format!("{}{}", base, "_catalog");
 
     // `CatalogResponse`로 응답을 디코딩합니다.
-    let catalog: CatalogResponse = resp.json().await?;
+    let catalog: CatalogResponse = get_revalidated(&url, cache::read_catalog(&base), |entry| cache::write_catalog(&base, entry)).await?;
     Ok(catalog.repositories)
 }
 
-pub async fn fetch_tags(image: &str) -> Result<Vec<String>, Error> {
-    let url = format!("{}{}/tags/list", get_registry_url(), image);
-    let client = reqwest::Client::new();
-    let resp = client.get(&url).send().await?;
+pub async fn fetch_tags(image: &str) -> Result<Vec<String>, FetchError> {
+    let base = get_registry_url();
+    let url = format!("{}{}/tags/list", base, image);
 
-    let tags_response: TagsResponse = resp.json().await?;
-    Ok(tags_response.tags.unwrap_or_else(Vec::new))
+    let tags_response: TagsResponse =
+        get_revalidated(&url, cache::read_tags(&base, image), |entry| cache::write_tags(&base, image, entry)).await?;
+    Ok(tags_response.tags.unwrap_or_default())
 }
 
 /// Docker 이미지 이름을 1뎁스와 2뎁스 부분으로 분리합니다.
@@ -91,6 +785,169 @@ pub fn group_images_by_depth(images: Vec<String>) -> HashMap<String, Vec<String>
     grouped_images
 }
 
+/// registry가 404로 응답한 오류인지 확인합니다.
+pub fn is_not_found(error: &(dyn StdError + 'static)) -> bool {
+    error
+        .downcast_ref::<reqwest::Error>()
+        .and_then(|e| e.status())
+        .is_some_and(|status| status == reqwest::StatusCode::NOT_FOUND)
+}
+
+/// 스크립트용 종료 코드를 고르기 위한 오류 분류
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    NotFound,
+    Auth,
+    Network,
+    Other,
+}
+
+pub fn classify_error(error: &(dyn StdError + 'static)) -> FailureKind {
+    if error.is::<cache::OfflineMiss>() {
+        return FailureKind::NotFound;
+    }
+    let Some(error) = error.downcast_ref::<reqwest::Error>() else { return FailureKind::Other };
+    match error.status() {
+        Some(reqwest::StatusCode::NOT_FOUND) => FailureKind::NotFound,
+        Some(reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN) => FailureKind::Auth,
+        Some(_) => FailureKind::Other,
+        None if error.is_connect() || error.is_timeout() || error.is_request() => FailureKind::Network,
+        None => FailureKind::Other,
+    }
+}
+
+/// referrers API를 지원하지 않는 registry에서 쓰는 태그 이름. "sha256:abc" → "sha256-abc"
+pub fn referrers_tag(digest: &str) -> String {
+    digest.replacen(':', "-", 1)
+}
+
+/// `digest`를 subject로 가리키는 아티팩트(서명, SBOM, attestation 등) 목록을 가져옵니다.
+/// OCI 1.1 referrers API가 없으면 `sha256-<hex>` 태그의 인덱스를 대신 읽습니다.
+pub async fn fetch_referrers(image: &str, digest: &str) -> Result<Vec<Descriptor>, Box<dyn StdError>> {
+    ensure_online(&format!("referrers of {}@{}", image, digest))?;
+    let url = format!("{}{}/referrers/{}", get_registry_url(), image, digest);
+    let client = client();
+
+    let resp = client.get(&url).header(ACCEPT, MEDIA_TYPE_OCI_INDEX).send().await?;
+    let is_index = resp
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with(MEDIA_TYPE_OCI_INDEX));
+    // 지원하지 않는 registry는 404나 다른 형식의 응답을 돌려줌
+    if resp.status().is_success() && is_index {
+        let index: Manifest = serde_json::from_slice(&resp.bytes().await?)?;
+        return Ok(index.manifests);
+    }
+
+    let fallback = format!("{}{}/manifests/{}", get_registry_url(), image, referrers_tag(digest));
+    let resp = client.get(&fallback).header(ACCEPT, MEDIA_TYPE_OCI_INDEX).send().await?;
+    if resp.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(Vec::new());
+    }
+    let index: Manifest = serde_json::from_slice(&resp.error_for_status()?.bytes().await?)?;
+    Ok(index.manifests)
+}
+
+/// 트리에 보일 저장소/태그 와일드카드 (`matches_pattern`)
+#[derive(Debug, Clone, Default)]
+pub struct TreeFilter {
+    pub repository: Option<String>,
+    pub tag: Option<String>,
+}
+
+impl TreeFilter {
+    fn wants_repository(&self, repository: &str) -> bool {
+        self.repository.as_ref().is_none_or(|p| matches_pattern(p, repository))
+    }
+
+    /// 필터에 맞지 않는 태그를 빼고 이름순으로 정렬
+    fn apply_to_tags(&self, tags: &mut Vec<String>) {
+        tags.retain(|t| self.tag.as_ref().is_none_or(|p| matches_pattern(p, t)));
+        tags.sort();
+    }
+}
+
+/// 필터에 맞는 저장소와 태그를 이름순으로 읽습니다. 목록 사이에 지워진 저장소(404)는 태그 없이 둠
+pub async fn fetch_repository_tags(filter: &TreeFilter) -> Result<BTreeMap<String, Vec<String>>, Box<dyn StdError>> {
+    let repositories: Vec<String> = fetch_images()
+        .await
+        .map_err(|e| e as Box<dyn StdError>)?
+        .into_iter()
+        .filter(|r| filter.wants_repository(r))
+        .collect();
+    let results: Vec<(String, Result<Vec<String>, FetchError>)> = futures::stream::iter(repositories)
+        .map(|repository| async move {
+            let tags = fetch_tags(&repository).await;
+            (repository, tags)
+        })
+        .buffer_unordered(concurrency())
+        .collect()
+        .await;
+    let mut tree = BTreeMap::new();
+    for (repository, tags) in results {
+        let mut tags = match tags {
+            Ok(tags) => tags,
+            Err(e) if is_not_found(e.as_ref()) => Vec::new(),
+            Err(e) => return Err(e as Box<dyn StdError>),
+        };
+        filter.apply_to_tags(&mut tags);
+        tree.insert(repository, tags);
+    }
+    Ok(tree)
+}
+
+/// 네트워크 없이 캐시에 남은 카탈로그와 태그 목록만으로 만든 트리. 카탈로그가 없으면 None
+pub fn cached_repository_tags(filter: &TreeFilter) -> Option<BTreeMap<String, Vec<String>>> {
+    let base = get_registry_url();
+    let catalog: CatalogResponse = serde_json::from_value(cache::read_catalog(&base)?.body).ok()?;
+    let mut tree = BTreeMap::new();
+    for repository in catalog.repositories.into_iter().filter(|r| filter.wants_repository(r)) {
+        let mut tags = cache::read_tags(&base, &repository)
+            .and_then(|entry| serde_json::from_value::<TagsResponse>(entry.body).ok())
+            .and_then(|response| response.tags)
+            .unwrap_or_default();
+        filter.apply_to_tags(&mut tags);
+        tree.insert(repository, tags);
+    }
+    Some(tree)
+}
+
+/// "repo" → [태그] 목록을 App::new가 받는 트리 형태로 변환합니다.
+pub fn tree_items_from_tags(repositories: &BTreeMap<String, Vec<String>>) -> TreeItems {
+    let mut groups: BTreeMap<String, Vec<(String, Vec<String>)>> = BTreeMap::new();
+    for (repository, tags) in repositories {
+        let (depth1, depth2) = split_image_depths(repository);
+        groups.entry(depth1.to_string()).or_default().push((depth2.to_string(), tags.clone()));
+    }
+    groups.into_iter().collect()
+}
+
+/// `*`(임의 문자열)와 `?`(한 글자)를 지원하는 간단한 와일드카드 매칭
+pub fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
 pub fn parse_v1compatibility_fields(manifest: &Value) -> (Vec<CompatibilityRow>, String) {
     let empty_vec = vec![];
     let history = manifest.get("history").and_then(|h| h.as_array()).unwrap_or(&empty_vec);
@@ -133,4 +990,81 @@ pub fn parse_v1compatibility_fields(manifest: &Value) -> (Vec<CompatibilityRow>,
     let full_json_string = serde_json::to_string_pretty(&manifest).unwrap_or_default();
 
     (table_data, full_json_string)
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn child(platform: &str) -> Descriptor {
+        Descriptor {
+            media_type: MEDIA_TYPE_OCI_MANIFEST.to_string(),
+            digest: String::new(),
+            size: 0,
+            platform: Platform::parse(platform),
+            artifact_type: None,
+            annotations: Default::default(),
+        }
+    }
+
+    #[test]
+    fn picks_platform_from_index() {
+        let multi = [child("linux/arm64/v8"), child("linux/amd64"), child("unknown/unknown")];
+        assert_eq!(pick_platform(&multi, None).to_string(), "linux/amd64");
+        let arm_only = [child("linux/arm64/v8"), child("unknown/unknown")];
+        assert_eq!(pick_platform(&arm_only, None).to_string(), "linux/arm64/v8");
+        let wanted = Platform::parse("linux/s390x").unwrap();
+        assert_eq!(pick_platform(&multi, Some(&wanted)).to_string(), "linux/s390x");
+        assert_eq!(pick_platform(&[], None).to_string(), DEFAULT_PLATFORM);
+    }
+
+    #[test]
+    fn sends_credentials_only_to_the_profile_registry() {
+        let settings = ClientSettings {
+            auth: Some(Auth::Basic { username: "ci".to_string(), password: Some("secret".to_string()) }),
+            auth_url: Some("https://registry.example.com".to_string()),
+            ..Default::default()
+        };
+        let client = build_client(&settings).unwrap();
+        let authorized = |url: &str| client.get(url).build().unwrap().headers().contains_key(AUTHORIZATION);
+        assert!(authorized("https://registry.example.com/v2/"));
+        assert!(authorized("https://REGISTRY.example.com:443/v2/grp/app/manifests/1.0"));
+        assert!(!authorized("http://registry.example.com/v2/"));
+        assert!(!authorized("https://registry.example.com:5000/v2/"));
+        assert!(!authorized("https://upstream.example.org/v2/"));
+        assert!(!authorized("https://blobs.example.com/sha256/abc"));
+
+        let anonymous = build_client(&ClientSettings::default()).unwrap();
+        assert!(!anonymous.get("https://registry.example.com/v2/").build().unwrap().headers().contains_key(AUTHORIZATION));
+        assert!(build_client(&ClientSettings { auth_url: None, ..settings }).is_err());
+    }
+
+    #[test]
+    fn matches_wildcards() {
+        assert!(matches_pattern("*", ""));
+        assert!(matches_pattern("team/*", "team/web"));
+        assert!(matches_pattern("team/*", "team/a/b"));
+        assert!(!matches_pattern("team/*", "other/web"));
+        assert!(matches_pattern("v?.*", "v1.2.3"));
+        assert!(!matches_pattern("v?.*", "v10.2"));
+        assert!(matches_pattern("*-rc*", "1.0-rc1"));
+        assert!(matches_pattern("a*b*c", "aXbYbZc"));
+        assert!(!matches_pattern("a*b*c", "aXbYbZ"));
+        assert!(matches_pattern("exact", "exact"));
+        assert!(!matches_pattern("exact", "exactly"));
+    }
+
+    #[test]
+    fn parses_references() {
+        assert_eq!(parse_reference("grp/app:1.0").unwrap(), ("grp/app".to_string(), "1.0".to_string()));
+        assert_eq!(parse_reference("localhost:5000/app:1").unwrap(), ("localhost:5000/app".to_string(), "1".to_string()));
+        let digest = format!("sha256:{}", "a".repeat(64));
+        assert_eq!(parse_reference(&format!("app@{}", digest)).unwrap(), ("app".to_string(), digest));
+        assert!(parse_reference("app@sha256:zz").is_err());
+        assert_eq!(parse_reference("grp/app").unwrap(), ("grp/app".to_string(), "latest".to_string()));
+        assert_eq!(parse_reference("app").unwrap(), ("app".to_string(), "latest".to_string()));
+        assert_eq!(parse_reference("host:5000/grp/app").unwrap(), ("host:5000/grp/app".to_string(), "latest".to_string()));
+        assert_eq!(parse_reference("host:5000/grp/app:2").unwrap(), ("host:5000/grp/app".to_string(), "2".to_string()));
+        assert!(parse_reference("app:").is_err());
+        assert!(parse_reference("grp/").is_err());
+    }
+}
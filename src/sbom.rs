@@ -0,0 +1,273 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::error::Error as StdError;
+use tabled::Tabled;
+
+use crate::attestation::{self, AttachedDocument};
+use crate::registry::{self, Platform};
+use crate::time;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SbomFormat {
+    Spdx,
+    CycloneDx,
+}
+
+impl SbomFormat {
+    pub fn parse(value: &str) -> Option<SbomFormat> {
+        match value {
+            "spdx" | "spdx-json" => Some(SbomFormat::Spdx),
+            "cyclonedx" | "cdx" => Some(SbomFormat::CycloneDx),
+            _ => None,
+        }
+    }
+
+    /// 문서 내용으로 형식을 판별합니다.
+    pub fn detect(document: &Value) -> Option<SbomFormat> {
+        if document.get("spdxVersion").is_some() {
+            Some(SbomFormat::Spdx)
+        } else if document.get("bomFormat").and_then(|f| f.as_str()) == Some("CycloneDX") {
+            Some(SbomFormat::CycloneDx)
+        } else {
+            None
+        }
+    }
+}
+
+impl std::fmt::Display for SbomFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SbomFormat::Spdx => "SPDX",
+            SbomFormat::CycloneDx => "CycloneDX",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Tabled)]
+pub struct Package {
+    #[tabled(rename = "Name")]
+    pub name: String,
+    #[tabled(rename = "Version")]
+    pub version: String,
+    #[tabled(rename = "Type")]
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[tabled(rename = "License")]
+    pub license: String,
+    #[tabled(rename = "PURL")]
+    pub purl: String,
+}
+
+impl Package {
+    /// 이름/버전/유형/라이선스/PURL 중 하나라도 `query`를 포함하면 true (대소문자 무시)
+    pub fn matches(&self, query: &str) -> bool {
+        let query = query.to_lowercase();
+        [&self.name, &self.version, &self.kind, &self.license, &self.purl]
+            .iter()
+            .any(|field| field.to_lowercase().contains(&query))
+    }
+}
+
+/// 이미지에 첨부된 SBOM 문서 하나
+#[derive(Debug, Clone, Serialize)]
+pub struct Sbom {
+    pub format: SbomFormat,
+    pub source: String,
+    pub packages: Vec<Package>,
+}
+
+fn text(value: &Value, key: &str) -> String {
+    value.get(key).and_then(|v| v.as_str()).unwrap_or("").to_string()
+}
+
+fn spdx_packages(document: &Value) -> Vec<Package> {
+    let empty = vec![];
+    let packages = document.get("packages").and_then(|p| p.as_array()).unwrap_or(&empty);
+    packages
+        .iter()
+        .map(|package| {
+            let license = [text(package, "licenseConcluded"), text(package, "licenseDeclared")]
+                .into_iter()
+                .find(|l| !l.is_empty() && l != "NOASSERTION")
+                .unwrap_or_default();
+            let purl = package
+                .get("externalRefs")
+                .and_then(|r| r.as_array())
+                .and_then(|refs| refs.iter().find(|r| text(r, "referenceType") == "purl"))
+                .map(|r| text(r, "referenceLocator"))
+                .unwrap_or_default();
+            Package {
+                name: text(package, "name"),
+                version: text(package, "versionInfo"),
+                kind: text(package, "primaryPackagePurpose").to_lowercase(),
+                license,
+                purl,
+            }
+        })
+        .collect()
+}
+
+fn cyclonedx_license(component: &Value) -> String {
+    let empty = vec![];
+    let licenses = component.get("licenses").and_then(|l| l.as_array()).unwrap_or(&empty);
+    let names: Vec<String> = licenses
+        .iter()
+        .filter_map(|entry| {
+            let license = entry.get("license");
+            license
+                .and_then(|l| l.get("id").or_else(|| l.get("name")))
+                .or_else(|| entry.get("expression"))
+                .and_then(|v| v.as_str())
+                .map(|v| v.to_string())
+        })
+        .collect();
+    names.join(" AND ")
+}
+
+/// 중첩된 components까지 모두 펼칩니다.
+fn cyclonedx_components(components: Option<&Value>, out: &mut Vec<Package>) {
+    let Some(components) = components.and_then(|c| c.as_array()) else { return };
+    for component in components {
+        out.push(Package {
+            name: text(component, "name"),
+            version: text(component, "version"),
+            kind: text(component, "type"),
+            license: cyclonedx_license(component),
+            purl: text(component, "purl"),
+        });
+        cyclonedx_components(component.get("components"), out);
+    }
+}
+
+pub fn parse_packages(format: SbomFormat, document: &Value) -> Vec<Package> {
+    match format {
+        SbomFormat::Spdx => spdx_packages(document),
+        SbomFormat::CycloneDx => {
+            let mut packages = Vec::new();
+            cyclonedx_components(document.get("components"), &mut packages);
+            packages
+        }
+    }
+}
+
+pub fn sboms_from_documents(documents: &[AttachedDocument]) -> Vec<Sbom> {
+    documents
+        .iter()
+        .filter_map(|doc| {
+            let format = SbomFormat::detect(&doc.document)?;
+            Some(Sbom { format, source: format!("{} ({})", doc.source, format), packages: parse_packages(format, &doc.document) })
+        })
+        .collect()
+}
+
+pub async fn fetch_sboms(image: &str, reference: &str, platform: &Platform) -> Result<Vec<Sbom>, Box<dyn StdError>> {
+    let documents = attestation::fetch_documents(image, reference, platform).await?;
+    Ok(sboms_from_documents(&documents))
+}
+
+/// 여러 SBOM의 패키지를 이름/버전/PURL 기준으로 중복 없이 합칩니다.
+pub fn merge_packages<'a>(sboms: impl IntoIterator<Item = &'a Sbom>) -> Vec<Package> {
+    let mut seen = HashSet::new();
+    let mut packages: Vec<Package> = sboms
+        .into_iter()
+        .flat_map(|s| s.packages.iter())
+        .filter(|p| seen.insert((p.name.clone(), p.version.clone(), p.purl.clone())))
+        .cloned()
+        .collect();
+    packages.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.version.cmp(&b.version)));
+    packages
+}
+
+/// CycloneDX component type으로 쓸 수 있는 값. 레이어 스캔의 생태계 이름(deb, npm 등)은 library로 내보냄
+const CYCLONEDX_TYPES: &[&str] = &[
+    "application",
+    "framework",
+    "library",
+    "container",
+    "platform",
+    "operating-system",
+    "device",
+    "device-driver",
+    "firmware",
+    "file",
+    "machine-learning-model",
+    "data",
+];
+
+/// 패키지 목록으로 CycloneDX 1.5 JSON 문서를 만듭니다.
+pub fn cyclonedx_document(image: &str, tag: &str, packages: &[Package]) -> Value {
+    let components: Vec<Value> = packages
+        .iter()
+        .map(|p| {
+            let mut component = json!({
+                "type": if CYCLONEDX_TYPES.contains(&p.kind.as_str()) { p.kind.as_str() } else { "library" },
+                "name": p.name,
+                "version": p.version,
+            });
+            if !p.purl.is_empty() {
+                component["purl"] = json!(p.purl);
+            }
+            if !p.license.is_empty() {
+                component["licenses"] = json!([{ "expression": p.license }]);
+            }
+            component
+        })
+        .collect();
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "timestamp": time::now_rfc3339(),
+            "tools": [{ "name": "repo-tree", "version": env!("CARGO_PKG_VERSION") }],
+            "component": { "type": "container", "name": image, "version": tag },
+        },
+        "components": components,
+    })
+}
+
+/// 패키지 목록으로 SPDX 2.3 JSON 문서를 만듭니다.
+pub fn spdx_document(image: &str, tag: &str, packages: &[Package]) -> Value {
+    let spdx_packages: Vec<Value> = packages
+        .iter()
+        .enumerate()
+        .map(|(i, p)| {
+            let mut package = json!({
+                "name": p.name,
+                "SPDXID": format!("SPDXRef-Package-{}", i + 1),
+                "versionInfo": p.version,
+                "downloadLocation": "NOASSERTION",
+                "licenseConcluded": if p.license.is_empty() { "NOASSERTION" } else { p.license.as_str() },
+            });
+            if !p.purl.is_empty() {
+                package["externalRefs"] = json!([{
+                    "referenceCategory": "PACKAGE-MANAGER",
+                    "referenceType": "purl",
+                    "referenceLocator": p.purl,
+                }]);
+            }
+            package
+        })
+        .collect();
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": format!("{}:{}", image, tag),
+        "documentNamespace": format!("https://{}/{}/{}-{}", registry::registry_host(), image, tag, time::unix_now()),
+        "creationInfo": {
+            "created": time::now_rfc3339(),
+            "creators": [format!("Tool: repo-tree-{}", env!("CARGO_PKG_VERSION"))],
+        },
+        "packages": spdx_packages,
+    })
+}
+
+pub fn export_document(format: SbomFormat, image: &str, tag: &str, packages: &[Package]) -> Value {
+    match format {
+        SbomFormat::Spdx => spdx_document(image, tag, packages),
+        SbomFormat::CycloneDx => cyclonedx_document(image, tag, packages),
+    }
+}
@@ -0,0 +1,253 @@
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error as StdError;
+use std::path::Path;
+use tabled::Tabled;
+use tui::style::Color;
+
+use crate::layers;
+use crate::registry::{self, Platform, TreeFilter};
+use crate::time;
+use crate::treeprint;
+use crate::ui::{self, App};
+
+// 파일 형식이 바뀌면 올림
+const SNAPSHOT_VERSION: u32 = 1;
+
+/// 태그 하나의 기록. 크기와 생성 시각은 읽지 못하면 비워 둠 (아티팩트, 다른 플랫폼 전용 이미지 등)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagRecord {
+    pub digest: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+}
+
+/// 어느 시점의 registry 상태: 저장소 → (태그 → 기록)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub version: u32,
+    pub registry: String,
+    /// 기록한 시각 (RFC 3339)
+    pub taken_at: String,
+    /// --platform. 지정하지 않았으면 "auto" (태그마다 이미지에 있는 플랫폼)
+    pub platform: String,
+    pub repositories: BTreeMap<String, BTreeMap<String, TagRecord>>,
+}
+
+impl Snapshot {
+    pub fn tag_count(&self) -> usize {
+        self.repositories.values().map(|tags| tags.len()).sum()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Box<dyn StdError>> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Snapshot, Box<dyn StdError>> {
+        let bytes = std::fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let snapshot: Snapshot = serde_json::from_slice(&bytes).map_err(|e| format!("{}: not a snapshot file: {}", path.display(), e))?;
+        if snapshot.version > SNAPSHOT_VERSION {
+            return Err(format!("{}: snapshot version {} is newer than this repo-tree supports", path.display(), snapshot.version).into());
+        }
+        Ok(snapshot)
+    }
+}
+
+async fn tag_record(repository: &str, tag: &str, platform: Option<&Platform>) -> Result<TagRecord, Box<dyn StdError>> {
+    let digest = registry::fetch_manifest_digest(repository, tag).await.map_err(|e| format!("{}:{}: {}", repository, tag, e))?;
+    // 다이제스트로 읽으면 매니페스트 캐시를 그대로 쓸 수 있음
+    let summary = treeprint::image_summary(repository, &digest, platform).await.ok();
+    Ok(TagRecord {
+        digest,
+        size: summary.as_ref().map(|(size, _)| *size),
+        created: summary.and_then(|(_, created)| created),
+    })
+}
+
+/// 현재 registry의 필터에 맞는 모든 태그를 기록합니다.
+/// `platform`이 없으면 태그마다 이미지에 있는 플랫폼에서 고름
+pub async fn record(filter: &TreeFilter, platform: Option<&Platform>) -> Result<Snapshot, Box<dyn StdError>> {
+    let repositories = registry::fetch_repository_tags(filter).await?;
+    let tags: Vec<(&String, &String)> = repositories.iter().flat_map(|(repository, tags)| tags.iter().map(move |tag| (repository, tag))).collect();
+    let records: Vec<(&String, &String, Result<TagRecord, String>)> = stream::iter(tags)
+        .map(|(repository, tag)| async move {
+            let record = tag_record(repository, tag, platform).await.map_err(|e| e.to_string());
+            (repository, tag, record)
+        })
+        .buffer_unordered(registry::concurrency())
+        .collect()
+        .await;
+
+    // 태그가 없는 저장소도 남겨야 저장소 삭제와 구분됨
+    let mut snapshot: BTreeMap<String, BTreeMap<String, TagRecord>> = repositories.keys().map(|r| (r.clone(), BTreeMap::new())).collect();
+    for (repository, tag, record) in records {
+        snapshot.get_mut(repository).unwrap().insert(tag.clone(), record?);
+    }
+    Ok(Snapshot {
+        version: SNAPSHOT_VERSION,
+        registry: registry::get_registry_url().trim_end_matches("/v2/").to_string(),
+        taken_at: time::now_rfc3339(),
+        platform: platform.map_or_else(|| "auto".to_string(), |p| p.to_string()),
+        repositories: snapshot,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    /// 새 태그가 처음 보는 이미지를 가리킴
+    Pushed,
+    /// 있던 태그가 처음 보는 이미지로 바뀜
+    Updated,
+    /// 태그가 저장소에 이미 있던 이미지를 가리키게 됨 (새 태그 또는 옮겨진 태그)
+    Retagged,
+    Deleted,
+}
+
+impl ChangeKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeKind::Pushed => "pushed",
+            ChangeKind::Updated => "updated",
+            ChangeKind::Retagged => "retagged",
+            ChangeKind::Deleted => "deleted",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            ChangeKind::Pushed => Color::Green,
+            ChangeKind::Updated => Color::Yellow,
+            ChangeKind::Retagged => Color::Cyan,
+            ChangeKind::Deleted => Color::Red,
+        }
+    }
+}
+
+impl std::fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+fn display_digest(digest: &Option<String>) -> String {
+    digest.as_deref().map(|d| d.chars().take(19).collect()).unwrap_or_else(|| "-".to_string())
+}
+
+fn display_size(size: &Option<u64>) -> String {
+    size.map(layers::format_size).unwrap_or_else(|| "-".to_string())
+}
+
+#[derive(Debug, Clone, Serialize, Tabled)]
+pub struct SnapshotChange {
+    #[tabled(rename = "Repository")]
+    pub repository: String,
+    #[tabled(rename = "Tag")]
+    pub tag: String,
+    #[tabled(rename = "Change")]
+    pub change: ChangeKind,
+    #[tabled(rename = "Before", display_with = "display_digest")]
+    pub digest_before: Option<String>,
+    #[tabled(rename = "After", display_with = "display_digest")]
+    pub digest_after: Option<String>,
+    #[tabled(rename = "Size", display_with = "display_size")]
+    pub size: Option<u64>,
+    #[tabled(skip)]
+    pub created: Option<String>,
+}
+
+/// 저장소 단위 변화 (TUI에서 저장소 줄에 표시)
+#[derive(Debug, Clone, Serialize)]
+pub struct RepositoryChange {
+    pub repository: String,
+    pub added: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SnapshotDiff {
+    pub registry_before: String,
+    pub registry_after: String,
+    pub taken_before: String,
+    pub taken_after: String,
+    pub repositories: Vec<RepositoryChange>,
+    pub changes: Vec<SnapshotChange>,
+}
+
+impl SnapshotDiff {
+    pub fn count(&self, kind: ChangeKind) -> usize {
+        self.changes.iter().filter(|c| c.change == kind).count()
+    }
+}
+
+pub fn compare(before: &Snapshot, after: &Snapshot) -> SnapshotDiff {
+    let empty = BTreeMap::new();
+    let names: BTreeSet<&String> = before.repositories.keys().chain(after.repositories.keys()).collect();
+    let mut repositories = Vec::new();
+    let mut changes = Vec::new();
+
+    for repository in names {
+        match (before.repositories.contains_key(repository), after.repositories.contains_key(repository)) {
+            (false, true) => repositories.push(RepositoryChange { repository: repository.clone(), added: true }),
+            (true, false) => repositories.push(RepositoryChange { repository: repository.clone(), added: false }),
+            _ => {}
+        }
+        let tags_before = before.repositories.get(repository).unwrap_or(&empty);
+        let tags_after = after.repositories.get(repository).unwrap_or(&empty);
+        let known: BTreeSet<&String> = tags_before.values().map(|r| &r.digest).collect();
+        let tags: BTreeSet<&String> = tags_before.keys().chain(tags_after.keys()).collect();
+        for tag in tags {
+            let (old, new) = (tags_before.get(tag), tags_after.get(tag));
+            let change = match (old, new) {
+                (Some(_), None) => ChangeKind::Deleted,
+                (Some(old), Some(new)) if old.digest == new.digest => continue,
+                (_, Some(new)) if known.contains(&new.digest) => ChangeKind::Retagged,
+                (None, Some(_)) => ChangeKind::Pushed,
+                (Some(_), Some(_)) => ChangeKind::Updated,
+                (None, None) => continue,
+            };
+            let current = new.or(old).unwrap();
+            changes.push(SnapshotChange {
+                repository: repository.clone(),
+                tag: tag.clone(),
+                change,
+                digest_before: old.map(|r| r.digest.clone()),
+                digest_after: new.map(|r| r.digest.clone()),
+                size: current.size,
+                created: current.created.clone(),
+            });
+        }
+    }
+    SnapshotDiff {
+        registry_before: before.registry.clone(),
+        registry_after: after.registry.clone(),
+        taken_before: before.taken_at.clone(),
+        taken_after: after.taken_at.clone(),
+        repositories,
+        changes,
+    }
+}
+
+/// 바뀐 태그만 보여 주고 변화 종류별로 색을 입힌 TUI 트리를 만듭니다.
+pub fn diff_app(diff: &SnapshotDiff, title: &str) -> App {
+    let mut repositories: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for change in &diff.changes {
+        repositories.entry(change.repository.clone()).or_default().push(change.tag.clone());
+    }
+    for change in &diff.repositories {
+        repositories.entry(change.repository.clone()).or_default();
+    }
+
+    let mut app = App::new(registry::tree_items_from_tags(&repositories)).with_title(title);
+    for change in &diff.repositories {
+        let (label, color) = if change.added { ("new repository", Color::Green) } else { ("deleted repository", Color::Red) };
+        app.set_mark(&ui::repository_row_name(&change.repository), label, color);
+    }
+    for change in &diff.changes {
+        app.set_mark(&format!("{}/{}", ui::repository_row_name(&change.repository), change.tag), change.change.label(), change.change.color());
+    }
+    app
+}
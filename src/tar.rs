@@ -0,0 +1,443 @@
+use std::io::{self, Read, Write};
+
+const BLOCK_SIZE: usize = 512;
+
+/// docker-archive 생성을 위한 최소한의 ustar Writer
+pub struct Builder<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> Builder<W> {
+    pub fn new(inner: W) -> Self {
+        Builder { inner }
+    }
+
+    /// 메모리에 있는 데이터를 하나의 파일 엔트리로 추가합니다.
+    pub fn append_bytes(&mut self, path: &str, data: &[u8]) -> io::Result<()> {
+        self.start_entry(path, data.len() as u64)?;
+        self.inner.write_all(data)?;
+        self.end_entry(data.len() as u64)
+    }
+
+    /// 헤더만 먼저 기록합니다. 이후 `writer()`로 정확히 `size` 바이트를 쓰고 `end_entry`를 호출해야 합니다.
+    pub fn start_entry(&mut self, path: &str, size: u64) -> io::Result<()> {
+        let header = build_header(path, size, b'0')?;
+        self.inner.write_all(&header)
+    }
+
+    pub fn writer(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub fn end_entry(&mut self, size: u64) -> io::Result<()> {
+        let remainder = (size % BLOCK_SIZE as u64) as usize;
+        if remainder != 0 {
+            self.inner.write_all(&[0u8; BLOCK_SIZE][..BLOCK_SIZE - remainder])?;
+        }
+        Ok(())
+    }
+
+    /// 아카이브 종료 블록(0으로 채워진 블록 2개)을 기록합니다.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.inner.write_all(&[0u8; BLOCK_SIZE * 2])?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+fn build_header(path: &str, size: u64, entry_type: u8) -> io::Result<[u8; BLOCK_SIZE]> {
+    let mut header = [0u8; BLOCK_SIZE];
+
+    // 100바이트를 넘는 경로는 prefix(155) + name(100)으로 나눠 저장
+    let (prefix, name) = if path.len() <= 100 {
+        ("", path)
+    } else {
+        // prefix는 155바이트까지. 여러 바이트 문자 중간에서 자르지 않도록 문자 경계까지 물러남
+        let mut end = path.len().min(156);
+        while !path.is_char_boundary(end) {
+            end -= 1;
+        }
+        let split = path[..end]
+            .rfind('/')
+            .filter(|&i| path.len() - i - 1 <= 100)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, format!("path too long for tar: {}", path)))?;
+        (&path[..split], &path[split + 1..])
+    };
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+    write_octal(&mut header[100..108], 0o644);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    if size < 0o77777777777 {
+        write_octal(&mut header[124..136], size);
+    } else {
+        // 8GiB 이상은 GNU base-256 인코딩 사용
+        header[124] = 0x80;
+        for (i, byte) in size.to_be_bytes().iter().enumerate() {
+            header[128 + i] = *byte;
+        }
+    }
+    write_octal(&mut header[136..148], 0);
+    header[156] = entry_type;
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+
+    // 체크섬 계산 시 체크섬 필드는 공백으로 간주
+    header[148..156].copy_from_slice(b"        ");
+    let checksum: u32 = header.iter().map(|&b| b as u32).sum();
+    write_octal(&mut header[148..155], checksum as u64);
+    header[155] = b' ';
+
+    Ok(header)
+}
+
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    let text = format!("{:0width$o}", value, width = digits);
+    field[..digits].copy_from_slice(&text.as_bytes()[text.len() - digits..]);
+    field[digits] = 0;
+}
+
+pub const TYPE_REGULAR: u8 = b'0';
+pub const TYPE_HARDLINK: u8 = b'1';
+pub const TYPE_SYMLINK: u8 = b'2';
+pub const TYPE_DIRECTORY: u8 = b'5';
+const TYPE_GNU_LONGLINK: u8 = b'K';
+const TYPE_GNU_LONGNAME: u8 = b'L';
+const TYPE_PAX_HEADER: u8 = b'x';
+const TYPE_PAX_GLOBAL: u8 = b'g';
+/// GNU longname/PAX 확장 헤더의 최대 크기. 헤더의 크기 필드를 믿고 그대로 할당하지 않음
+const MAX_EXTENDED_HEADER: u64 = 1 << 20;
+
+#[derive(Debug, Clone)]
+pub struct EntryHeader {
+    pub path: String,
+    pub size: u64,
+    pub entry_type: u8,
+    pub mode: u32,
+    pub uid: u64,
+    pub gid: u64,
+    pub uname: String,
+    pub gname: String,
+    pub link_name: String,
+    /// 아카이브 시작 기준 데이터 시작 위치
+    pub data_offset: u64,
+}
+
+impl EntryHeader {
+    pub fn is_file(&self) -> bool {
+        self.entry_type == TYPE_REGULAR || self.entry_type == 0
+    }
+}
+
+/// 순차적으로 엔트리를 읽는 tar Reader (GNU longname, PAX path 지원)
+pub struct Archive<R: Read> {
+    inner: R,
+    position: u64,
+    // 현재 엔트리에서 아직 읽지 않은 데이터 + 패딩 바이트 수
+    remaining: u64,
+    data_remaining: u64,
+}
+
+impl<R: Read> Archive<R> {
+    pub fn new(inner: R) -> Self {
+        Archive { inner, position: 0, remaining: 0, data_remaining: 0 }
+    }
+
+    pub fn next_entry(&mut self) -> io::Result<Option<EntryHeader>> {
+        let mut long_path: Option<String> = None;
+        let mut long_link: Option<String> = None;
+        let mut pax_size: Option<u64> = None;
+        loop {
+            self.skip_remaining()?;
+
+            let mut header = [0u8; BLOCK_SIZE];
+            if !self.read_block(&mut header)? || header.iter().all(|&b| b == 0) {
+                return Ok(None);
+            }
+
+            let size = parse_size(&header[124..136])?;
+            let entry_type = header[156];
+            let padded = size.div_ceil(BLOCK_SIZE as u64) * BLOCK_SIZE as u64;
+
+            match entry_type {
+                TYPE_GNU_LONGNAME | TYPE_GNU_LONGLINK | TYPE_PAX_HEADER | TYPE_PAX_GLOBAL => {
+                    if size > MAX_EXTENDED_HEADER {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("tar extended header too large ({} bytes)", size)));
+                    }
+                    let mut data = vec![0u8; size as usize];
+                    self.inner.read_exact(&mut data)?;
+                    self.position += size;
+                    self.remaining = padded - size;
+                    self.data_remaining = 0;
+                    if entry_type == TYPE_GNU_LONGNAME {
+                        long_path = Some(cstr(&data));
+                    } else if entry_type == TYPE_GNU_LONGLINK {
+                        long_link = Some(cstr(&data));
+                    } else if entry_type == TYPE_PAX_HEADER {
+                        if let Some(path) = parse_pax_record(&data, "path") {
+                            long_path = Some(path);
+                        }
+                        if let Some(link) = parse_pax_record(&data, "linkpath") {
+                            long_link = Some(link);
+                        }
+                        // 8GiB 이상 파일은 ustar 크기 필드 대신 PAX size 레코드에 크기가 있음
+                        if let Some(value) = parse_pax_record(&data, "size") {
+                            let value = value.parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("invalid PAX size '{}'", value)))?;
+                            pax_size = Some(value);
+                        }
+                    }
+                }
+                _ => {
+                    let path = long_path.take().unwrap_or_else(|| {
+                        let name = cstr(&header[0..100]);
+                        let prefix = if &header[257..262] == b"ustar" { cstr(&header[345..500]) } else { String::new() };
+                        if prefix.is_empty() { name } else { format!("{}/{}", prefix, name) }
+                    });
+                    // 링크/디렉터리 엔트리는 데이터가 없음
+                    let has_data = !matches!(entry_type, TYPE_HARDLINK | TYPE_SYMLINK | TYPE_DIRECTORY);
+                    let size = if has_data { pax_size.take().unwrap_or(size) } else { 0 };
+                    let padded = if has_data { size.div_ceil(BLOCK_SIZE as u64) * BLOCK_SIZE as u64 } else { 0 };
+                    self.remaining = padded;
+                    self.data_remaining = size;
+                    let ustar = &header[257..262] == b"ustar";
+                    return Ok(Some(EntryHeader {
+                        path: normalize_path(&path),
+                        size,
+                        entry_type,
+                        mode: parse_octal(&header[100..108]).unwrap_or(0) as u32,
+                        uid: parse_size(&header[108..116]).unwrap_or(0),
+                        gid: parse_size(&header[116..124]).unwrap_or(0),
+                        uname: if ustar { cstr(&header[265..297]) } else { String::new() },
+                        gname: if ustar { cstr(&header[297..329]) } else { String::new() },
+                        link_name: long_link.take().unwrap_or_else(|| cstr(&header[157..257])),
+                        data_offset: self.position,
+                    }));
+                }
+            }
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// 현재 엔트리의 데이터를 읽는 Reader
+    pub fn data(&mut self) -> EntryData<'_, R> {
+        EntryData { archive: self }
+    }
+
+    fn read_block(&mut self, block: &mut [u8; BLOCK_SIZE]) -> io::Result<bool> {
+        let mut filled = 0;
+        while filled < BLOCK_SIZE {
+            let n = self.inner.read(&mut block[filled..])?;
+            if n == 0 {
+                if filled == 0 {
+                    return Ok(false);
+                }
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated tar header"));
+            }
+            filled += n;
+        }
+        self.position += BLOCK_SIZE as u64;
+        Ok(true)
+    }
+
+    fn skip_remaining(&mut self) -> io::Result<()> {
+        if self.remaining > 0 {
+            let skipped = io::copy(&mut (&mut self.inner).take(self.remaining), &mut io::sink())?;
+            if skipped != self.remaining {
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated tar entry"));
+            }
+            self.position += skipped;
+            self.remaining = 0;
+            self.data_remaining = 0;
+        }
+        Ok(())
+    }
+}
+
+pub struct EntryData<'a, R: Read> {
+    archive: &'a mut Archive<R>,
+}
+
+impl<R: Read> Read for EntryData<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let archive = &mut *self.archive;
+        if archive.data_remaining == 0 {
+            return Ok(0);
+        }
+        let limit = buf.len().min(archive.data_remaining as usize);
+        let n = archive.inner.read(&mut buf[..limit])?;
+        if n == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated tar entry"));
+        }
+        archive.data_remaining -= n as u64;
+        archive.remaining -= n as u64;
+        archive.position += n as u64;
+        Ok(n)
+    }
+}
+
+fn cstr(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+/// "./a/b" 와 "a/b" 를 같은 경로로 취급합니다.
+pub fn normalize_path(path: &str) -> String {
+    path.trim_start_matches("./").trim_start_matches('/').to_string()
+}
+
+fn parse_size(field: &[u8]) -> io::Result<u64> {
+    if field[0] & 0x80 != 0 {
+        // GNU base-256 인코딩
+        return Ok(field[4..].iter().fold(0u64, |acc, &b| (acc << 8) | b as u64));
+    }
+    parse_octal(field)
+}
+
+fn parse_octal(field: &[u8]) -> io::Result<u64> {
+    let text = cstr(field);
+    let text = text.trim_matches(|c: char| c == ' ' || c == '\0');
+    if text.is_empty() {
+        return Ok(0);
+    }
+    u64::from_str_radix(text, 8).map_err(|_| io::Error::new(io::ErrorKind::InvalidData, format!("invalid octal field '{}'", text)))
+}
+
+// PAX 레코드 형식: "<len> <key>=<value>\n"
+fn parse_pax_record(data: &[u8], key: &str) -> Option<String> {
+    let text = String::from_utf8_lossy(data);
+    text.lines().find_map(|line| {
+        let (_, record) = line.split_once(' ')?;
+        let (record_key, value) = record.split_once('=')?;
+        (record_key == key).then(|| value.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(bytes: &[u8]) -> Vec<(EntryHeader, Vec<u8>)> {
+        let mut archive = Archive::new(bytes);
+        let mut entries = Vec::new();
+        while let Some(header) = archive.next_entry().unwrap() {
+            let mut data = Vec::new();
+            archive.data().read_to_end(&mut data).unwrap();
+            entries.push((header, data));
+        }
+        entries
+    }
+
+    // GNU longname 또는 PAX 확장 헤더 뒤에 짧은 이름의 일반 파일을 붙인 아카이브
+    fn extended(entry_type: u8, payload: &[u8]) -> Vec<u8> {
+        let mut bytes = build_header("././@LongLink", payload.len() as u64, entry_type).unwrap().to_vec();
+        bytes.extend_from_slice(payload);
+        bytes.resize(bytes.len().div_ceil(BLOCK_SIZE) * BLOCK_SIZE, 0);
+        let mut builder = Builder::new(bytes);
+        builder.append_bytes("short", b"data").unwrap();
+        builder.finish().unwrap()
+    }
+
+    #[test]
+    fn round_trips_entries() {
+        let long_path = format!("{}/{}", "d".repeat(120), "file.txt");
+        let mut builder = Builder::new(Vec::new());
+        builder.append_bytes("./etc/hosts", b"127.0.0.1 localhost\n").unwrap();
+        builder.append_bytes(&long_path, &[7u8; 600]).unwrap();
+        builder.append_bytes("empty", b"").unwrap();
+        let bytes = builder.finish().unwrap();
+        assert_eq!(bytes.len() % BLOCK_SIZE, 0);
+
+        let entries = read_all(&bytes);
+        let paths: Vec<&str> = entries.iter().map(|(h, _)| h.path.as_str()).collect();
+        assert_eq!(paths, ["etc/hosts", long_path.as_str(), "empty"]);
+        assert_eq!(entries[0].1, b"127.0.0.1 localhost\n");
+        assert_eq!(entries[1].1, vec![7u8; 600]);
+        assert!(entries.iter().all(|(h, _)| h.is_file() && h.mode == 0o644));
+        assert_eq!(entries[1].0.data_offset, 3 * BLOCK_SIZE as u64);
+    }
+
+    #[test]
+    fn skips_unread_data() {
+        let mut builder = Builder::new(Vec::new());
+        builder.append_bytes("a", &[1u8; 1000]).unwrap();
+        builder.append_bytes("b", b"second").unwrap();
+        let bytes = builder.finish().unwrap();
+        let mut archive = Archive::new(&bytes[..]);
+        assert_eq!(archive.next_entry().unwrap().unwrap().path, "a");
+        let b = archive.next_entry().unwrap().unwrap();
+        let mut data = String::new();
+        archive.data().read_to_string(&mut data).unwrap();
+        assert_eq!((b.path.as_str(), data.as_str()), ("b", "second"));
+        assert!(archive.next_entry().unwrap().is_none());
+    }
+
+    #[test]
+    fn reads_gnu_longname_and_pax_path() {
+        let name = "x".repeat(200);
+        let gnu = read_all(&extended(TYPE_GNU_LONGNAME, format!("{}\0", name).as_bytes()));
+        assert_eq!(gnu[0].0.path, name);
+        assert_eq!(gnu[0].1, b"data");
+
+        let record = format!("path={}\n", name);
+        let record = format!("{} {}", record.len() + 4, record);
+        let pax = read_all(&extended(TYPE_PAX_HEADER, record.as_bytes()));
+        assert_eq!(pax[0].0.path, name);
+    }
+
+    #[test]
+    fn rejects_truncated_archives() {
+        let mut builder = Builder::new(Vec::new());
+        builder.append_bytes("a", &[1u8; 1000]).unwrap();
+        let bytes = builder.finish().unwrap();
+        let mut archive = Archive::new(&bytes[..700]);
+        archive.next_entry().unwrap();
+        assert!(archive.next_entry().is_err());
+        assert!(Archive::new(&bytes[..100]).next_entry().is_err());
+    }
+
+    #[test]
+    fn parses_numeric_fields() {
+        assert_eq!(parse_octal(b"0000644\0").unwrap(), 0o644);
+        assert_eq!(parse_octal(b"      \0").unwrap(), 0);
+        assert!(parse_octal(b"9\0").is_err());
+        let mut base256 = [0u8; 12];
+        base256[0] = 0x80;
+        base256[4..].copy_from_slice(&(10u64 << 30).to_be_bytes());
+        assert_eq!(parse_size(&base256).unwrap(), 10 << 30);
+        assert_eq!(normalize_path("./a/b"), "a/b");
+        assert_eq!(normalize_path("/a"), "a");
+    }
+
+    #[test]
+    fn honors_pax_size_and_caps_extended_headers() {
+        // ustar 크기 필드는 0이고 실제 크기는 PAX size 레코드에만 있는 엔트리
+        let record = "size=4\n";
+        let record = format!("{} {}", record.len() + 2, record);
+        let mut bytes = build_header("././@PaxHeader", record.len() as u64, TYPE_PAX_HEADER).unwrap().to_vec();
+        bytes.extend_from_slice(record.as_bytes());
+        bytes.resize(BLOCK_SIZE * 2, 0);
+        bytes.extend_from_slice(&build_header("big", 0, TYPE_REGULAR).unwrap());
+        bytes.extend_from_slice(b"data");
+        bytes.resize(BLOCK_SIZE * 6, 0);
+        let entries = read_all(&bytes);
+        assert_eq!((entries[0].0.size, entries[0].1.as_slice()), (4, &b"data"[..]));
+
+        let huge = build_header("././@LongLink", 1 << 40, TYPE_GNU_LONGNAME).unwrap();
+        assert_eq!(Archive::new(&huge[..]).next_entry().unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn splits_long_multibyte_paths_on_char_boundaries() {
+        // 156번째 바이트가 한글 글자 중간에 오는 경로
+        let path = format!("{}/{}/file", "가".repeat(50), "나".repeat(10));
+        let mut builder = Builder::new(Vec::new());
+        builder.append_bytes(&path, b"x").unwrap();
+        let entries = read_all(&builder.finish().unwrap());
+        assert_eq!(entries[0].0.path, path);
+    }
+}
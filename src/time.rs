@@ -0,0 +1,104 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 현재 시각을 RFC 3339(UTC) 문자열로 반환합니다. 예: "2024-05-01T12:00:00Z"
+pub fn now_rfc3339() -> String {
+    format_unix(unix_now())
+}
+
+pub fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs() as i64).unwrap_or(0)
+}
+
+pub fn format_unix(secs: i64) -> String {
+    let days = secs.div_euclid(86_400);
+    let rem = secs.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3600,
+        (rem % 3600) / 60,
+        rem % 60
+    )
+}
+
+// Howard Hinnant의 days → (년, 월, 일) 변환 알고리즘
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// "2024-05-01", "2024-05-01T12:00:00Z" 또는 "2024-05-01 12:00"(UTC)을 유닉스 초로 바꿉니다.
+pub fn parse_utc(text: &str) -> Option<i64> {
+    let text = text.trim().trim_end_matches('Z');
+    let (date, clock) = match text.split_once(['T', ' ']) {
+        Some((date, clock)) => (date, clock),
+        None => (text, "00:00:00"),
+    };
+    let mut date_parts = date.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (year, month, day) = (date_parts.next()??, date_parts.next()??, date_parts.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let mut clock_parts = clock.splitn(3, ':').map(|p| p.split('.').next().unwrap_or(p).parse::<i64>().ok());
+    let hour = clock_parts.next()??;
+    let minute = clock_parts.next().unwrap_or(Some(0))?;
+    let second = clock_parts.next().unwrap_or(Some(0))?;
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    Some(days_from_civil(year, month as u32, day as u32) * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+// civil_from_days의 역변환
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 } as i64;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_unix_times() {
+        assert_eq!(format_unix(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_unix(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_unix(1_714_564_800), "2024-05-01T12:00:00Z");
+        assert_eq!(format_unix(-1), "1969-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn parses_utc_times() {
+        assert_eq!(parse_utc("2024-05-01"), Some(1_714_521_600));
+        assert_eq!(parse_utc("2024-05-01T12:00:00Z"), Some(1_714_564_800));
+        assert_eq!(parse_utc("2024-05-01 12:00"), Some(1_714_564_800));
+        assert_eq!(parse_utc("2024-05-01T12:00:00.123Z"), Some(1_714_564_800));
+        assert_eq!(parse_utc("2000-02-29"), Some(951_782_400));
+        for secs in [0, 951_782_400, 1_714_564_859, 4_102_444_800] {
+            assert_eq!(parse_utc(&format_unix(secs)), Some(secs));
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_times() {
+        for text in ["", "2024", "2024-13-01", "2024-05-32", "2024-05-01T24:00", "2024-05-01T12:60", "yesterday"] {
+            assert_eq!(parse_utc(text), None, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::error::Error as StdError;
+
+use crate::layers;
+use crate::registry::{self, Platform};
+use crate::ui::{self, App, TreeItems};
+
+/// 태그 줄 뒤에 붙일 수 있는 열
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Digest,
+    Size,
+    Created,
+}
+
+impl Column {
+    pub fn parse(text: &str) -> Option<Column> {
+        match text {
+            "digest" => Some(Column::Digest),
+            "size" => Some(Column::Size),
+            "created" => Some(Column::Created),
+            _ => None,
+        }
+    }
+}
+
+pub struct PrintOptions {
+    /// 1: 1뎁스만, 2: 저장소까지, 3: 태그까지
+    pub depth: usize,
+    pub max_tags_per_repo: Option<usize>,
+    /// ├── 대신 |-- 같은 ASCII 문자
+    pub ascii: bool,
+    pub columns: Vec<Column>,
+    /// --platform. 없으면 이미지마다 `registry::image_platform`으로 고름
+    pub platform: Option<Platform>,
+}
+
+/// 잘린 태그 자리에 넣는 "… N more" 항목인지
+fn is_more_label(tag: &str) -> bool {
+    tag.starts_with("… ") && tag.ends_with(" more")
+}
+
+/// 깊이와 저장소당 태그 수 제한을 적용한 트리 데이터
+pub fn limit_tree(repositories: &BTreeMap<String, Vec<String>>, options: &PrintOptions) -> TreeItems {
+    let mut items = registry::tree_items_from_tags(repositories);
+    for (_, repos) in items.iter_mut() {
+        if options.depth < 2 {
+            repos.clear();
+        }
+        for (_, tags) in repos.iter_mut() {
+            if options.depth < 3 {
+                tags.clear();
+            }
+            if let Some(max) = options.max_tags_per_repo {
+                if tags.len() > max {
+                    let hidden = tags.len() - max;
+                    tags.truncate(max);
+                    tags.push(format!("… {} more", hidden));
+                }
+            }
+        }
+    }
+    items
+}
+
+pub fn to_ascii(line: &str) -> String {
+    line.replace("├── ", "|-- ").replace("└── ", "`-- ").replace("│   ", "|   ").replace('…', "...")
+}
+
+/// 플랫폼 이미지의 레이어 크기 합과 config의 생성 시각
+pub async fn image_summary(image: &str, tag: &str, preferred: Option<&Platform>) -> Result<(u64, Option<String>), Box<dyn StdError>> {
+    let platform = registry::image_platform(image, tag, preferred).await;
+    let (_, manifest) = registry::fetch_image_manifest(image, tag, &platform).await?;
+    let size = manifest.layers.iter().map(|l| l.size).sum();
+    let created = match &manifest.config {
+        Some(descriptor) => {
+            let config: Value = serde_json::from_slice(&registry::fetch_blob(image, descriptor, Vec::new()).await?)?;
+            config.get("created").and_then(|c| c.as_str()).map(|c| c.to_string())
+        }
+        None => None,
+    };
+    Ok((size, created))
+}
+
+/// 태그 하나의 열 값. 읽지 못한 값은 "-"
+async fn column_values(image: &str, tag: &str, options: &PrintOptions) -> Vec<String> {
+    let digest = if options.columns.contains(&Column::Digest) { registry::fetch_manifest_digest(image, tag).await.ok() } else { None };
+    let summary = if options.columns.iter().any(|c| *c != Column::Digest) { image_summary(image, tag, options.platform.as_ref()).await.ok() } else { None };
+    options
+        .columns
+        .iter()
+        .map(|column| {
+            let value = match column {
+                Column::Digest => digest.clone(),
+                Column::Size => summary.as_ref().map(|(size, _)| layers::format_size(*size)),
+                Column::Created => summary.as_ref().and_then(|(_, created)| created.clone()),
+            };
+            value.unwrap_or_else(|| "-".to_string())
+        })
+        .collect()
+}
+
+/// TUI와 같은 트리를 줄 단위 문자열로 그립니다.
+pub async fn render(repositories: &BTreeMap<String, Vec<String>>, options: &PrintOptions) -> Vec<String> {
+    let app = App::new(limit_tree(repositories, options));
+    let mut rows = Vec::new();
+    for (i, item) in app.items.iter().enumerate() {
+        let line = if options.ascii { to_ascii(item) } else { item.clone() };
+        let (image, tag) = ui::split_full_image_name(&app.full_image_names[i]);
+        let values = if app.item_types[i] == 3 && !options.columns.is_empty() && !is_more_label(tag) {
+            column_values(image, tag, options).await
+        } else {
+            Vec::new()
+        };
+        rows.push((line, values));
+    }
+    if options.columns.is_empty() {
+        return rows.into_iter().map(|(line, _)| line).collect();
+    }
+
+    // 트리와 각 열을 가장 긴 값에 맞춰 정렬
+    let tree_width = rows.iter().map(|(line, _)| line.chars().count()).max().unwrap_or(0);
+    let mut widths = vec![0; options.columns.len()];
+    for (_, values) in &rows {
+        for (width, value) in widths.iter_mut().zip(values) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+    rows.into_iter()
+        .map(|(line, values)| {
+            if values.is_empty() {
+                return line;
+            }
+            let mut out = format!("{:<width$}", line, width = tree_width);
+            for (value, width) in values.iter().zip(&widths) {
+                out.push_str(&format!("  {:<width$}", value, width = width));
+            }
+            out.trim_end().to_string()
+        })
+        .collect()
+}
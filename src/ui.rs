@@ -11,15 +11,35 @@ use tui::widgets::Clear as PopupClear;
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 use crossterm::{execute, terminal::{Clear, ClearType}};
 
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use crate::cache;
+use crate::diff::RegistryState;
+use crate::efficiency::{self, EfficiencyReport};
+use crate::extract;
+use crate::filebrowser::FileBrowser;
+use crate::fsdiff;
+use crate::history;
+use crate::inventory;
+use crate::metadiff;
+use crate::picker::{self, RegistryChoice, RegistryPicker, TreeState};
+use crate::provenance::{self, Attestation};
+use crate::layers::{self, FileKind, LayerFiles};
 use crate::registry;
+use crate::sbom::Package;
+use crate::verify::{self, PublicKey};
+use crate::vuln::{self, VulnDb, VulnReport};
+use crate::watch;
 
 use tui::backend::CrosstermBackend;
 use std::io::Stdout;
+use serde::Serialize;
 use serde_json::Value;
 use tabled::{ Tabled, Table, settings::{Style as TStyle, Modify, object::Columns, Alignment as TAlignment}};
     
-#[derive(Tabled)]
+#[derive(Tabled, Serialize)]
 pub struct CompatibilityRow {
     #[tabled(rename = "id")]
     pub id: String,
@@ -37,18 +57,38 @@ pub struct CompatibilityRow {
     pub config: String,
 }
 
-#[derive(Tabled)]
+#[derive(Tabled, Serialize)]
 pub struct LayerInfo {
     #[tabled(rename = "BlobSum (Digest)")]
+    #[serde(rename = "digest")]
     pub blob_sum: String,
-    
+
+    #[tabled(skip)]
+    pub media_type: String,
+
     #[tabled(rename = "Size")]
+    #[serde(skip)]
     pub size: String,
-    
+
+    /// JSON에는 사람이 읽는 크기 대신 바이트 수
+    #[tabled(skip)]
+    #[serde(rename = "size")]
+    pub bytes: u64,
+
     #[tabled(rename = "Command")]
     pub command: String,
 }
 
+/// (1뎁스, [(2뎁스, [태그])]) 형태의 트리 원본 데이터
+pub type TreeItems = Vec<(String, Vec<(String, Vec<String>)>)>;
+
+/// 트리 항목 뒤에 붙는 상태 표시 (예: 동기화 상태, 서명 검증 결과)
+#[derive(Debug, Clone)]
+pub struct TagMark {
+    pub label: String,
+    pub color: Color,
+}
+
 pub struct App {
     pub items: Vec<String>,
     pub item_types: Vec<usize>,
@@ -59,51 +99,287 @@ pub struct App {
     pub popup_content: String,
     pub popup_scroll_offset: usize, // 팝업 내부 스크롤 위치
     pub popup_scroll_offset_x: usize, // 수평 스크롤 오프셋 추가
+    pub title: String,
+    pub marks: HashMap<String, TagMark>, // 풀 이미지 이름별 상태 표시
+    pub popup_tab: PopupTab,
+    pub file_browser: Option<FileBrowser>,
+    pub files_error: Option<String>,
+    pub files_load: Option<FilesLoad>,
+    pub efficiency: Option<EfficiencyReport>,
+    pub packages: Option<PackageView>,
+    pub packages_load: Option<TabLoad<PackageView>>,
+    pub artifacts: HashMap<String, registry::Descriptor>, // 4뎁스(referrer) 항목의 descriptor
+    pub trusted_keys: Vec<PublicKey>, // 서명 검증에 쓰는 공개키
+    pub compare_selection: Vec<String>, // 비교하려고 표시한 태그 (최대 2개)
+    pub comparison: Option<CompareView>,
+    pub compare_load: Option<tokio::sync::oneshot::Receiver<CompareView>>, // c/C 비교 작업. 끝나면 comparison을 결과로 바꿈
+    pub vuln_db: Option<Arc<VulnDb>>,
+    pub vulns: Option<VulnReport>,
+    pub vuln_error: Option<String>,
+    pub vulns_load: Option<TabLoad<Result<VulnReport, String>>>,
+    pub severities: HashMap<String, TagMark>, // 풀 이미지 이름별 취약점 심각도 배지
+    pub attestations: Option<Vec<Attestation>>,
+    pub attestations_error: Option<String>,
+    pub registries: Vec<RegistryChoice>, // R로 고를 수 있는 registry 목록
+    pub current_registry: usize,
+    pub registry_picker: Option<RegistryPicker>,
+    pub saved_trees: HashMap<usize, TreeState>, // registry별로 보관한 트리 화면 상태
+    pub row_registry: HashMap<String, usize>, // diff-registries: 태그가 있는 registry (registries 인덱스)
+    active_registry: Option<usize>, // 지금 요청을 보내는 registry (registries 인덱스)
+    pub refresh: Option<TreeRefresh>, // 캐시로 띄운 트리를 재검증하는 백그라운드 작업
+    pub auto_refresh: Option<AutoRefresh>,
+    pub new_marks: HashSet<String>, // --refresh가 새로 찾은 항목 ("new" 배지, 다음 폴링이나 Enter로 지움)
+    pub platform: Option<registry::Platform>, // --platform: 멀티 아키텍처 이미지에서 읽을 플랫폼
 }
 
-impl App {
-    pub fn new(raw_items: Vec<(String, Vec<(String, Vec<String>)>)>) -> App {
-        let mut items = Vec::new();
-        let mut item_types = Vec::new();
-        let mut full_image_names = Vec::new(); // 풀 이미지 이름 목록
-
-        for (i, (depth1, depth2_list)) in raw_items.iter().enumerate() {
-            let host = depth1.clone();
-            let is_last_host = i == raw_items.len() - 1;
-        
-            // 호스트 항목 추가 (1뎁스)
-            let host_prefix = if is_last_host { "└── " } else { "├── " };
-            items.push(format!("{}{}", host_prefix, depth1));
-            item_types.push(1);
-            full_image_names.push(host.clone());
-        
-            for (j, (depth2, tags)) in depth2_list.iter().enumerate() {
-                let repo_name = format!("{}/{}", host, depth2);
-                let is_last_repo = j == depth2_list.len() - 1;
-        
-                // 이미지명 항목 추가 (2뎁스)
-                let depth2_prefix = if is_last_host { "    " } else { "│   " };
-                let repo_prefix = if is_last_repo { "└── " } else { "├── " };
-                items.push(format!("{}{}{}", depth2_prefix, repo_prefix, depth2));
-                item_types.push(2);
-                full_image_names.push(repo_name.clone());
-        
-                for (k, tag) in tags.iter().enumerate() {
-                    let is_last_tag = k == tags.len() - 1;
-        
-                    // 태그 항목 추가 (3뎁스)
-                    let tag_prefix = if is_last_repo {
-                        if is_last_tag { format!("{}    └── ", depth2_prefix) } else { format!("{}    ├── ", depth2_prefix) }
-                    } else {
-                        if is_last_tag { format!("{}│   └── ", depth2_prefix) } else { format!("{}│   ├── ", depth2_prefix) }
-                    };
-                    let full_image_name = format!("{}/{}", repo_name, tag);
-                    items.push(format!("{}{}", tag_prefix, tag));
-                    item_types.push(3);
-                    full_image_names.push(full_image_name);
-                }
+/// 캐시된 트리를 먼저 보여준 뒤 registry에서 다시 읽는 작업
+pub struct TreeRefresh {
+    registry: usize,
+    cached: BTreeMap<String, Vec<String>>,
+    receiver: tokio::sync::oneshot::Receiver<Result<BTreeMap<String, Vec<String>>, String>>,
+}
+
+/// Files 탭을 열 때 백그라운드로 레이어를 읽는 작업
+pub struct FilesLoad {
+    full_image_name: String,
+    receiver: tokio::sync::oneshot::Receiver<Result<Vec<LayerFiles>, String>>,
+}
+
+/// Packages/Vulns 탭을 열 때 백그라운드로 읽는 작업. 팝업을 닫으면 버림
+pub struct TabLoad<T> {
+    full_image_name: String,
+    receiver: tokio::sync::oneshot::Receiver<T>,
+}
+
+/// --refresh: 일정 간격으로 registry를 다시 읽어 새 저장소/태그와 다른 이미지를 가리키게 된 태그에 "new" 배지를 붙임
+/// 배지는 verify/vuln 표시와 따로 보관하며 다음 폴링 결과가 오면 새로 계산함
+pub struct AutoRefresh {
+    interval: Duration,
+    registry: usize,
+    // 이전 폴링 결과. 없으면 다음 폴링이 비교 기준이 됨
+    state: Option<RegistryState>,
+    next_poll: Instant,
+    pending: Option<tokio::sync::oneshot::Receiver<Result<RegistryState, String>>>,
+}
+
+impl AutoRefresh {
+    pub fn new(interval: Duration) -> AutoRefresh {
+        AutoRefresh { interval, registry: 0, state: None, next_poll: Instant::now(), pending: None }
+    }
+}
+
+/// 상세 팝업의 Packages 탭 상태
+pub struct PackageView {
+    pub packages: Vec<Package>,
+    pub sources: Vec<String>,
+    pub error: Option<String>,
+    pub filter: String,
+    pub searching: bool,
+}
+
+impl PackageView {
+    pub fn visible(&self) -> Vec<&Package> {
+        self.packages.iter().filter(|p| self.filter.is_empty() || p.matches(&self.filter)).collect()
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        if let Some(error) = &self.error {
+            return vec![error.clone()];
+        }
+        if self.sources.is_empty() {
+            return vec!["No SPDX or CycloneDX SBOM is attached to this image.".to_string()];
+        }
+        let visible = self.visible();
+        let mut lines = vec![
+            format!("{} of {} packages from {}", visible.len(), self.packages.len(), self.sources.join(", ")),
+            format!("{:<32} {:<20} {:<12} {:<24} {}", "NAME", "VERSION", "TYPE", "LICENSE", "PURL"),
+        ];
+        for package in visible {
+            lines.push(format!(
+                "{:<32} {:<20} {:<12} {:<24} {}",
+                package.name, package.version, package.kind, package.license, package.purl
+            ));
+        }
+        lines
+    }
+}
+
+/// 두 태그의 비교 결과를 보여 주는 팝업
+pub struct CompareView {
+    pub title: String,
+    pub lines: Vec<(String, Color)>,
+    /// 있으면 `lines`를 왼쪽(A), 이것을 오른쪽(B) 창에 나란히 그림
+    pub right: Option<Vec<(String, Color)>>,
+    pub scroll: usize,
+}
+
+impl CompareView {
+    fn from_fs_diff(diff: &fsdiff::FsDiff) -> CompareView {
+        let mut lines = vec![(diff.summary(), Color::Cyan)];
+        if diff.changes.is_empty() {
+            lines.push(("No file changes.".to_string(), Color::Gray));
+        }
+        for change in &diff.changes {
+            let sizes = match change.status {
+                fsdiff::ChangeStatus::Modified => format!(
+                    "{} -> {} ({})",
+                    layers::format_size(change.size_a.unwrap_or(0)),
+                    layers::format_size(change.size_b.unwrap_or(0)),
+                    fsdiff::format_delta(change.delta)
+                ),
+                _ => fsdiff::format_delta(change.delta),
+            };
+            lines.push((format!("{} /{}  {}", change.status.symbol(), change.path, sizes), change.status.color()));
+        }
+        CompareView { title: format!("Files  A={}  B={}", diff.image_a, diff.image_b), lines, right: None, scroll: 0 }
+    }
+
+    fn from_meta_diff(diff: &metadiff::MetaDiff) -> CompareView {
+        let (left, right) = metadiff::side_by_side(diff);
+        CompareView { title: format!("Manifest/config  {}", diff.summary()), lines: left, right: Some(right), scroll: 0 }
+    }
+
+    fn error(title: String, error: &dyn std::error::Error) -> CompareView {
+        CompareView { title, lines: vec![(format!("Comparison failed: {}", error), Color::Red)], right: None, scroll: 0 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopupTab {
+    Details,
+    Files,
+    Efficiency,
+    Packages,
+    Vulns,
+    Attestations,
+}
+
+impl PopupTab {
+    pub fn next(self) -> PopupTab {
+        match self {
+            PopupTab::Details => PopupTab::Files,
+            PopupTab::Files => PopupTab::Efficiency,
+            PopupTab::Efficiency => PopupTab::Packages,
+            PopupTab::Packages => PopupTab::Vulns,
+            PopupTab::Vulns => PopupTab::Attestations,
+            PopupTab::Attestations => PopupTab::Details,
+        }
+    }
+
+    /// 팝업 제목에 쓰는 탭 목록. 현재 탭은 대괄호로 표시
+    pub fn title(self) -> String {
+        [
+            (PopupTab::Details, "Details"),
+            (PopupTab::Files, "Files"),
+            (PopupTab::Efficiency, "Efficiency"),
+            (PopupTab::Packages, "Packages"),
+            (PopupTab::Vulns, "Vulns"),
+            (PopupTab::Attestations, "Attestations"),
+        ]
+            .iter()
+            .map(|(tab, name)| if *tab == self { format!("[{}]", name) } else { name.to_string() })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// "group/app/1.0" 형태의 풀 이미지 이름을 (이미지, 태그)로 분리합니다.
+pub fn split_full_image_name(full_image_name: &str) -> (&str, &str) {
+    match full_image_name.rsplit_once('/') {
+        Some((image, tag)) => (image, tag),
+        None => (full_image_name, ""),
+    }
+}
+
+/// 두 태그를 같은 플랫폼으로 비교하도록 첫 번째 태그("repo:tag") 기준으로 고름
+async fn compare_platform(reference: &str, preferred: Option<&registry::Platform>) -> registry::Platform {
+    match registry::parse_reference(reference).ok() {
+        Some((image, tag)) => registry::image_platform(&image, &tag, preferred).await,
+        None => registry::pick_platform(&[], preferred),
+    }
+}
+
+/// 풀 이미지 이름을 CLI와 같은 "repo:tag" 참조로 바꿉니다.
+pub fn image_reference(full_image_name: &str) -> String {
+    let (image, tag) = split_full_image_name(full_image_name);
+    format!("{}:{}", image.trim_end_matches('/'), tag)
+}
+
+/// referrer 한 줄: "application/spdx+json  1.2 KB  sha256:abcdef012345  key=value, ..."
+pub fn artifact_label(descriptor: &registry::Descriptor) -> String {
+    let kind = descriptor.artifact_type.as_deref().unwrap_or(&descriptor.media_type);
+    let mut label = format!(
+        "{}  {}  {}",
+        kind,
+        layers::format_size(descriptor.size),
+        descriptor.digest.chars().take(19).collect::<String>()
+    );
+    if !descriptor.annotations.is_empty() {
+        let annotations: Vec<String> = descriptor.annotations.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+        label.push_str(&format!("  {}", annotations.join(", ")));
+    }
+    label
+}
+
+/// 트리 원본 데이터를 화면 줄, 항목 깊이, 풀 이미지 이름으로 펼칩니다.
+/// 트리에서 저장소 줄의 full_image_names 값. 슬래시가 없는 저장소는 2뎁스가 비어 "repo/"가 됨
+pub fn repository_row_name(repository: &str) -> String {
+    if repository.contains('/') {
+        repository.to_string()
+    } else {
+        format!("{}/", repository)
+    }
+}
+
+fn tree_rows(raw_items: &TreeItems) -> (Vec<String>, Vec<usize>, Vec<String>) {
+    let mut items = Vec::new();
+    let mut item_types = Vec::new();
+    let mut full_image_names = Vec::new(); // 풀 이미지 이름 목록
+
+    for (i, (depth1, depth2_list)) in raw_items.iter().enumerate() {
+        let host = depth1.clone();
+        let is_last_host = i == raw_items.len() - 1;
+    
+        // 호스트 항목 추가 (1뎁스)
+        let host_prefix = if is_last_host { "└── " } else { "├── " };
+        items.push(format!("{}{}", host_prefix, depth1));
+        item_types.push(1);
+        full_image_names.push(host.clone());
+    
+        for (j, (depth2, tags)) in depth2_list.iter().enumerate() {
+            let repo_name = format!("{}/{}", host, depth2);
+            let is_last_repo = j == depth2_list.len() - 1;
+    
+            // 이미지명 항목 추가 (2뎁스)
+            let depth2_prefix = if is_last_host { "    " } else { "│   " };
+            let repo_prefix = if is_last_repo { "└── " } else { "├── " };
+            items.push(format!("{}{}{}", depth2_prefix, repo_prefix, depth2));
+            item_types.push(2);
+            full_image_names.push(repo_name.clone());
+    
+            for (k, tag) in tags.iter().enumerate() {
+                let is_last_tag = k == tags.len() - 1;
+    
+                // 태그 항목 추가 (3뎁스)
+                let tag_prefix = if is_last_repo {
+                    if is_last_tag { format!("{}    └── ", depth2_prefix) } else { format!("{}    ├── ", depth2_prefix) }
+                } else {
+                    if is_last_tag { format!("{}│   └── ", depth2_prefix) } else { format!("{}│   ├── ", depth2_prefix) }
+                };
+                let full_image_name = format!("{}/{}", repo_name, tag);
+                items.push(format!("{}{}", tag_prefix, tag));
+                item_types.push(3);
+                full_image_names.push(full_image_name);
             }
         }
+    }
+
+    (items, item_types, full_image_names)
+}
+
+impl App {
+    pub fn new(raw_items: TreeItems) -> App {
+        let (items, item_types, full_image_names) = tree_rows(&raw_items);
 
         App {
             items,
@@ -115,9 +391,49 @@ impl App {
             popup_content: String::new(),
             popup_scroll_offset: 0,
             popup_scroll_offset_x: 0,
+            title: "Docker Images Tree".to_string(),
+            marks: HashMap::new(),
+            popup_tab: PopupTab::Details,
+            file_browser: None,
+            files_error: None,
+            files_load: None,
+            efficiency: None,
+            packages: None,
+            packages_load: None,
+            artifacts: HashMap::new(),
+            trusted_keys: Vec::new(),
+            compare_selection: Vec::new(),
+            comparison: None,
+            compare_load: None,
+            vuln_db: None,
+            vulns_load: None,
+            vulns: None,
+            vuln_error: None,
+            severities: HashMap::new(),
+            attestations: None,
+            attestations_error: None,
+            registries: Vec::new(),
+            current_registry: 0,
+            registry_picker: None,
+            saved_trees: HashMap::new(),
+            refresh: None,
+            auto_refresh: None,
+            row_registry: HashMap::new(),
+            active_registry: None,
+            new_marks: HashSet::new(),
+            platform: None,
         }
     }
 
+    pub fn with_title(mut self, title: &str) -> App {
+        self.title = title.to_string();
+        self
+    }
+
+    pub fn set_mark(&mut self, full_image_name: &str, label: &str, color: Color) {
+        self.marks.insert(full_image_name.to_string(), TagMark { label: label.to_string(), color });
+    }
+
     pub fn next(&mut self, max_visible_items: usize) {
         if self.selected_index + 1 < self.items.len() {
             self.selected_index += 1;
@@ -161,7 +477,9 @@ impl App {
     }
     
     pub async fn open_popup(&mut self) {
-        if self.item_types[self.selected_index] == 3 {
+        if self.item_types[self.selected_index] == 4 {
+            self.open_artifact_popup().await;
+        } else if self.item_types[self.selected_index] == 3 {
             let full_image_name = &self.full_image_names[self.selected_index];
             let parts: Vec<&str> = full_image_name.rsplitn(2, '/').collect();
             let tag_name = parts[0];
@@ -180,23 +498,539 @@ impl App {
                     .with(Modify::new(Columns::single(0)).with(TAlignment::left()))
                     .with(Modify::new(Columns::single(1)).with(TAlignment::left()));
     
+                // 인덱스면 자식 매니페스트를 플랫폼/attestation 대상과 함께 요약
+                let children = manifest_value
+                    .get("manifests")
+                    .and_then(|m| serde_json::from_value::<Vec<registry::Descriptor>>(m.clone()).ok())
+                    .map(|children| {
+                        let lines: Vec<String> = children.iter().map(|c| format!("  {}", provenance::index_entry_label(c))).collect();
+                        let platform = registry::pick_platform(&children, self.platform.as_ref());
+                        format!("Manifests:\n{}\nOther tabs show {}\n", lines.join("\n"), platform)
+                    })
+                    .unwrap_or_default();
+
+                // 이 태그가 예전에 가리키던 다이제스트 (history::observe가 기록)
+                let observations = history::timeline(&registry::get_registry_url(), image_name, tag_name);
+                let timeline = if observations.is_empty() { String::new() } else { format!("{}\n\n", history::lines(&observations).join("\n")) };
+
                 // popup_content에 테이블과 구분선, 전체 JSON 추가
-                self.popup_content = format!("{}\n------------------------\n{}", table, full_json);
+                self.popup_content = format!("{}{}{}\n------------------------\n{}", timeline, children, table, full_json);
     
                 self.popup_open = true;
                 self.popup_scroll_offset = 0;
+                self.popup_tab = PopupTab::Details;
+                self.file_browser = None;
+                self.files_error = None;
+                self.files_load = None;
+                self.efficiency = None;
+                self.packages = None;
+            }
+        }
+    }
+
+    /// 태그 아래에 referrer 항목을 끼워 넣습니다. 태그 줄의 트리 접두사를 이어받음
+    pub fn insert_referrers(&mut self, index: usize, descriptors: &[registry::Descriptor]) {
+        let full_image_name = self.full_image_names[index].clone();
+        let (_, tag) = split_full_image_name(&full_image_name);
+        let item = &self.items[index];
+        let prefix = &item[..item.len() - tag.len()];
+        let child_prefix = match prefix.strip_suffix("├── ") {
+            Some(p) => format!("{}│   ", p),
+            None => format!("{}    ", prefix.strip_suffix("└── ").unwrap_or(prefix)),
+        };
+
+        let mut rows: Vec<(String, String)> = descriptors
+            .iter()
+            .map(|d| {
+                let name = format!("{}@{}", full_image_name, d.digest);
+                self.artifacts.insert(name.clone(), d.clone());
+                (artifact_label(d), name)
+            })
+            .collect();
+        if rows.is_empty() {
+            rows.push(("(no referrers)".to_string(), format!("{}@", full_image_name)));
+        }
+        let count = rows.len();
+        for (offset, (label, name)) in rows.into_iter().enumerate() {
+            let branch = if offset + 1 == count { "└── " } else { "├── " };
+            self.items.insert(index + 1 + offset, format!("{}{}{}", child_prefix, branch, label));
+            self.item_types.insert(index + 1 + offset, 4);
+            self.full_image_names.insert(index + 1 + offset, name);
+        }
+    }
+
+    fn remove_referrers(&mut self, index: usize) -> bool {
+        let mut removed = false;
+        while self.item_types.get(index + 1) == Some(&4) {
+            self.items.remove(index + 1);
+            self.item_types.remove(index + 1);
+            let name = self.full_image_names.remove(index + 1);
+            self.artifacts.remove(&name);
+            removed = true;
+        }
+        removed
+    }
+
+    async fn load_referrers(&mut self, index: usize) -> Result<(), Box<dyn std::error::Error>> {
+        let full_image_name = self.full_image_names[index].clone();
+        let (image, tag) = split_full_image_name(&full_image_name);
+        let digest = registry::fetch_manifest_digest(image, tag).await?;
+        let descriptors = registry::fetch_referrers(image, &digest).await?;
+        self.insert_referrers(index, &descriptors);
+        Ok(())
+    }
+
+    /// 선택한 태그의 referrer를 펼치거나 접습니다.
+    pub async fn toggle_referrers(&mut self) {
+        let index = self.selected_index;
+        if self.item_types[index] != 3 || self.remove_referrers(index) {
+            return;
+        }
+        if let Err(e) = self.load_referrers(index).await {
+            self.set_mark(&self.full_image_names[index].clone(), &format!("referrers: {}", e), Color::Red);
+        }
+    }
+
+    /// 모든 태그의 referrer를 미리 불러옵니다. 뒤에서부터 넣어야 앞쪽 인덱스가 바뀌지 않음
+    pub async fn load_all_referrers(&mut self) {
+        for index in (0..self.items.len()).rev() {
+            if self.item_types[index] == 3 {
+                if let Err(e) = self.load_referrers(index).await {
+                    self.set_mark(&self.full_image_names[index].clone(), &format!("referrers: {}", e), Color::Red);
+                }
+            }
+        }
+    }
+
+    async fn verify_tag(&mut self, index: usize) {
+        let full_image_name = self.full_image_names[index].clone();
+        let (image, tag) = split_full_image_name(&full_image_name);
+        let result = match registry::fetch_manifest_digest(image, tag).await {
+            Ok(digest) => verify::verify_digest(image, &digest, &self.trusted_keys).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(status) => self.set_mark(&full_image_name, status.label(), status.color()),
+            Err(e) => self.set_mark(&full_image_name, &format!("verify failed: {}", e), Color::Red),
+        }
+    }
+
+    /// 선택한 태그의 서명을 검증해 배지로 표시합니다. 키가 없으면 기본 키 디렉터리에서 읽음
+    pub async fn verify_selected(&mut self) {
+        let index = self.selected_index;
+        if self.item_types[index] != 3 {
+            return;
+        }
+        if self.trusted_keys.is_empty() {
+            match verify::load_keys(&[]) {
+                Ok(keys) => self.trusted_keys = keys,
+                Err(e) => {
+                    self.set_mark(&self.full_image_names[index].clone(), &e.to_string(), Color::Red);
+                    return;
+                }
+            }
+        }
+        self.verify_tag(index).await;
+    }
+
+    /// 모든 태그를 검증합니다. 서명/attestation을 담은 `sha256-<hex>` 태그 자체는 건너뜀
+    pub async fn verify_all(&mut self) {
+        for index in 0..self.items.len() {
+            let (_, tag) = split_full_image_name(&self.full_image_names[index]);
+            if self.item_types[index] == 3 && !tag.starts_with("sha256-") {
+                self.verify_tag(index).await;
             }
         }
     }
 
+    /// referrer 항목의 descriptor와 매니페스트를 상세 팝업에 보여 줍니다.
+    async fn open_artifact_popup(&mut self) {
+        let full_name = self.full_image_names[self.selected_index].clone();
+        let Some(descriptor) = self.artifacts.get(&full_name).cloned() else { return };
+        let (tag_name, _) = full_name.rsplit_once('@').unwrap_or((&full_name, ""));
+        let (image, _) = split_full_image_name(tag_name);
+
+        let manifest = match registry::fetch_manifest_raw(image, &descriptor.digest).await {
+            Ok(raw) => serde_json::from_slice::<Value>(&raw.bytes)
+                .map(|v| serde_json::to_string_pretty(&v).unwrap_or_default())
+                .unwrap_or_else(|_| String::from_utf8_lossy(&raw.bytes).into_owned()),
+            Err(e) => format!("Could not fetch manifest: {}", e),
+        };
+        self.popup_content = format!(
+            "{}\n------------------------\n{}",
+            serde_json::to_string_pretty(&descriptor).unwrap_or_default(),
+            manifest
+        );
+        self.popup_open = true;
+        self.popup_scroll_offset = 0;
+        self.popup_tab = PopupTab::Details;
+        self.file_browser = None;
+        self.files_error = None;
+        self.files_load = None;
+        self.efficiency = None;
+        self.packages = None;
+        self.packages_load = None;
+        self.vulns = None;
+        self.vuln_error = None;
+        self.vulns_load = None;
+        self.attestations = None;
+        self.attestations_error = None;
+    }
+
     pub fn close_popup(&mut self) {
         self.popup_open = false;
         self.popup_content.clear();
+        self.file_browser = None;
+        self.files_error = None;
+        self.files_load = None;
+        self.efficiency = None;
+        self.packages = None;
+        self.packages_load = None;
+        self.vulns = None;
+        self.vuln_error = None;
+        self.vulns_load = None;
+        self.attestations = None;
+        self.attestations_error = None;
     }
-    
-    pub fn handle_popup_input(&mut self, key: KeyEvent, max_visible_popup_lines: usize) {
+
+    /// 선택된 태그의 SBOM(없으면 레이어 스캔)을 백그라운드로 읽기 시작합니다. 다 읽으면 `poll_packages`가 Packages 탭을 준비
+    pub fn load_packages(&mut self) {
+        let full_image_name = self.full_image_names[self.selected_index].clone();
+        let (image, tag) = split_full_image_name(&full_image_name);
+        let (image, tag) = (image.to_string(), tag.to_string());
+        let preferred = self.platform.clone();
+
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let platform = registry::image_platform(&image, &tag, preferred.as_ref()).await;
+            let mut view = PackageView { packages: Vec::new(), sources: Vec::new(), error: None, filter: String::new(), searching: false };
+            match inventory::image_packages(&image, &tag, &platform).await.map_err(|e| e.to_string()) {
+                Ok((packages, sources)) => {
+                    view.packages = packages;
+                    view.sources = sources;
+                }
+                Err(e) => view.error = Some(format!("Could not read packages of {}:{}: {}", image, tag, e)),
+            }
+            let _ = sender.send(view);
+        });
+        self.packages_load = Some(TabLoad { full_image_name, receiver });
+    }
+
+    pub fn poll_packages(&mut self) {
+        let Some(load) = self.packages_load.as_mut() else { return };
+        let view = match load.receiver.try_recv() {
+            Ok(view) => view,
+            Err(tokio::sync::oneshot::error::TryRecvError::Empty) => return,
+            Err(tokio::sync::oneshot::error::TryRecvError::Closed) => PackageView {
+                packages: Vec::new(),
+                sources: Vec::new(),
+                error: Some(format!("Could not read packages of {}: package task stopped", image_reference(&load.full_image_name))),
+                filter: String::new(),
+                searching: false,
+            },
+        };
+        self.packages_load = None;
+        self.packages = Some(view);
+    }
+
+    /// 취약점 DB가 없으면 기본 위치에서 읽습니다.
+    fn ensure_vuln_db(&mut self) -> Result<Arc<VulnDb>, String> {
+        if self.vuln_db.is_none() {
+            self.vuln_db = Some(Arc::new(VulnDb::load_default().map_err(|e| e.to_string())?));
+        }
+        Ok(self.vuln_db.clone().unwrap())
+    }
+
+    /// 트리의 심각도 배지. 검사하지 못했으면 짧은 배지만 달고 오류는 Vulns 탭에서 보여 줌
+    fn set_severity(&mut self, full_image_name: &str, result: &Result<VulnReport, String>) {
+        let mark = match result {
+            Ok(report) => {
+                let (label, color) = report.badge();
+                TagMark { label, color }
+            }
+            Err(_) => TagMark { label: "vulns?".to_string(), color: Color::Red },
+        };
+        self.severities.insert(full_image_name.to_string(), mark);
+    }
+
+    /// 태그의 패키지를 DB와 대조하고 트리의 심각도 배지를 갱신합니다.
+    async fn scan_vulns_tag(&mut self, index: usize) -> Result<VulnReport, String> {
+        let full_image_name = self.full_image_names[index].clone();
+        let (image, tag) = split_full_image_name(&full_image_name);
+        let platform = registry::image_platform(image, tag, self.platform.as_ref()).await;
+        let db = self.ensure_vuln_db()?;
+        let result = vuln::scan_image(&db, image, tag, &platform).await.map_err(|e| e.to_string());
+        self.set_severity(&full_image_name, &result);
+        result
+    }
+
+    /// Vulns 탭: Packages 탭에서 읽은 목록이 있으면 그대로 대조하고, 없으면 백그라운드로 검사함 (`poll_vulns`)
+    pub fn load_vulns(&mut self) {
+        let full_image_name = self.full_image_names[self.selected_index].clone();
+        let db = match self.ensure_vuln_db() {
+            Ok(db) => db,
+            Err(e) => {
+                self.vuln_error = Some(e);
+                return;
+            }
+        };
+        let (image, tag) = split_full_image_name(&full_image_name);
+        let (image, tag) = (image.to_string(), tag.to_string());
+        if let Some(view) = self.packages.as_ref().filter(|v| v.error.is_none()) {
+            let report = vuln::match_packages(&db, &format!("{}:{}", image, tag), &view.packages, view.sources.clone());
+            let result = Ok(report);
+            self.set_severity(&full_image_name, &result);
+            self.vulns = result.ok();
+            return;
+        }
+        let preferred = self.platform.clone();
+
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let platform = registry::image_platform(&image, &tag, preferred.as_ref()).await;
+            let result = vuln::scan_image(&db, &image, &tag, &platform).await.map_err(|e| e.to_string());
+            let _ = sender.send(result);
+        });
+        self.vulns_load = Some(TabLoad { full_image_name, receiver });
+    }
+
+    pub fn poll_vulns(&mut self) {
+        let Some(load) = self.vulns_load.as_mut() else { return };
+        let result = match load.receiver.try_recv() {
+            Ok(result) => result,
+            Err(tokio::sync::oneshot::error::TryRecvError::Empty) => return,
+            Err(tokio::sync::oneshot::error::TryRecvError::Closed) => Err("vulnerability scan stopped".to_string()),
+        };
+        let load = self.vulns_load.take().unwrap();
+        self.set_severity(&load.full_image_name, &result);
+        let (image, tag) = split_full_image_name(&load.full_image_name);
+        match result {
+            Ok(report) => self.vulns = Some(report),
+            Err(e) => self.vuln_error = Some(format!("Could not scan {}:{} for vulnerabilities: {}", image, tag, e)),
+        }
+    }
+
+    /// 선택된 태그의 in-toto attestation(BuildKit provenance 등)을 읽어 Attestations 탭을 준비합니다.
+    pub async fn load_attestations(&mut self) {
+        let full_image_name = self.full_image_names[self.selected_index].clone();
+        let (image, tag) = split_full_image_name(&full_image_name);
+        let platform = registry::image_platform(image, tag, self.platform.as_ref()).await;
+        match provenance::fetch_attestations(image, tag, &platform).await {
+            Ok(attestations) => self.attestations = Some(attestations),
+            Err(e) => self.attestations_error = Some(format!("Could not read attestations of {}:{}: {}", image, tag, e)),
+        }
+    }
+
+    /// 선택한 태그를 검사해 심각도 배지로 표시합니다.
+    pub async fn scan_selected_vulns(&mut self) {
+        if self.item_types[self.selected_index] == 3 {
+            let _ = self.scan_vulns_tag(self.selected_index).await;
+        }
+    }
+
+    pub async fn scan_all_vulns(&mut self) {
+        for index in 0..self.items.len() {
+            let (_, tag) = split_full_image_name(&self.full_image_names[index]);
+            if self.item_types[index] == 3 && !tag.starts_with("sha256-") {
+                let _ = self.scan_vulns_tag(index).await;
+            }
+        }
+    }
+
+    /// Packages 탭의 검색 입력. 검색 중이면 true를 돌려줌
+    fn handle_package_search(&mut self, key: KeyEvent) -> bool {
+        let Some(view) = self.packages.as_mut() else { return false };
+        if !view.searching {
+            if key.code == KeyCode::Char('/') {
+                view.searching = true;
+                view.filter.clear();
+                self.popup_scroll_offset = 0;
+                return true;
+            }
+            return false;
+        }
+        match key.code {
+            KeyCode::Char(c) => view.filter.push(c),
+            KeyCode::Backspace => {
+                view.filter.pop();
+            }
+            KeyCode::Esc => {
+                view.filter.clear();
+                view.searching = false;
+            }
+            KeyCode::Enter => view.searching = false,
+            _ => {}
+        }
+        self.popup_scroll_offset = 0;
+        true
+    }
+
+    /// 선택된 태그의 레이어를 백그라운드로 모두 읽기 시작합니다. 다 읽으면 `poll_files`가 Files 탭을 준비
+    pub fn load_files(&mut self) {
+        let full_image_name = self.full_image_names[self.selected_index].clone();
+        let (image, tag) = split_full_image_name(&full_image_name);
+        let (image, tag) = (image.to_string(), tag.to_string());
+        let preferred = self.platform.clone();
+
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let platform = registry::image_platform(&image, &tag, preferred.as_ref()).await;
+            let result = async {
+                let (_, manifest) = registry::fetch_image_manifest(&image, &tag, &platform).await.map_err(|e| e.to_string())?;
+                let mut files = Vec::new();
+                for (index, layer) in manifest.layers.iter().enumerate() {
+                    files.push(layers::fetch_layer_files(&image, layer, index).await.map_err(|e| e.to_string())?);
+                }
+                Ok(files)
+            }
+            .await;
+            let _ = sender.send(result);
+        });
+        self.files_load = Some(FilesLoad { full_image_name, receiver });
+    }
+
+    /// 레이어를 다 읽었으면 Files 탭(과 Efficiency 탭)을 준비합니다. 그 사이에 팝업을 닫았으면 결과를 버림
+    pub fn poll_files(&mut self) {
+        let Some(load) = self.files_load.as_mut() else { return };
+        let result = match load.receiver.try_recv() {
+            Ok(result) => result,
+            Err(tokio::sync::oneshot::error::TryRecvError::Empty) => return,
+            Err(tokio::sync::oneshot::error::TryRecvError::Closed) => Err("layer task stopped".to_string()),
+        };
+        let load = self.files_load.take().unwrap();
+        let (image, tag) = split_full_image_name(&load.full_image_name);
+        match result {
+            Ok(files) => self.file_browser = Some(FileBrowser::new(image, tag, files)),
+            Err(e) => self.files_error = Some(format!("Could not read layers of {}:{}: {}", image, tag, e)),
+        }
+        self.analyze_efficiency();
+    }
+
+    /// Files 탭에서 읽은 레이어 목록을 그대로 분석에 사용
+    fn analyze_efficiency(&mut self) {
+        if self.popup_tab == PopupTab::Efficiency && self.efficiency.is_none() {
+            if let Some(browser) = &self.file_browser {
+                let name = format!("{}:{}", browser.image, browser.tag);
+                self.efficiency = Some(efficiency::analyze(&name, &browser.layers, efficiency::DEFAULT_TOP));
+            }
+        }
+    }
+
+    /// Files 탭에서 선택한 항목을 현재 디렉터리에 꺼냅니다.
+    pub async fn extract_selected_file(&mut self) {
+        let Some(browser) = self.file_browser.as_mut() else { return };
+        let Some(row) = browser.selected_row().cloned() else { return };
+        let name = if row.name.is_empty() { "rootfs".to_string() } else { row.name.clone() };
+        let dest = std::path::PathBuf::from(&name);
+        // 현재 디렉터리에 같은 이름이 있으면 x를 한 번 더 눌러야 덮어씀
+        if std::fs::symlink_metadata(&dest).is_ok() && browser.confirm_overwrite.as_deref() != Some(row.path.as_str()) {
+            browser.status = Some(format!("./{} already exists; press x again to overwrite it", name));
+            browser.confirm_overwrite = Some(row.path.clone());
+            return;
+        }
+        browser.confirm_overwrite = None;
+        let descriptors: Vec<registry::Descriptor> = browser.layers.iter().map(|l| l.descriptor()).collect();
+
+        let result = match row.entry.as_ref().map(|e| (e.kind, e.layer)) {
+            _ if row.whiteout => Err("deleted entries cannot be extracted".into()),
+            // 파일이 들어 있는 레이어를 이미 알고 있으므로 그 레이어만 내려받음
+            Some((FileKind::File, layer)) => extract::extract_from_layer(&browser.image, &descriptors[layer], &row.path, &dest)
+                .await
+                .map(|bytes| format!("Extracted /{} ({}) to ./{}", row.path, layers::format_size(bytes), name)),
+            Some((FileKind::Symlink, _)) | Some((FileKind::Hardlink, _)) => {
+                extract::extract_path(&browser.image, &descriptors, &row.path, &dest, true)
+                    .await
+                    .map(|r| format!("Extracted /{} (-> /{}) to ./{}", row.path, r.source_path, name))
+            }
+            _ => {
+                let filter = browser.layers_under(&row.path);
+                extract::extract_directory(&browser.image, &descriptors, &row.path, &dest, Some(&filter))
+                    .await
+                    .map(|r| match r.missing_links.len() {
+                        0 => format!("Extracted /{} ({} files, {}) to ./{}/", row.path, r.files, layers::format_size(r.bytes), name),
+                        n => format!(
+                            "Extracted /{} ({} files, {}) to ./{}/, {} hard link(s) skipped",
+                            row.path,
+                            r.files,
+                            layers::format_size(r.bytes),
+                            name,
+                            n
+                        ),
+                    })
+            }
+        };
+        browser.status = Some(result.unwrap_or_else(|e| format!("Extract failed: {}", e)));
+    }
+
+    async fn handle_files_input(&mut self, key: KeyEvent, max_visible_popup_lines: usize) {
+        if key.code == KeyCode::Char('x') {
+            self.extract_selected_file().await;
+            return;
+        }
+        let Some(browser) = self.file_browser.as_mut() else { return };
+        browser.confirm_overwrite = None;
+        // 첫 줄은 보기 설명이므로 목록 높이에서 제외
+        let visible = max_visible_popup_lines.saturating_sub(1);
+        match key.code {
+            KeyCode::Down => browser.move_down(1, visible),
+            KeyCode::Up => browser.move_up(1),
+            KeyCode::PageDown => browser.move_down(visible, visible),
+            KeyCode::PageUp => browser.move_up(visible),
+            KeyCode::Right => browser.expand(),
+            KeyCode::Left => browser.collapse(),
+            KeyCode::Enter | KeyCode::Char(' ') => browser.toggle(),
+            KeyCode::Char(']') => browser.next_view(),
+            KeyCode::Char('[') => browser.previous_view(),
+            _ => {}
+        }
+    }
+
+    pub async fn handle_popup_input(&mut self, key: KeyEvent, max_visible_popup_lines: usize) {
+        if self.popup_tab == PopupTab::Packages && self.handle_package_search(key) {
+            return;
+        }
+        match key.code {
+            // referrer 상세에는 Files/Efficiency 탭이 없음
+            KeyCode::Tab if self.item_types[self.selected_index] == 4 => return,
+            KeyCode::Tab => {
+                self.popup_tab = self.popup_tab.next();
+                self.popup_scroll_offset = 0;
+                let needs_layers = matches!(self.popup_tab, PopupTab::Files | PopupTab::Efficiency);
+                if needs_layers && self.file_browser.is_none() && self.files_error.is_none() && self.files_load.is_none() {
+                    self.load_files();
+                }
+                self.analyze_efficiency();
+                if self.popup_tab == PopupTab::Packages && self.packages.is_none() && self.packages_load.is_none() {
+                    self.load_packages();
+                }
+                if self.popup_tab == PopupTab::Vulns && self.vulns.is_none() && self.vuln_error.is_none() && self.vulns_load.is_none() {
+                    self.load_vulns();
+                }
+                if self.popup_tab == PopupTab::Attestations && self.attestations.is_none() && self.attestations_error.is_none() {
+                    self.load_attestations().await;
+                }
+                return;
+            }
+            KeyCode::Esc => {
+                self.close_popup();
+                return;
+            }
+            _ => {}
+        }
+        if self.popup_tab == PopupTab::Files {
+            self.handle_files_input(key, max_visible_popup_lines).await;
+            return;
+        }
+
         // 팝업 내용의 총 줄 수와 스크롤 가능한 최대 줄 수 계산
-        let max_popup_lines = self.popup_content.lines().count();
+        let max_popup_lines = match (self.popup_tab, &self.efficiency) {
+            (PopupTab::Efficiency, Some(report)) => efficiency::report_lines(report).len(),
+            (PopupTab::Efficiency, None) => 0,
+            (PopupTab::Packages, _) => self.packages.as_ref().map(|v| v.lines().len()).unwrap_or(0),
+            (PopupTab::Vulns, _) => self.vulns.as_ref().map(|r| vuln::report_lines(r).len()).unwrap_or(0),
+            (PopupTab::Attestations, _) => self.attestations.as_ref().map(|a| provenance::report_lines(a).len()).unwrap_or(0),
+            _ => self.popup_content.lines().count(),
+        };
         let max_scroll_offset = max_popup_lines.saturating_sub(max_visible_popup_lines);
 
         match key.code {
@@ -230,7 +1064,364 @@ impl App {
         }
     }
 
+    /// 선택한 태그를 비교 대상으로 표시하거나 해제합니다. 세 번째 태그를 표시하면 가장 오래된 표시가 빠집니다.
+    pub fn toggle_compare_mark(&mut self) {
+        if self.item_types[self.selected_index] != 3 {
+            return;
+        }
+        let name = self.full_image_names[self.selected_index].clone();
+        if let Some(index) = self.compare_selection.iter().position(|n| *n == name) {
+            self.compare_selection.remove(index);
+            return;
+        }
+        if self.compare_selection.len() == 2 {
+            self.compare_selection.remove(0);
+        }
+        self.compare_selection.push(name);
+    }
+
+    /// 비교할 두 태그: 표시한 태그 두 개, 또는 표시한 태그 하나와 현재 선택한 태그
+    fn compare_targets(&self) -> Option<(String, String)> {
+        match self.compare_selection.as_slice() {
+            [a, b] => Some((a.clone(), b.clone())),
+            [a] if self.item_types[self.selected_index] == 3 && self.full_image_names[self.selected_index] != *a => {
+                Some((a.clone(), self.full_image_names[self.selected_index].clone()))
+            }
+            _ => None,
+        }
+    }
+
+    /// 두 태그 비교를 백그라운드로 시작합니다. 끝날 때까지 비교 창에 진행 상태를 보여 주고 `poll_compare`가 결과로 바꿈
+    fn start_compare<F>(&mut self, title: String, progress: &str, compare: F)
+    where
+        F: std::future::Future<Output = CompareView> + Send + 'static,
+    {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let _ = sender.send(compare.await);
+        });
+        self.comparison = Some(CompareView { title, lines: vec![(progress.to_string(), Color::Gray)], right: None, scroll: 0 });
+        self.compare_load = Some(receiver);
+    }
+
+    pub fn compare_files(&mut self) {
+        let Some((a, b)) = self.compare_targets() else { return };
+        let (a, b) = (image_reference(&a), image_reference(&b));
+        let preferred = self.platform.clone();
+        let title = format!("Files  A={}  B={}", a, b);
+        self.start_compare(title.clone(), "Reading the layers that differ...", async move {
+            let platform = compare_platform(&a, preferred.as_ref()).await;
+            match fsdiff::diff_images(&a, &b, &platform).await {
+                Ok(diff) => CompareView::from_fs_diff(&diff),
+                Err(e) => CompareView::error(title, e.as_ref()),
+            }
+        });
+    }
+
+    pub fn compare_metadata(&mut self) {
+        let Some((a, b)) = self.compare_targets() else { return };
+        let (a, b) = (image_reference(&a), image_reference(&b));
+        let preferred = self.platform.clone();
+        let title = format!("Manifest/config  A={}  B={}", a, b);
+        self.start_compare(title.clone(), "Reading manifests and configs...", async move {
+            let platform = compare_platform(&a, preferred.as_ref()).await;
+            match metadiff::diff_images(&a, &b, &platform).await {
+                Ok(diff) => CompareView::from_meta_diff(&diff),
+                Err(e) => CompareView::error(title, e.as_ref()),
+            }
+        });
+    }
+
+    /// 비교가 끝났으면 진행 상태를 결과로 바꿉니다. 그 사이에 비교 창을 닫았으면 결과를 버림
+    pub fn poll_compare(&mut self) {
+        let Some(receiver) = self.compare_load.as_mut() else { return };
+        let result = match receiver.try_recv() {
+            Ok(view) => Ok(view),
+            Err(tokio::sync::oneshot::error::TryRecvError::Empty) => return,
+            Err(tokio::sync::oneshot::error::TryRecvError::Closed) => Err("compare task stopped"),
+        };
+        self.compare_load = None;
+        let Some(current) = self.comparison.as_mut() else { return };
+        *current = match result {
+            Ok(view) => view,
+            Err(e) => CompareView { title: current.title.clone(), lines: vec![(format!("Comparison failed: {}", e), Color::Red)], right: None, scroll: 0 },
+        };
+    }
+
+    pub fn handle_compare_input(&mut self, key: KeyEvent, max_visible_popup_lines: usize) {
+        let Some(view) = self.comparison.as_mut() else { return };
+        let max_scroll = view.lines.len().saturating_sub(max_visible_popup_lines);
+        match key.code {
+            KeyCode::Down => view.scroll = (view.scroll + 1).min(max_scroll),
+            KeyCode::Up => view.scroll = view.scroll.saturating_sub(1),
+            KeyCode::PageDown => view.scroll = (view.scroll + max_visible_popup_lines).min(max_scroll),
+            KeyCode::PageUp => view.scroll = view.scroll.saturating_sub(max_visible_popup_lines),
+            KeyCode::Esc | KeyCode::Enter => {
+                self.comparison = None;
+                self.compare_load = None;
+            }
+            _ => {}
+        }
+    }
+
+    /// registry 선택기를 열고 각 registry의 접속 상태를 확인합니다.
+    pub async fn open_registry_picker(&mut self) {
+        if self.registries.is_empty() {
+            return;
+        }
+        self.registry_picker = Some(RegistryPicker::open(&self.registries, self.current_registry).await);
+    }
+
+    pub async fn handle_picker_input(&mut self, key: KeyEvent) {
+        let Some(picker) = self.registry_picker.as_mut() else { return };
+        match key.code {
+            KeyCode::Down if picker.selected + 1 < self.registries.len() => picker.selected += 1,
+            KeyCode::Up => picker.selected = picker.selected.saturating_sub(1),
+            KeyCode::Esc | KeyCode::Char('R') | KeyCode::Char('q') => self.registry_picker = None,
+            KeyCode::Enter => {
+                let index = picker.selected;
+                self.switch_registry(index).await;
+            }
+            _ => {}
+        }
+    }
+
+    fn take_tree_state(&mut self) -> TreeState {
+        TreeState {
+            items: std::mem::take(&mut self.items),
+            item_types: std::mem::take(&mut self.item_types),
+            full_image_names: std::mem::take(&mut self.full_image_names),
+            selected_index: self.selected_index,
+            scroll_offset: self.scroll_offset,
+            marks: std::mem::take(&mut self.marks),
+            severities: std::mem::take(&mut self.severities),
+            artifacts: std::mem::take(&mut self.artifacts),
+            compare_selection: std::mem::take(&mut self.compare_selection),
+            row_registry: std::mem::take(&mut self.row_registry),
+        }
+    }
+
+    fn restore_tree_state(&mut self, state: TreeState) {
+        self.items = state.items;
+        self.item_types = state.item_types;
+        self.full_image_names = state.full_image_names;
+        self.selected_index = state.selected_index;
+        self.scroll_offset = state.scroll_offset;
+        self.marks = state.marks;
+        self.severities = state.severities;
+        self.artifacts = state.artifacts;
+        self.compare_selection = state.compare_selection;
+        self.row_registry = state.row_registry;
+    }
+
+    /// registry 주소와 클라이언트 설정을 바꿉니다.
+    fn activate_registry(&mut self, index: usize) -> Result<(), String> {
+        let choice = &self.registries[index];
+        let settings = choice.settings.clone()?;
+        registry::configure_client(&settings).map_err(|e| e.to_string())?;
+        registry::set_registry_url(&choice.url);
+        self.active_registry = Some(index);
+        Ok(())
+    }
+
+    /// diff-registries 트리에서는 선택한 태그가 있는 registry로 요청을 보내도록 바꿉니다.
+    fn use_row_registry(&mut self) {
+        let owner = self.full_image_names.get(self.selected_index).and_then(|name| self.row_registry.get(name)).copied();
+        let Some(owner) = owner.or((!self.row_registry.is_empty()).then_some(self.current_registry)) else { return };
+        if self.active_registry != Some(owner) && owner < self.registries.len() {
+            if let Err(error) = self.activate_registry(owner) {
+                self.title = format!("{} [{}: {}]", self.title, self.registries[owner].name, error);
+            }
+        }
+    }
+
+    /// 다른 registry로 트리를 바꿉니다. 전에 본 registry면 펼친 항목과 선택 위치를 그대로 되살림
+    pub async fn switch_registry(&mut self, index: usize) {
+        if index == self.current_registry {
+            self.registry_picker = None;
+            return;
+        }
+        let result = match self.activate_registry(index) {
+            Ok(()) if self.saved_trees.contains_key(&index) => Ok(None),
+            Ok(()) => registry::fetch_repository_tags(&self.registries[index].filter).await.map(Some).map_err(|e| e.to_string()),
+            Err(e) => Err(e),
+        };
+        let repositories = match result {
+            Ok(repositories) => repositories,
+            Err(error) => {
+                // 실패하면 원래 registry로 되돌리고 선택기에 오류를 표시
+                let _ = self.activate_registry(self.current_registry);
+                if let Some(picker) = self.registry_picker.as_mut() {
+                    picker.status[index] = picker::Reachability::Unreachable(error);
+                }
+                return;
+            }
+        };
+
+        self.close_popup();
+        self.comparison = None;
+        self.compare_load = None;
+        let state = self.take_tree_state();
+        self.saved_trees.insert(self.current_registry, state);
+        self.new_marks.clear();
+        match repositories {
+            Some(repositories) => {
+                let (items, item_types, full_image_names) = tree_rows(&registry::tree_items_from_tags(&repositories));
+                self.items = items;
+                self.item_types = item_types;
+                self.full_image_names = full_image_names;
+                self.selected_index = 0;
+                self.scroll_offset = 0;
+            }
+            None => {
+                let state = self.saved_trees.remove(&index).unwrap();
+                self.restore_tree_state(state);
+            }
+        }
+        self.current_registry = index;
+        self.title = self.registry_title();
+        self.registry_picker = None;
+    }
+
+    /// 캐시에서 읽은 `cached` 트리를 띄운 상태에서 registry를 백그라운드로 다시 읽습니다.
+    pub fn refresh_in_background(&mut self, cached: BTreeMap<String, Vec<String>>, filter: registry::TreeFilter) {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        tokio::spawn(async move {
+            let result = registry::fetch_repository_tags(&filter).await.map_err(|e| e.to_string());
+            let _ = sender.send(result);
+        });
+        self.refresh = Some(TreeRefresh { registry: self.current_registry, cached, receiver });
+        self.title = format!("{} [cached, refreshing...]", self.registry_title());
+    }
+
+    /// 재검증이 끝났으면 바뀐 부분을 트리에 반영합니다. 선택한 항목은 이름으로 유지
+    pub fn poll_refresh(&mut self) {
+        let Some(refresh) = self.refresh.as_mut() else { return };
+        let result = match refresh.receiver.try_recv() {
+            Ok(result) => result,
+            Err(tokio::sync::oneshot::error::TryRecvError::Empty) => return,
+            Err(tokio::sync::oneshot::error::TryRecvError::Closed) => Err("refresh task stopped".to_string()),
+        };
+        let refresh = self.refresh.take().unwrap();
+        // 그 사이에 다른 registry로 바꿨으면 결과를 버림
+        if refresh.registry != self.current_registry {
+            return;
+        }
+        let repositories = match result {
+            Ok(repositories) => repositories,
+            Err(error) => {
+                self.title = format!("{} [cached, refresh failed: {}]", self.registry_title(), error);
+                return;
+            }
+        };
+        self.title = self.registry_title();
+        if repositories != refresh.cached {
+            self.replace_tree(&repositories);
+        }
+    }
+
+    /// 트리를 새 저장소/태그 목록으로 바꿉니다. 선택한 항목은 이름으로 유지
+    fn replace_tree(&mut self, repositories: &BTreeMap<String, Vec<String>>) {
+        let selected = self.full_image_names.get(self.selected_index).cloned();
+        let (items, item_types, full_image_names) = tree_rows(&registry::tree_items_from_tags(repositories));
+        self.items = items;
+        self.item_types = item_types;
+        self.full_image_names = full_image_names;
+        self.selected_index = selected
+            .and_then(|name| self.full_image_names.iter().position(|n| *n == name))
+            .unwrap_or(self.selected_index.min(self.items.len().saturating_sub(1)));
+        self.scroll_offset = self.scroll_offset.min(self.selected_index);
+    }
+
+    /// --refresh 간격이 지났으면 백그라운드 폴링을 시작하고, 끝난 폴링은 이전 결과와 비교해 트리에 반영합니다.
+    pub fn poll_auto_refresh(&mut self) {
+        let current_registry = self.current_registry;
+        let filter = self.registries.get(current_registry).map(|c| c.filter.clone()).unwrap_or_default();
+        let Some(auto) = self.auto_refresh.as_mut() else { return };
+        // registry를 바꾸면 그 registry의 첫 폴링부터 다시 시작
+        if auto.registry != current_registry {
+            auto.registry = current_registry;
+            auto.state = None;
+            auto.pending = None;
+            auto.next_poll = Instant::now();
+        }
+        let Some(receiver) = auto.pending.as_mut() else {
+            if Instant::now() >= auto.next_poll {
+                let (sender, receiver) = tokio::sync::oneshot::channel();
+                tokio::spawn(async move {
+                    let result = watch::poll(&filter).await.map_err(|e| e.to_string());
+                    let _ = sender.send(result);
+                });
+                auto.pending = Some(receiver);
+            }
+            return;
+        };
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(tokio::sync::oneshot::error::TryRecvError::Empty) => return,
+            Err(tokio::sync::oneshot::error::TryRecvError::Closed) => Err("refresh task stopped".to_string()),
+        };
+        auto.pending = None;
+        auto.next_poll = Instant::now() + auto.interval;
+        let state = match result {
+            Ok(state) => state,
+            Err(error) => {
+                self.title = format!("{} [auto-refresh failed: {}]", self.registry_title(), error);
+                return;
+            }
+        };
+        let previous = auto.state.replace(state.clone());
+        if self.title.contains("[auto-refresh failed") {
+            self.title = self.registry_title();
+        }
+        // 이전 폴링의 배지는 이번 결과로 대체
+        self.new_marks.clear();
+        let Some(previous) = previous else { return };
+        let events = watch::changes(&previous, &state);
+        if events.is_empty() {
+            return;
+        }
+        let tags_changed = previous.len() != state.len()
+            || previous.iter().zip(&state).any(|((r1, t1), (r2, t2))| r1 != r2 || !t1.keys().eq(t2.keys()));
+        if tags_changed {
+            let repositories: BTreeMap<String, Vec<String>> = state.iter().map(|(r, tags)| (r.clone(), tags.keys().cloned().collect())).collect();
+            self.replace_tree(&repositories);
+        }
+        self.new_marks.extend(events.iter().filter(|e| e.is_new()).map(watch::tree_name));
+    }
+
+    /// 검색어 입력이나 registry 선택기처럼 글자 키를 직접 받는 중인지 (이때 q는 종료가 아님)
+    pub fn owns_keyboard(&self) -> bool {
+        let searching = self.popup_open && self.popup_tab == PopupTab::Packages && self.packages.as_ref().is_some_and(|v| v.searching);
+        searching || self.registry_picker.is_some()
+    }
+
+    pub fn has_background_work(&self) -> bool {
+        self.refresh.is_some() || self.auto_refresh.is_some() || self.files_load.is_some() || self.compare_load.is_some()
+            || self.packages_load.is_some() || self.vulns_load.is_some()
+    }
+
+    /// 트리 제목: 현재 registry 이름과 주소
+    /// 오프라인이면 캐시가 마지막으로 확인된 시각도 표시
+    pub fn registry_title(&self) -> String {
+        let title = match self.registries.get(self.current_registry) {
+            Some(choice) if choice.name != choice.url => format!("Docker Images Tree - {} ({})", choice.name, choice.url),
+            Some(choice) => format!("Docker Images Tree - {}", choice.url),
+            None => "Docker Images Tree".to_string(),
+        };
+        if !cache::is_offline() {
+            return title;
+        }
+        match cache::snapshot_time(&registry::get_registry_url()) {
+            Some(time) => format!("{} [OFFLINE - stale snapshot from {}]", title, crate::time::format_unix(time)),
+            None => format!("{} [OFFLINE]", title),
+        }
+    }
+
     pub async fn handle_main_input(&mut self, key: KeyEvent, max_visible_items: usize) {
+        if !matches!(key.code, KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown) {
+            self.use_row_registry();
+        }
         match key.code {
             KeyCode::Char('q') => {}
             KeyCode::Down => self.next(max_visible_items),
@@ -241,12 +1432,23 @@ impl App {
                 if self.popup_open {
                     self.close_popup();
                 } else {
+                    // 열어 본 항목은 "new" 배지를 확인한 것으로 봄
+                    if let Some(name) = self.full_image_names.get(self.selected_index) {
+                        self.new_marks.remove(name);
+                    }
                     self.open_popup().await;
                 }
             }
             KeyCode::Esc => {
                 self.close_popup();
             }
+            KeyCode::Char('m') => self.toggle_compare_mark(),
+            KeyCode::Char('c') => self.compare_files(),
+            KeyCode::Char('C') => self.compare_metadata(),
+            KeyCode::Char('a') => self.toggle_referrers().await,
+            KeyCode::Char('v') => self.verify_selected().await,
+            KeyCode::Char('S') => self.scan_selected_vulns().await,
+            KeyCode::Char('R') => self.open_registry_picker().await,
             _ => {}
         }
     }
@@ -284,10 +1486,10 @@ pub fn render_ui<B: Backend>(f: &mut Frame<B>, app: &App) {
 
     let usage_text = vec![
         Spans::from("Usage:"),
-        Spans::from("  - Use arrow keys ↑/↓ to navigate"),
-        Spans::from("  - Press Enter to open details"),
-        Spans::from("  - Press Esc to close details"),
-        Spans::from("  - Press q or Ctrl+C to quit"),
+        Spans::from("  - ↑/↓ navigate, Enter/Esc open/close details"),
+        Spans::from("  - m: mark tags, c/C: compare files/config"),
+        Spans::from("  - a: signatures/SBOMs, v/S: verify/vuln scan"),
+        Spans::from("  - R: switch registry, q/Ctrl+C: quit"),
     ];
 
     let version = env!("CARGO_PKG_VERSION");
@@ -346,6 +1548,7 @@ pub fn render_ui<B: Backend>(f: &mut Frame<B>, app: &App) {
                 1 => Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD), // 1뎁스
                 2 => Style::default().fg(Color::Green),                               // 2뎁스
                 3 => Style::default().fg(Color::Gray),                                // 3뎁스 (태그)
+                4 => Style::default().fg(Color::Magenta),                             // 4뎁스 (referrer)
                 _ => Style::default(),
             };
 
@@ -354,25 +1557,75 @@ pub fn render_ui<B: Backend>(f: &mut Frame<B>, app: &App) {
             } else {
                 Span::styled(item.clone(), style)
             };
-            ListItem::new(vec![Spans::from(styled_item)])
+            let mut spans = vec![styled_item];
+            if let Some(mark) = app.marks.get(&app.full_image_names[i]) {
+                spans.push(Span::styled(format!(" [{}]", mark.label), Style::default().fg(mark.color).add_modifier(Modifier::BOLD)));
+            }
+            if app.new_marks.contains(&app.full_image_names[i]) {
+                spans.push(Span::styled(" [new]", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)));
+            }
+            if let Some(severity) = app.severities.get(&app.full_image_names[i]) {
+                spans.push(Span::styled(format!(" [{}]", severity.label), Style::default().fg(severity.color).add_modifier(Modifier::BOLD)));
+            }
+            if let Some(index) = app.compare_selection.iter().position(|n| *n == app.full_image_names[i]) {
+                let label = if index == 0 { " <A>" } else { " <B>" };
+                spans.push(Span::styled(label, Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)));
+            }
+            ListItem::new(vec![Spans::from(spans)])
         })
         .collect();
 
     let visible_items = &items[app.scroll_offset..(app.scroll_offset + max_visible_items).min(items.len())];
 
     let list = List::new(visible_items.to_vec())
-        .block(Block::default().borders(Borders::ALL).title("Docker Images Tree"))
+        .block(Block::default().borders(Borders::ALL).title(app.title.clone()))
         .highlight_style(Style::default().bg(Color::Yellow).add_modifier(Modifier::BOLD));
 
     // 하단 레이아웃에 트리 렌더링
     f.render_widget(list, vertical_chunks[1]);
 
     // 팝업이 열려 있으면 팝업 표시
-    if app.popup_open {
-        let popup = Paragraph::new(app.popup_content.clone())
+    if let Some(picker) = &app.registry_picker {
+        picker::render_picker(f, picker, &app.registries, app.current_registry);
+    } else if let Some(view) = &app.comparison {
+        render_compare_popup(f, view);
+    } else if app.popup_open && app.popup_tab == PopupTab::Files {
+        render_files_popup(f, app);
+    } else if app.popup_open {
+        let content = match app.popup_tab {
+            PopupTab::Efficiency => match (&app.efficiency, &app.files_error) {
+                (Some(report), _) => efficiency::report_lines(report).join("\n"),
+                (None, Some(error)) => error.clone(),
+                (None, None) => "Loading layers...".to_string(),
+            },
+            PopupTab::Packages => match &app.packages {
+                Some(view) => view.lines().join("\n"),
+                None => "Loading SBOMs...".to_string(),
+            },
+            PopupTab::Vulns => match (&app.vulns, &app.vuln_error) {
+                (Some(report), _) => vuln::report_lines(report).join("\n"),
+                (None, Some(error)) => error.clone(),
+                (None, None) => "Matching packages...".to_string(),
+            },
+            PopupTab::Attestations => match (&app.attestations, &app.attestations_error) {
+                (Some(attestations), _) => provenance::report_lines(attestations).join("\n"),
+                (None, Some(error)) => error.clone(),
+                (None, None) => "Loading attestations...".to_string(),
+            },
+            _ => app.popup_content.clone(),
+        };
+        let hint = match &app.packages {
+            Some(view) if app.popup_tab == PopupTab::Packages && view.searching => format!("(search: {}_  Enter: done)", view.filter),
+            Some(view) if app.popup_tab == PopupTab::Packages && !view.filter.is_empty() => {
+                format!("(Tab: switch, /: search \"{}\")", view.filter)
+            }
+            _ if app.popup_tab == PopupTab::Packages => "(Tab: switch, /: search)".to_string(),
+            _ => "(Tab: switch)".to_string(),
+        };
+        let popup = Paragraph::new(content)
             .block(
                 Block::default()
-                    .title("Tag Details")
+                    .title(format!("Tag Details  {}  {}", app.popup_tab.title(), hint))
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(Color::Magenta)),
             )
@@ -384,6 +1637,84 @@ pub fn render_ui<B: Backend>(f: &mut Frame<B>, app: &App) {
     }
 }
 
+fn render_files_popup<B: Backend>(f: &mut Frame<B>, app: &App) {
+    let area = centered_rect(80, 60, f.size());
+    let block = Block::default()
+        .title(format!("Tag Details  {}  (Tab: switch, [/]: layer, ←/→: fold, x: extract)", app.popup_tab.title()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta));
+    let style = Style::default().fg(Color::White).bg(Color::Black);
+    f.render_widget(PopupClear, area);
+
+    let Some(browser) = &app.file_browser else {
+        let message = app.files_error.clone().unwrap_or_else(|| "Loading layers...".to_string());
+        f.render_widget(Paragraph::new(message).block(block).style(style), area);
+        return;
+    };
+
+    let visible = (area.height as usize).saturating_sub(3);
+    let header = match &browser.status {
+        Some(status) => format!("{}  —  {}", browser.view_label(), status),
+        None => browser.view_label(),
+    };
+    let mut lines = vec![ListItem::new(Spans::from(Span::styled(
+        header,
+        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+    )))];
+    for (i, row) in browser.rows().iter().enumerate().skip(browser.scroll).take(visible) {
+        let mut row_style = if row.whiteout {
+            Style::default().fg(Color::Red)
+        } else if row.is_dir {
+            Style::default().fg(Color::Blue)
+        } else if row.entry.as_ref().is_some_and(|e| e.link_target.is_some()) {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default().fg(Color::Gray)
+        };
+        if i == browser.selected {
+            row_style = row_style.bg(Color::Yellow).add_modifier(Modifier::BOLD);
+        }
+        lines.push(ListItem::new(Spans::from(Span::styled(browser.format_row(row), row_style))));
+    }
+    f.render_widget(List::new(lines).block(block).style(style), area);
+}
+
+fn compare_list<'a>(lines: &[(String, Color)], scroll: usize, visible: usize, block: Block<'a>) -> List<'a> {
+    let items: Vec<ListItem> = lines
+        .iter()
+        .skip(scroll)
+        .take(visible)
+        .map(|(line, color)| ListItem::new(Spans::from(Span::styled(line.clone(), Style::default().fg(*color)))))
+        .collect();
+    List::new(items).block(block).style(Style::default().bg(Color::Black))
+}
+
+fn render_compare_popup<B: Backend>(f: &mut Frame<B>, view: &CompareView) {
+    let area = centered_rect(80, 60, f.size());
+    let visible = (area.height as usize).saturating_sub(2);
+    let block = Block::default()
+        .title(format!("{}  (Esc: close)", view.title))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Magenta));
+    f.render_widget(PopupClear, area);
+
+    let Some(right) = &view.right else {
+        f.render_widget(compare_list(&view.lines, view.scroll, visible, block), area);
+        return;
+    };
+    // 두 창은 같은 스크롤 위치를 공유
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+    let left_block = block.borders(Borders::TOP | Borders::BOTTOM | Borders::LEFT);
+    let right_block = Block::default()
+        .borders(Borders::TOP | Borders::BOTTOM | Borders::RIGHT | Borders::LEFT)
+        .border_style(Style::default().fg(Color::Magenta));
+    f.render_widget(compare_list(&view.lines, view.scroll, visible, left_block), panes[0]);
+    f.render_widget(compare_list(right, view.scroll, visible, right_block), panes[1]);
+}
+
 pub async fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mut app: App) -> io::Result<()> {
     // 터미널 화면 전체 초기화
     execute!(terminal.backend_mut(), Clear(ClearType::All))?;
@@ -396,14 +1727,28 @@ pub async fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mut app:
         let popup_height = (terminal.size()?.height * 60 / 100) as usize; // 60% 높이에 맞춤
         let max_visible_popup_lines = popup_height.saturating_sub(2); // 여백 고려
 
+        // 백그라운드 작업(재검증, 자동 새로 고침, 레이어/패키지 읽기, 비교) 중에는 키 입력을 기다리면서 결과도 확인
+        if app.has_background_work() && !event::poll(Duration::from_millis(200))? {
+            app.poll_refresh();
+            app.poll_auto_refresh();
+            app.poll_files();
+            app.poll_compare();
+            app.poll_packages();
+            app.poll_vulns();
+            continue;
+        }
         if let Event::Key(key) = event::read()? {
             if (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
-            || key.code == KeyCode::Char('q') {
+            || (key.code == KeyCode::Char('q') && !app.owns_keyboard()) {
                 execute!(terminal.backend_mut(), Clear(ClearType::All))?;
                 return Ok(());
             }
-            if app.popup_open {
-                app.handle_popup_input(key, max_visible_popup_lines);
+            if app.registry_picker.is_some() {
+                app.handle_picker_input(key).await;
+            } else if app.comparison.is_some() {
+                app.handle_compare_input(key, max_visible_popup_lines);
+            } else if app.popup_open {
+                app.handle_popup_input(key, max_visible_popup_lines).await;
             } else {
                 app.handle_main_input(key, max_visible_popup_lines).await;
             }
@@ -0,0 +1,187 @@
+use openssl::hash::MessageDigest;
+use openssl::pkey::{Id, PKey, Public};
+use openssl::sign::Verifier;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::error::Error as StdError;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tui::style::Color;
+
+use crate::registry::{self, Descriptor};
+
+pub const ARTIFACT_TYPE_COSIGN_SIGNATURE: &str = "application/vnd.dev.cosign.artifact.sig.v1+json";
+pub const MEDIA_TYPE_SIMPLE_SIGNING: &str = "application/vnd.dev.cosign.simplesigning.v1+json";
+pub const ANNOTATION_COSIGN_SIGNATURE: &str = "dev.cosignproject.cosign/signature";
+
+/// 공개키 하나와 표시용 이름(파일 이름)
+pub struct PublicKey {
+    pub name: String,
+    key: PKey<Public>,
+}
+
+impl PublicKey {
+    pub fn load(path: &Path) -> Result<PublicKey, Box<dyn StdError>> {
+        let pem = fs::read(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let key = PKey::public_key_from_pem(&pem).map_err(|e| format!("{}: not a PEM public key ({})", path.display(), e))?;
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        Ok(PublicKey { name, key })
+    }
+
+    /// cosign은 ECDSA/RSA 키로 payload의 SHA-256에 서명하고, ed25519는 payload 자체에 서명함
+    fn verify(&self, payload: &[u8], signature: &[u8]) -> bool {
+        let result = if self.key.id() == Id::ED25519 {
+            Verifier::new_without_digest(&self.key).and_then(|mut v| v.verify_oneshot(signature, payload))
+        } else {
+            Verifier::new(MessageDigest::sha256(), &self.key).and_then(|mut v| {
+                v.update(payload)?;
+                v.verify(signature)
+            })
+        };
+        result.unwrap_or(false)
+    }
+}
+
+/// 설정 디렉터리의 기본 키 묶음: $XDG_CONFIG_HOME/repo-tree/keys/*.pub
+pub fn default_keys_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("repo-tree").join("keys"))
+}
+
+/// 지정한 키 파일(또는 키 파일이 든 디렉터리)을 읽습니다. 아무것도 없으면 기본 키 디렉터리를 사용합니다.
+pub fn load_keys(paths: &[PathBuf]) -> Result<Vec<PublicKey>, Box<dyn StdError>> {
+    let paths: Vec<PathBuf> = if paths.is_empty() { default_keys_dir().into_iter().collect() } else { paths.to_vec() };
+    let mut keys = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut files: Vec<PathBuf> = fs::read_dir(&path)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| p.extension().is_some_and(|ext| ext == "pub" || ext == "pem"))
+                .collect();
+            files.sort();
+            for file in files {
+                keys.push(PublicKey::load(&file)?);
+            }
+        } else {
+            keys.push(PublicKey::load(&path)?);
+        }
+    }
+    if keys.is_empty() {
+        return Err("no public keys found; pass --key or put *.pub files in the keys directory".into());
+    }
+    Ok(keys)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum VerifyStatus {
+    Verified { key: String, signature: String },
+    Unverified { reason: String },
+    NoSignature,
+}
+
+impl VerifyStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            VerifyStatus::Verified { .. } => "verified",
+            VerifyStatus::Unverified { .. } => "unverified",
+            VerifyStatus::NoSignature => "no signature",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            VerifyStatus::Verified { .. } => Color::Green,
+            VerifyStatus::Unverified { .. } => Color::Red,
+            VerifyStatus::NoSignature => Color::DarkGray,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifyResult {
+    pub reference: String,
+    pub digest: String,
+    #[serde(flatten)]
+    pub status: VerifyStatus,
+}
+
+/// 서명 매니페스트 목록: referrers API(또는 대체 태그)와 cosign의 `sha256-<hex>.sig` 태그
+async fn signature_manifests(image: &str, digest: &str) -> Result<Vec<String>, Box<dyn StdError>> {
+    let mut manifests: Vec<String> = registry::fetch_referrers(image, digest)
+        .await?
+        .into_iter()
+        .filter(|d| d.artifact_type.as_deref() == Some(ARTIFACT_TYPE_COSIGN_SIGNATURE))
+        .map(|d| d.digest)
+        .collect();
+    let sig_tag = format!("{}.sig", registry::referrers_tag(digest));
+    match registry::fetch_manifest_digest(image, &sig_tag).await {
+        Ok(sig_digest) if !manifests.contains(&sig_digest) => manifests.push(sig_digest),
+        Ok(_) => {}
+        Err(e) if registry::is_not_found(e.as_ref()) => {}
+        Err(e) => return Err(e),
+    }
+    Ok(manifests)
+}
+
+/// simple signing payload가 이 다이제스트를 가리키는지 확인합니다.
+fn payload_digest(payload: &[u8]) -> Option<String> {
+    let value: Value = serde_json::from_slice(payload).ok()?;
+    value
+        .pointer("/critical/image/docker-manifest-digest")
+        .and_then(|v| v.as_str())
+        .map(|v| v.to_string())
+}
+
+/// 서명 레이어 하나를 검증합니다. 실패하면 이유를 돌려줌
+async fn verify_layer(image: &str, digest: &str, layer: &Descriptor, keys: &[PublicKey]) -> Result<String, String> {
+    let encoded = layer
+        .annotations
+        .get(ANNOTATION_COSIGN_SIGNATURE)
+        .ok_or_else(|| "signature layer has no signature annotation".to_string())?;
+    let signature = openssl::base64::decode_block(encoded.trim()).map_err(|e| format!("invalid base64 signature: {}", e))?;
+    let payload = registry::fetch_blob(image, layer, Vec::new()).await.map_err(|e| e.to_string())?;
+
+    match payload_digest(&payload) {
+        Some(signed) if signed == digest => {}
+        Some(signed) => return Err(format!("signature is for {}", signed)),
+        None => return Err("payload is not a cosign simple signing document".to_string()),
+    }
+    keys.iter()
+        .find(|key| key.verify(&payload, &signature))
+        .map(|key| key.name.clone())
+        .ok_or_else(|| "signature does not match any trusted key".to_string())
+}
+
+/// 태그 다이제스트의 cosign 서명을 모두 확인해 하나라도 신뢰하는 키로 검증되면 Verified입니다.
+/// 투명성 로그(Rekor)나 인증서(Fulcio)는 확인하지 않으므로 네트워크 밖에서도 동작합니다.
+pub async fn verify_digest(image: &str, digest: &str, keys: &[PublicKey]) -> Result<VerifyStatus, Box<dyn StdError>> {
+    let mut reasons = BTreeSet::new();
+    let mut found = false;
+    for manifest_digest in signature_manifests(image, digest).await? {
+        let manifest = registry::fetch_manifest_raw(image, &manifest_digest).await?.parse()?;
+        for layer in manifest.layers.iter().filter(|l| l.media_type == MEDIA_TYPE_SIMPLE_SIGNING) {
+            found = true;
+            match verify_layer(image, digest, layer, keys).await {
+                Ok(key) => return Ok(VerifyStatus::Verified { key, signature: manifest_digest }),
+                Err(reason) => {
+                    reasons.insert(reason);
+                }
+            }
+        }
+    }
+    if !found {
+        return Ok(VerifyStatus::NoSignature);
+    }
+    Ok(VerifyStatus::Unverified { reason: reasons.into_iter().collect::<Vec<_>>().join("; ") })
+}
+
+pub async fn verify_reference(reference: &str, keys: &[PublicKey]) -> Result<VerifyResult, Box<dyn StdError>> {
+    let (image, tag) = registry::parse_reference(reference)?;
+    let digest = registry::fetch_manifest_digest(&image, &tag).await?;
+    let status = verify_digest(&image, &digest, keys).await?;
+    Ok(VerifyResult { reference: format!("{}:{}", image, tag), digest, status })
+}
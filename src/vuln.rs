@@ -0,0 +1,831 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::path::{Path, PathBuf};
+use tabled::Tabled;
+use tui::style::Color;
+
+use crate::inventory;
+use crate::registry::Platform;
+use crate::sbom::Package;
+use crate::zip;
+
+/// zip 덤프 안의 advisory 하나에 허용하는 최대 크기
+const MAX_ADVISORY_SIZE: u64 = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Unknown,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn parse(value: &str) -> Option<Severity> {
+        match value.to_ascii_lowercase().as_str() {
+            "unknown" => Some(Severity::Unknown),
+            "low" => Some(Severity::Low),
+            "medium" | "moderate" => Some(Severity::Medium),
+            "high" => Some(Severity::High),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+
+    pub fn from_score(score: f64) -> Severity {
+        match score {
+            s if s >= 9.0 => Severity::Critical,
+            s if s >= 7.0 => Severity::High,
+            s if s >= 4.0 => Severity::Medium,
+            s if s > 0.0 => Severity::Low,
+            _ => Severity::Unknown,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Unknown => "UNKNOWN",
+            Severity::Low => "LOW",
+            Severity::Medium => "MEDIUM",
+            Severity::High => "HIGH",
+            Severity::Critical => "CRITICAL",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            Severity::Unknown => Color::Gray,
+            Severity::Low => Color::Blue,
+            Severity::Medium => Color::Yellow,
+            Severity::High => Color::LightRed,
+            Severity::Critical => Color::Red,
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// CVSS v3 벡터 문자열로 기본 점수를 계산합니다.
+pub fn cvss3_base_score(vector: &str) -> Option<f64> {
+    let metrics: HashMap<&str, &str> = vector.split('/').skip(1).filter_map(|m| m.split_once(':')).collect();
+    let changed = *metrics.get("S")? == "C";
+    let av = match *metrics.get("AV")? { "N" => 0.85, "A" => 0.62, "L" => 0.55, "P" => 0.2, _ => return None };
+    let ac = match *metrics.get("AC")? { "L" => 0.77, "H" => 0.44, _ => return None };
+    let pr = match (*metrics.get("PR")?, changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+    let ui = match *metrics.get("UI")? { "N" => 0.85, "R" => 0.62, _ => return None };
+    let cia = |key: &str| match metrics.get(key) { Some(&"H") => Some(0.56), Some(&"L") => Some(0.22), Some(&"N") => Some(0.0), _ => None };
+    let iss = 1.0 - (1.0 - cia("C")?) * (1.0 - cia("I")?) * (1.0 - cia("A")?);
+    let impact = if changed { 7.52 * (iss - 0.029) - 3.25 * (iss - 0.02f64).powi(15) } else { 6.42 * iss };
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+    let exploitability = 8.22 * av * ac * pr * ui;
+    let total = if changed { 1.08 * (impact + exploitability) } else { impact + exploitability };
+    // 명세의 Roundup: 소수점 첫째 자리로 올림 (부동소수점 오차 보정 포함)
+    let scaled = (total.min(10.0) * 100_000.0).round() as u64;
+    Some(if scaled.is_multiple_of(10_000) { scaled as f64 / 100_000.0 } else { (scaled / 10_000 + 1) as f64 / 10.0 })
+}
+
+/// dpkg의 문자 순서: '~'가 가장 앞, 글자, 그 밖의 기호 순
+fn dpkg_order(c: Option<u8>) -> i32 {
+    match c {
+        None => 0,
+        Some(c) if c.is_ascii_digit() => 0,
+        Some(c) if c.is_ascii_alphabetic() => c as i32,
+        Some(b'~') => -1,
+        Some(c) => c as i32 + 256,
+    }
+}
+
+/// dpkg의 verrevcmp 알고리즘
+fn dpkg_compare_part(a: &str, b: &str) -> Ordering {
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        while (i < a.len() && !a[i].is_ascii_digit()) || (j < b.len() && !b[j].is_ascii_digit()) {
+            let (ac, bc) = (dpkg_order(a.get(i).copied()), dpkg_order(b.get(j).copied()));
+            if ac != bc {
+                return ac.cmp(&bc);
+            }
+            i += 1;
+            j += 1;
+        }
+        while i < a.len() && a[i] == b'0' {
+            i += 1;
+        }
+        while j < b.len() && b[j] == b'0' {
+            j += 1;
+        }
+        let mut first_diff = Ordering::Equal;
+        while i < a.len() && a[i].is_ascii_digit() && j < b.len() && b[j].is_ascii_digit() {
+            if first_diff == Ordering::Equal {
+                first_diff = a[i].cmp(&b[j]);
+            }
+            i += 1;
+            j += 1;
+        }
+        if i < a.len() && a[i].is_ascii_digit() {
+            return Ordering::Greater;
+        }
+        if j < b.len() && b[j].is_ascii_digit() {
+            return Ordering::Less;
+        }
+        if first_diff != Ordering::Equal {
+            return first_diff;
+        }
+    }
+    Ordering::Equal
+}
+
+/// "epoch:upstream-revision" 형식의 데비안 버전 비교
+fn dpkg_compare(a: &str, b: &str) -> Ordering {
+    fn split(version: &str) -> (u64, &str, &str) {
+        let (epoch, rest) = match version.split_once(':') {
+            Some((epoch, rest)) => (epoch.parse().unwrap_or(0), rest),
+            None => (0, version),
+        };
+        let (upstream, revision) = rest.rsplit_once('-').unwrap_or((rest, ""));
+        (epoch, upstream, revision)
+    }
+    let (epoch_a, upstream_a, revision_a) = split(a);
+    let (epoch_b, upstream_b, revision_b) = split(b);
+    epoch_a
+        .cmp(&epoch_b)
+        .then_with(|| dpkg_compare_part(upstream_a, upstream_b))
+        .then_with(|| dpkg_compare_part(revision_a, revision_b))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Segment {
+    Number(String),
+    Text(String),
+}
+
+/// 이 이름의 글자 조각은 정식 버전보다 앞선다 (1.0rc1 < 1.0)
+fn is_prerelease(text: &str) -> bool {
+    matches!(text, "a" | "alpha" | "b" | "beta" | "c" | "rc" | "cr" | "pre" | "preview" | "dev" | "snapshot" | "m")
+}
+
+fn segments(version: &str) -> Vec<Segment> {
+    let version = version.strip_prefix('v').filter(|v| v.starts_with(|c: char| c.is_ascii_digit())).unwrap_or(version);
+    let mut out = Vec::new();
+    let mut current = String::new();
+    for c in version.chars() {
+        let same_kind = current.chars().next().is_some_and(|p| p.is_ascii_digit() == c.is_ascii_digit());
+        if !current.is_empty() && (!c.is_ascii_alphanumeric() || !same_kind) {
+            out.push(segment(std::mem::take(&mut current)));
+        }
+        if c.is_ascii_alphanumeric() {
+            current.push(c.to_ascii_lowercase());
+        }
+    }
+    if !current.is_empty() {
+        out.push(segment(current));
+    }
+    out
+}
+
+fn segment(text: String) -> Segment {
+    if text.starts_with(|c: char| c.is_ascii_digit()) {
+        Segment::Number(text.trim_start_matches('0').to_string())
+    } else {
+        Segment::Text(text)
+    }
+}
+
+/// SemVer, PEP 440, Maven, apk 등을 대략적으로 아우르는 비교: 숫자는 숫자로, 글자는 사전순으로 비교
+fn generic_compare(a: &str, b: &str) -> Ordering {
+    let (a, b) = (segments(a), segments(b));
+    for i in 0..a.len().max(b.len()) {
+        let ordering = match (a.get(i), b.get(i)) {
+            (Some(Segment::Number(x)), Some(Segment::Number(y))) => x.len().cmp(&y.len()).then_with(|| x.cmp(y)),
+            (Some(Segment::Text(x)), Some(Segment::Text(y))) => match (is_prerelease(x), is_prerelease(y)) {
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                _ => x.cmp(y),
+            },
+            (Some(Segment::Text(x)), _) => if is_prerelease(x) { Ordering::Less } else { Ordering::Greater },
+            (_, Some(Segment::Text(y))) => if is_prerelease(y) { Ordering::Greater } else { Ordering::Less },
+            (Some(Segment::Number(_)), None) => Ordering::Greater,
+            (None, Some(Segment::Number(_))) => Ordering::Less,
+            (None, None) => Ordering::Equal,
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+pub fn compare_versions(ecosystem: &str, a: &str, b: &str) -> Ordering {
+    match ecosystem {
+        "Debian" | "Ubuntu" => dpkg_compare(a, b),
+        _ => generic_compare(a, b),
+    }
+}
+
+/// 생태계별 이름 정규화 (PyPI는 대소문자와 -, _, . 를 구분하지 않음)
+fn normalize_name(ecosystem: &str, name: &str) -> String {
+    match ecosystem {
+        "PyPI" => name.to_lowercase().replace(['_', '.'], "-"),
+        _ => name.to_string(),
+    }
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%').then(|| text.get(i + 1..i + 3)).flatten().and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// OSV에서 패키지를 찾는 데 쓰는 purl의 부분
+#[derive(Debug, PartialEq, Eq)]
+pub struct OsvCoordinates {
+    pub ecosystem: String,
+    pub name: String,
+    pub version: String,
+    /// 배포판 릴리스 ("distro=debian-12"의 "12"). 모르면 None
+    pub release: Option<String>,
+}
+
+/// purl을 OSV 생태계, 패키지 이름, 버전과 배포판 릴리스로 바꿉니다.
+pub fn osv_coordinates(purl: &str) -> Option<OsvCoordinates> {
+    let rest = purl.strip_prefix("pkg:")?;
+    let rest = rest.split('#').next()?;
+    let (rest, qualifiers) = rest.split_once('?').unwrap_or((rest, ""));
+    let (path, version) = rest.rsplit_once('@')?;
+    let (kind, path) = path.split_once('/')?;
+    let (namespace, name) = match path.rsplit_once('/') {
+        Some((namespace, name)) => (percent_decode(namespace), percent_decode(name)),
+        None => (String::new(), percent_decode(path)),
+    };
+    let joined = |separator: &str| if namespace.is_empty() { name.clone() } else { format!("{}{}{}", namespace, separator, name) };
+    let (ecosystem, name) = match kind {
+        "deb" if namespace == "ubuntu" => ("Ubuntu", name.clone()),
+        "deb" => ("Debian", name.clone()),
+        "apk" => ("Alpine", name.clone()),
+        "pypi" => ("PyPI", name.clone()),
+        "maven" => ("Maven", joined(":")),
+        "npm" => ("npm", joined("/")),
+        "golang" => ("Go", joined("/")),
+        "cargo" => ("crates.io", name.clone()),
+        "gem" => ("RubyGems", name.clone()),
+        "nuget" => ("NuGet", name.clone()),
+        "composer" => ("Packagist", joined("/")),
+        "hex" => ("Hex", name.clone()),
+        "pub" => ("Pub", name.clone()),
+        _ => return None,
+    };
+    // "distro=debian-12", "distro=alpine-3.18.4"처럼 배포판 ID 뒤 마지막 - 다음이 릴리스
+    let release = qualifiers
+        .split('&')
+        .find_map(|q| q.strip_prefix("distro="))
+        .map(percent_decode)
+        .and_then(|distro| distro.rsplit_once('-').map(|(_, release)| release.to_string()))
+        .filter(|release| !release.is_empty());
+    Some(OsvCoordinates {
+        ecosystem: ecosystem.to_string(),
+        name: normalize_name(ecosystem, &name),
+        version: percent_decode(version),
+        release,
+    })
+}
+
+/// advisory의 생태계 릴리스("12", "v3.18", "22.04:LTS")가 이미지의 릴리스("12", "3.18.4", "22.04")에 해당하는지
+fn release_matches(advisory_release: &str, image_release: &str) -> bool {
+    advisory_release.split(':').map(|part| part.trim_start_matches('v')).any(|part| {
+        !part.is_empty() && (image_release == part || image_release.strip_prefix(part).is_some_and(|rest| rest.starts_with('.')))
+    })
+}
+
+// OSV 스키마 중 매칭에 필요한 부분만 읽음
+#[derive(Debug, Deserialize)]
+struct OsvAdvisory {
+    id: String,
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    details: String,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    withdrawn: Option<String>,
+    #[serde(default)]
+    severity: Vec<OsvSeverity>,
+    #[serde(default)]
+    affected: Vec<OsvAffected>,
+    #[serde(default)]
+    database_specific: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvSeverity {
+    #[serde(rename = "type")]
+    kind: String,
+    score: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvAffected {
+    package: Option<OsvPackage>,
+    #[serde(default)]
+    ranges: Vec<OsvRange>,
+    #[serde(default)]
+    versions: Vec<String>,
+    #[serde(default)]
+    database_specific: Option<Value>,
+    #[serde(default)]
+    ecosystem_specific: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvPackage {
+    ecosystem: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvRange {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    events: Vec<HashMap<String, String>>,
+}
+
+#[derive(Debug, Clone)]
+enum Event {
+    Introduced(String),
+    Fixed(String),
+    LastAffected(String),
+}
+
+impl Event {
+    fn version(&self) -> &str {
+        match self {
+            Event::Introduced(v) | Event::Fixed(v) | Event::LastAffected(v) => v,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct AffectedPackage {
+    ecosystem: String,
+    /// "Debian:12"의 "12"처럼 생태계 이름 뒤에 붙은 배포판 릴리스
+    release: Option<String>,
+    name: String,
+    /// 범위마다 버전 순으로 정렬한 이벤트
+    ranges: Vec<Vec<Event>>,
+    versions: Vec<String>,
+    severity: Option<Severity>,
+}
+
+impl AffectedPackage {
+    /// 릴리스를 모르는 쪽이 있으면 모든 릴리스에 해당한다고 봄
+    fn applies_to_release(&self, image_release: Option<&str>) -> bool {
+        match (&self.release, image_release) {
+            (Some(release), Some(image_release)) => release_matches(release, image_release),
+            _ => true,
+        }
+    }
+
+    /// 영향받는 버전이면 그 범위의 수정 버전 목록을 돌려줌
+    fn affects(&self, version: &str) -> Option<Vec<String>> {
+        let mut hit = self.versions.iter().any(|v| v == version);
+        let mut fixed = Vec::new();
+        // 수정된 뒤 다시 영향을 받는 범위가 있을 수 있어 이벤트를 끝까지 확인
+        for events in &self.ranges {
+            let mut affected = false;
+            for event in events {
+                match event {
+                    Event::Introduced(v) => affected = affected || v == "0" || self.compare(version, v) != Ordering::Less,
+                    Event::Fixed(v) => affected = affected && self.compare(version, v) == Ordering::Less,
+                    Event::LastAffected(v) => affected = affected && self.compare(version, v) != Ordering::Greater,
+                }
+            }
+            if affected {
+                hit = true;
+                // 현재 버전 바로 다음의 수정 버전
+                fixed.extend(events.iter().find_map(|e| match e {
+                    Event::Fixed(v) if self.compare(v, version) == Ordering::Greater => Some(v.clone()),
+                    _ => None,
+                }));
+            }
+        }
+        hit.then_some(fixed)
+    }
+
+    fn compare(&self, a: &str, b: &str) -> Ordering {
+        compare_versions(&self.ecosystem, a, b)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Advisory {
+    id: String,
+    summary: String,
+    aliases: Vec<String>,
+    severity: Severity,
+    score: Option<f64>,
+    affected: Vec<AffectedPackage>,
+}
+
+fn severity_text(value: Option<&Value>) -> Option<Severity> {
+    value?.get("severity")?.as_str().and_then(Severity::parse)
+}
+
+impl Advisory {
+    fn from_osv(osv: OsvAdvisory) -> Advisory {
+        let score = osv.severity.iter().filter(|s| s.kind == "CVSS_V3").find_map(|s| cvss3_base_score(&s.score));
+        let severity = score
+            .map(Severity::from_score)
+            .or_else(|| severity_text(osv.database_specific.as_ref()))
+            .unwrap_or(Severity::Unknown);
+        let affected = osv
+            .affected
+            .into_iter()
+            .filter_map(|a| {
+                let package = a.package?;
+                // "Debian:12" 같은 배포판 릴리스는 따로 두고 생태계 이름으로 색인
+                let (ecosystem, release) = match package.ecosystem.split_once(':') {
+                    Some((ecosystem, release)) => (ecosystem.to_string(), Some(release.to_string())),
+                    None => (package.ecosystem.clone(), None),
+                };
+                let ranges = a
+                    .ranges
+                    .iter()
+                    .filter(|r| r.kind == "ECOSYSTEM" || r.kind == "SEMVER")
+                    .map(|r| {
+                        let mut events: Vec<Event> = r
+                            .events
+                            .iter()
+                            .flat_map(|e| e.iter())
+                            .filter_map(|(kind, version)| match kind.as_str() {
+                                "introduced" => Some(Event::Introduced(version.clone())),
+                                "fixed" => Some(Event::Fixed(version.clone())),
+                                "last_affected" => Some(Event::LastAffected(version.clone())),
+                                _ => None,
+                            })
+                            .collect();
+                        events.sort_by(|x, y| match (x.version(), y.version()) {
+                            ("0", "0") => Ordering::Equal,
+                            ("0", _) => Ordering::Less,
+                            (_, "0") => Ordering::Greater,
+                            (a, b) => compare_versions(&ecosystem, a, b),
+                        });
+                        events
+                    })
+                    .collect();
+                let severity = severity_text(a.ecosystem_specific.as_ref()).or_else(|| severity_text(a.database_specific.as_ref()));
+                Some(AffectedPackage {
+                    name: normalize_name(&ecosystem, &package.name),
+                    ecosystem,
+                    release,
+                    ranges,
+                    versions: a.versions,
+                    severity,
+                })
+            })
+            .collect();
+        let summary = if osv.summary.is_empty() { osv.details.lines().next().unwrap_or("").to_string() } else { osv.summary };
+        Advisory { id: osv.id, summary, aliases: osv.aliases, severity, score, affected }
+    }
+}
+
+/// 로컬 디렉터리에서 읽은 OSV advisory 모음
+pub struct VulnDb {
+    pub path: PathBuf,
+    advisories: Vec<Advisory>,
+    /// (생태계, 이름) → advisories 위치
+    index: HashMap<(String, String), Vec<usize>>,
+}
+
+/// 기본 DB 위치: $XDG_DATA_HOME/repo-tree/osv (없으면 ~/.local/share/repo-tree/osv)
+pub fn default_db_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share")))?;
+    Some(base.join("repo-tree").join("osv"))
+}
+
+impl VulnDb {
+    /// 디렉터리 아래의 OSV JSON 파일과 osv.dev 형식의 zip 덤프를 모두 읽습니다.
+    pub fn load(path: &Path) -> Result<VulnDb, Box<dyn StdError>> {
+        let mut db = VulnDb { path: path.to_path_buf(), advisories: Vec::new(), index: HashMap::new() };
+        let mut pending = vec![path.to_path_buf()];
+        while let Some(path) = pending.pop() {
+            if path.is_dir() {
+                for entry in std::fs::read_dir(&path).map_err(|e| format!("could not read {}: {}", path.display(), e))? {
+                    pending.push(entry?.path());
+                }
+                continue;
+            }
+            match path.extension().and_then(|e| e.to_str()) {
+                Some("json") => db.add_json(&std::fs::read(&path)?),
+                Some("zip") => {
+                    let data = std::fs::read(&path)?;
+                    let entries = zip::entries(&data).ok_or_else(|| format!("{} is not a valid zip file", path.display()))?;
+                    for entry in entries.iter().filter(|e| e.name.ends_with(".json")) {
+                        if let Some(bytes) = zip::read(&data, entry, MAX_ADVISORY_SIZE) {
+                            db.add_json(&bytes);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if db.advisories.is_empty() {
+            return Err(format!("no OSV advisories found in {}", db.path.display()).into());
+        }
+        Ok(db)
+    }
+
+    /// 기본 위치의 DB를 읽습니다.
+    pub fn load_default() -> Result<VulnDb, Box<dyn StdError>> {
+        let path = default_db_dir().ok_or("could not determine the vulnerability database directory")?;
+        VulnDb::load(&path)
+    }
+
+    pub fn advisory_count(&self) -> usize {
+        self.advisories.len()
+    }
+
+    /// 파일 하나에 advisory 하나 또는 배열. 형식이 맞지 않는 파일은 건너뜀
+    fn add_json(&mut self, bytes: &[u8]) {
+        let advisories: Vec<OsvAdvisory> = match serde_json::from_slice::<Value>(bytes) {
+            Ok(Value::Array(items)) => items.into_iter().filter_map(|v| serde_json::from_value(v).ok()).collect(),
+            Ok(value) => serde_json::from_value(value).ok().into_iter().collect(),
+            Err(_) => return,
+        };
+        for osv in advisories.into_iter().filter(|a| a.withdrawn.is_none()) {
+            let advisory = Advisory::from_osv(osv);
+            let position = self.advisories.len();
+            for affected in &advisory.affected {
+                let entry = self.index.entry((affected.ecosystem.clone(), affected.name.clone())).or_default();
+                if entry.last() != Some(&position) {
+                    entry.push(position);
+                }
+            }
+            self.advisories.push(advisory);
+        }
+    }
+
+    /// 패키지 하나에 해당하는 취약점
+    pub fn matches(&self, package: &Package) -> Vec<Finding> {
+        let Some(OsvCoordinates { ecosystem, name, version, release }) = osv_coordinates(&package.purl) else { return Vec::new() };
+        let Some(positions) = self.index.get(&(ecosystem.clone(), name.clone())) else { return Vec::new() };
+        let mut findings = Vec::new();
+        for advisory in positions.iter().map(|&i| &self.advisories[i]) {
+            let affected = advisory.affected.iter().filter(|a| a.ecosystem == ecosystem && a.name == name && a.applies_to_release(release.as_deref()));
+            for affected in affected {
+                let Some(mut fixed) = affected.affects(&version) else { continue };
+                fixed.sort_by(|a, b| compare_versions(&ecosystem, a, b));
+                fixed.dedup();
+                findings.push(Finding {
+                    severity: if advisory.severity == Severity::Unknown { affected.severity.unwrap_or(Severity::Unknown) } else { advisory.severity },
+                    score: advisory.score,
+                    id: advisory.id.clone(),
+                    aliases: advisory.aliases.clone(),
+                    package: package.name.clone(),
+                    version: package.version.clone(),
+                    fixed,
+                    summary: advisory.summary.clone(),
+                });
+                break;
+            }
+        }
+        findings
+    }
+}
+
+fn display_fixed(fixed: &[String]) -> String {
+    if fixed.is_empty() { "-".to_string() } else { fixed.join(", ") }
+}
+
+fn display_summary(summary: &str) -> String {
+    if summary.chars().count() > 60 { format!("{}…", summary.chars().take(59).collect::<String>()) } else { summary.to_string() }
+}
+
+#[derive(Debug, Clone, Serialize, Tabled)]
+pub struct Finding {
+    #[tabled(rename = "Severity")]
+    pub severity: Severity,
+    #[tabled(skip)]
+    pub score: Option<f64>,
+    #[tabled(rename = "ID")]
+    pub id: String,
+    #[tabled(skip)]
+    pub aliases: Vec<String>,
+    #[tabled(rename = "Package")]
+    pub package: String,
+    #[tabled(rename = "Version")]
+    pub version: String,
+    #[tabled(rename = "Fixed In", display_with = "display_fixed")]
+    pub fixed: Vec<String>,
+    #[tabled(rename = "Summary", display_with = "display_summary")]
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VulnReport {
+    pub image: String,
+    pub sources: Vec<String>,
+    pub packages: usize,
+    pub findings: Vec<Finding>,
+}
+
+impl VulnReport {
+    pub fn count(&self, severity: Severity) -> usize {
+        self.findings.iter().filter(|f| f.severity == severity).count()
+    }
+
+    pub fn highest(&self) -> Option<Severity> {
+        self.findings.iter().map(|f| f.severity).max()
+    }
+
+    pub fn summary(&self) -> String {
+        let counts: Vec<String> = [Severity::Critical, Severity::High, Severity::Medium, Severity::Low, Severity::Unknown]
+            .iter()
+            .filter(|s| self.count(**s) > 0)
+            .map(|s| format!("{} {}", self.count(*s), s.label().to_lowercase()))
+            .collect();
+        let detail = if counts.is_empty() { String::new() } else { format!(" ({})", counts.join(", ")) };
+        format!(
+            "{}: {} vulnerabilities{} in {} packages from {}",
+            self.image,
+            self.findings.len(),
+            detail,
+            self.packages,
+            self.sources.join(", ")
+        )
+    }
+
+    /// 트리 배지: 가장 높은 심각도와 전체 건수
+    pub fn badge(&self) -> (String, Color) {
+        match self.highest() {
+            Some(severity) => (format!("{} {}", severity.label(), self.findings.len()), severity.color()),
+            None => ("no vulns".to_string(), Color::Green),
+        }
+    }
+}
+
+pub fn match_packages(db: &VulnDb, image: &str, packages: &[Package], sources: Vec<String>) -> VulnReport {
+    let mut findings: Vec<Finding> = packages.iter().flat_map(|p| db.matches(p)).collect();
+    findings.sort_by(|a, b| b.severity.cmp(&a.severity).then_with(|| a.package.cmp(&b.package)).then_with(|| a.id.cmp(&b.id)));
+    VulnReport { image: image.to_string(), sources, packages: packages.len(), findings }
+}
+
+/// 태그의 패키지 목록(SBOM 또는 레이어 스캔)을 DB와 대조합니다.
+pub async fn scan_image(db: &VulnDb, image: &str, reference: &str, platform: &Platform) -> Result<VulnReport, Box<dyn StdError>> {
+    let (packages, sources) = inventory::image_packages(image, reference, platform).await?;
+    Ok(match_packages(db, &format!("{}:{}", image, reference), &packages, sources))
+}
+
+/// TUI Vulns 탭에 표시할 줄 목록
+pub fn report_lines(report: &VulnReport) -> Vec<String> {
+    let mut lines = vec![
+        report.summary(),
+        String::new(),
+        format!("{:<9} {:<20} {:<28} {:<20} {:<20} {}", "SEVERITY", "ID", "PACKAGE", "VERSION", "FIXED IN", "SUMMARY"),
+    ];
+    for finding in &report.findings {
+        lines.push(format!(
+            "{:<9} {:<20} {:<28} {:<20} {:<20} {}",
+            finding.severity.label(),
+            finding.id,
+            finding.package,
+            finding.version,
+            display_fixed(&finding.fixed),
+            finding.summary
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn db(json: &str) -> VulnDb {
+        let mut db = VulnDb { path: PathBuf::new(), advisories: Vec::new(), index: HashMap::new() };
+        db.add_json(json.as_bytes());
+        db
+    }
+
+    fn deb(name: &str, version: &str, purl: &str) -> Package {
+        Package { name: name.to_string(), version: version.to_string(), kind: "deb".to_string(), license: String::new(), purl: purl.to_string() }
+    }
+
+    #[test]
+    fn scores_cvss3_vectors() {
+        assert_eq!(cvss3_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"), Some(9.8));
+        assert_eq!(cvss3_base_score("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N"), Some(6.1));
+        assert_eq!(cvss3_base_score("CVSS:3.1/AV:L/AC:L/PR:L/UI:N/S:U/C:N/I:N/A:N"), Some(0.0));
+        assert_eq!(cvss3_base_score("CVSS:3.1/AV:X"), None);
+        assert_eq!(Severity::from_score(9.8), Severity::Critical);
+        assert_eq!(Severity::from_score(6.1), Severity::Medium);
+    }
+
+    #[test]
+    fn compares_debian_versions() {
+        assert_eq!(dpkg_compare("1.0~rc1", "1.0"), Ordering::Less);
+        assert_eq!(dpkg_compare("1:1.0", "2.0"), Ordering::Greater);
+        assert_eq!(dpkg_compare("2.36-9+deb12u3", "2.36-9+deb12u10"), Ordering::Less);
+        assert_eq!(dpkg_compare("1.2.3-1", "1.2.3-1"), Ordering::Equal);
+        assert_eq!(dpkg_compare("1.2a", "1.2+"), Ordering::Less);
+    }
+
+    #[test]
+    fn compares_generic_versions() {
+        assert_eq!(generic_compare("1.10.0", "1.9.9"), Ordering::Greater);
+        assert_eq!(generic_compare("1.0rc1", "1.0"), Ordering::Less);
+        assert_eq!(generic_compare("v2.0.0", "2.0.0"), Ordering::Equal);
+        assert_eq!(generic_compare("3.18.4-r0", "3.18.4-r1"), Ordering::Less);
+    }
+
+    #[test]
+    fn reads_purl_coordinates() {
+        let coordinates = osv_coordinates("pkg:deb/debian/libc6@2.36-9?arch=amd64&distro=debian-12").unwrap();
+        assert_eq!(
+            coordinates,
+            OsvCoordinates { ecosystem: "Debian".into(), name: "libc6".into(), version: "2.36-9".into(), release: Some("12".into()) }
+        );
+        let coordinates = osv_coordinates("pkg:pypi/Flask_Cors@3.0.9").unwrap();
+        assert_eq!((coordinates.name.as_str(), coordinates.release), ("flask-cors", None));
+        assert_eq!(osv_coordinates("pkg:maven/org.apache%40x/log4j-core@2.14.1").unwrap().name, "org.apache@x:log4j-core");
+        assert_eq!(osv_coordinates("pkg:unknown/x@1"), None);
+    }
+
+    #[test]
+    fn matches_releases() {
+        assert!(release_matches("12", "12"));
+        assert!(release_matches("12", "12.5"));
+        assert!(release_matches("v3.18", "3.18.4"));
+        assert!(!release_matches("v3.1", "3.18.4"));
+        assert!(release_matches("22.04:LTS", "22.04"));
+        assert!(!release_matches("11", "12"));
+    }
+
+    #[test]
+    fn keeps_distribution_releases_apart() {
+        // Debian 11에서는 고쳐졌지만 12에서는 아직 고쳐지지 않은 취약점
+        let db = db(r#"{
+            "id": "DSA-1",
+            "affected": [
+                {"package": {"ecosystem": "Debian:11", "name": "curl"}, "ranges": [{"type": "ECOSYSTEM", "events": [{"introduced": "0"}, {"fixed": "7.74.0-1.3+deb11u7"}]}]},
+                {"package": {"ecosystem": "Debian:12", "name": "curl"}, "ranges": [{"type": "ECOSYSTEM", "events": [{"introduced": "0"}]}]}
+            ]
+        }"#);
+        let bullseye = deb("curl", "7.74.0-1.3+deb11u7", "pkg:deb/debian/curl@7.74.0-1.3+deb11u7?distro=debian-11");
+        assert!(db.matches(&bullseye).is_empty());
+        let bookworm = deb("curl", "7.88.1-10", "pkg:deb/debian/curl@7.88.1-10?distro=debian-12");
+        assert_eq!(db.matches(&bookworm).len(), 1);
+        // 릴리스를 모르면 어느 릴리스든 해당
+        let unknown = deb("curl", "7.74.0-1.3+deb11u7", "pkg:deb/debian/curl@7.74.0-1.3+deb11u7");
+        assert_eq!(db.matches(&unknown).len(), 1);
+    }
+
+    #[test]
+    fn reports_fixed_versions() {
+        let db = db(r#"[{
+            "id": "GHSA-1",
+            "severity": [{"type": "CVSS_V3", "score": "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"}],
+            "affected": [{"package": {"ecosystem": "PyPI", "name": "Flask-Cors"}, "ranges": [{"type": "ECOSYSTEM", "events": [{"introduced": "0"}, {"fixed": "3.0.9"}]}]}]
+        }, {"id": "GHSA-2", "withdrawn": "2024-01-01T00:00:00Z", "affected": []}]"#);
+        assert_eq!(db.advisory_count(), 1);
+        let package = Package { name: "Flask-Cors".into(), version: "3.0.8".into(), kind: "python".into(), license: String::new(), purl: "pkg:pypi/flask-cors@3.0.8".into() };
+        let findings = db.matches(&package);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].severity, Severity::Critical);
+        assert_eq!(findings[0].fixed, ["3.0.9"]);
+    }
+}
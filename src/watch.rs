@@ -0,0 +1,152 @@
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::error::Error as StdError;
+
+use crate::diff::RegistryState;
+use crate::registry::{self, TreeFilter};
+use crate::time;
+use crate::ui;
+
+/// 필터에 맞는 저장소/태그와 각 태그의 다이제스트를 읽습니다. 다이제스트 하나라도 실패하면 이번 폴링은 실패
+/// (빠진 태그를 다음 폴링에서 새 태그로 잘못 알리지 않도록)
+pub async fn poll(filter: &TreeFilter) -> Result<RegistryState, Box<dyn StdError>> {
+    let repositories = registry::fetch_repository_tags(filter).await?;
+    let tags: Vec<(String, String)> = repositories.iter().flat_map(|(repository, tags)| tags.iter().map(move |tag| (repository.clone(), tag.clone()))).collect();
+    let digests: Vec<(String, String, Result<String, String>)> = stream::iter(tags)
+        .map(|(repository, tag)| async move {
+            let digest = registry::fetch_manifest_digest(&repository, &tag).await.map_err(|e| format!("{}:{}: {}", repository, tag, e));
+            (repository, tag, digest)
+        })
+        .buffer_unordered(registry::concurrency())
+        .collect()
+        .await;
+
+    let mut state: RegistryState = repositories.keys().map(|r| (r.clone(), BTreeMap::new())).collect();
+    for (repository, tag, digest) in digests {
+        state.get_mut(&repository).unwrap().insert(tag, digest?);
+    }
+    Ok(state)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    NewRepository,
+    NewTag,
+    /// 있던 태그가 다른 다이제스트를 가리킴
+    RepointedTag,
+    DeletedTag,
+    DeletedRepository,
+}
+
+/// stdout에 JSON 한 줄로 내보내는 변화
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchEvent {
+    pub time: String,
+    pub event: EventKind,
+    pub repository: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_digest: Option<String>,
+}
+
+impl WatchEvent {
+    /// "new" 배지를 붙일 변화인지 (삭제는 트리에서 빠지므로 제외)
+    pub fn is_new(&self) -> bool {
+        matches!(self.event, EventKind::NewRepository | EventKind::NewTag | EventKind::RepointedTag)
+    }
+}
+
+/// 이전 폴링과 비교한 변화. 새 저장소는 저장소 이벤트 뒤에 태그마다 new_tag도 냄
+pub fn changes(old: &RegistryState, new: &RegistryState) -> Vec<WatchEvent> {
+    let now = time::now_rfc3339();
+    let event = |event, repository: &str, tag: Option<&String>, digest: Option<&String>, previous_digest: Option<&String>| WatchEvent {
+        time: now.clone(),
+        event,
+        repository: repository.to_string(),
+        tag: tag.cloned(),
+        digest: digest.cloned(),
+        previous_digest: previous_digest.cloned(),
+    };
+    let empty = BTreeMap::new();
+    let mut events = Vec::new();
+
+    for (repository, tags) in new {
+        let old_tags = match old.get(repository) {
+            Some(old_tags) => old_tags,
+            None => {
+                events.push(event(EventKind::NewRepository, repository, None, None, None));
+                &empty
+            }
+        };
+        for (tag, digest) in tags {
+            match old_tags.get(tag) {
+                None => events.push(event(EventKind::NewTag, repository, Some(tag), Some(digest), None)),
+                Some(previous) if previous != digest => events.push(event(EventKind::RepointedTag, repository, Some(tag), Some(digest), Some(previous))),
+                Some(_) => {}
+            }
+        }
+        for (tag, previous) in old_tags {
+            if !tags.contains_key(tag) {
+                events.push(event(EventKind::DeletedTag, repository, Some(tag), None, Some(previous)));
+            }
+        }
+    }
+    for repository in old.keys().filter(|r| !new.contains_key(*r)) {
+        events.push(event(EventKind::DeletedRepository, repository, None, None, None));
+    }
+    events
+}
+
+/// 이벤트가 가리키는 트리 줄의 이름 (ui::tree_rows의 full_image_names와 같은 형식)
+pub fn tree_name(event: &WatchEvent) -> String {
+    let repository = ui::repository_row_name(&event.repository);
+    match &event.tag {
+        Some(tag) => format!("{}/{}", repository, tag),
+        None => repository,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(entries: &[(&str, &[(&str, &str)])]) -> RegistryState {
+        entries
+            .iter()
+            .map(|(repository, tags)| (repository.to_string(), tags.iter().map(|(t, d)| (t.to_string(), d.to_string())).collect()))
+            .collect()
+    }
+
+    #[test]
+    fn reports_every_kind_of_change() {
+        let old = state(&[("grp/app", &[("1.0", "sha256:a"), ("2.0", "sha256:b"), ("old", "sha256:c")]), ("gone", &[("x", "sha256:d")])]);
+        let new = state(&[("grp/app", &[("1.0", "sha256:a"), ("2.0", "sha256:e"), ("3.0", "sha256:f")]), ("fresh", &[("1", "sha256:g")])]);
+        let events = changes(&old, &new);
+        let events: Vec<(EventKind, &str, Option<&str>)> = events.iter().map(|e| (e.event, e.repository.as_str(), e.tag.as_deref())).collect();
+        assert_eq!(
+            events,
+            [
+                (EventKind::NewRepository, "fresh", None),
+                (EventKind::NewTag, "fresh", Some("1")),
+                (EventKind::RepointedTag, "grp/app", Some("2.0")),
+                (EventKind::NewTag, "grp/app", Some("3.0")),
+                (EventKind::DeletedTag, "grp/app", Some("old")),
+                (EventKind::DeletedRepository, "gone", None),
+            ]
+        );
+        assert!(changes(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn only_additions_and_repoints_are_new() {
+        let old = state(&[("grp/app", &[("1.0", "sha256:a"), ("2.0", "sha256:b")])]);
+        let new = state(&[("grp/app", &[("1.0", "sha256:c")])]);
+        let new_events: Vec<bool> = changes(&old, &new).iter().map(WatchEvent::is_new).collect();
+        assert_eq!(new_events, [true, false]);
+    }
+}
@@ -0,0 +1,151 @@
+use std::io::Read;
+
+use flate2::read::DeflateDecoder;
+
+const LOCAL_HEADER: u32 = 0x0403_4b50;
+const CENTRAL_HEADER: u32 = 0x0201_4b50;
+const END_OF_CENTRAL_DIRECTORY: u32 = 0x0605_4b50;
+
+const METHOD_STORED: u16 = 0;
+const METHOD_DEFLATE: u16 = 8;
+
+/// 중앙 디렉터리에 기록된 항목 하나
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub name: String,
+    method: u16,
+    compressed_size: usize,
+    /// 로컬 헤더 위치
+    offset: usize,
+}
+
+fn u16_at(data: &[u8], pos: usize) -> Option<u16> {
+    data.get(pos..pos + 2).map(|b| u16::from_le_bytes([b[0], b[1]]))
+}
+
+fn u32_at(data: &[u8], pos: usize) -> Option<u32> {
+    data.get(pos..pos + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// 메모리에 올린 zip(jar 포함) 파일의 항목 목록. zip64는 지원하지 않음
+pub fn entries(data: &[u8]) -> Option<Vec<Entry>> {
+    // End of central directory는 뒤에서부터 찾음 (주석 최대 64 KiB)
+    let search_start = data.len().saturating_sub(22 + 65535);
+    let eocd = (search_start..data.len().saturating_sub(21)).rev().find(|&pos| u32_at(data, pos) == Some(END_OF_CENTRAL_DIRECTORY))?;
+    let count = u16_at(data, eocd + 10)? as usize;
+    let mut pos = u32_at(data, eocd + 16)? as usize;
+
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if u32_at(data, pos)? != CENTRAL_HEADER {
+            return None;
+        }
+        let name_len = u16_at(data, pos + 28)? as usize;
+        let extra_len = u16_at(data, pos + 30)? as usize;
+        let comment_len = u16_at(data, pos + 32)? as usize;
+        entries.push(Entry {
+            name: String::from_utf8_lossy(data.get(pos + 46..pos + 46 + name_len)?).into_owned(),
+            method: u16_at(data, pos + 10)?,
+            compressed_size: u32_at(data, pos + 20)? as usize,
+            offset: u32_at(data, pos + 42)? as usize,
+        });
+        pos += 46 + name_len + extra_len + comment_len;
+    }
+    Some(entries)
+}
+
+/// 항목 내용을 최대 `limit` 바이트까지 풀어 읽습니다. stored/deflate만 지원
+pub fn read(data: &[u8], entry: &Entry, limit: u64) -> Option<Vec<u8>> {
+    if u32_at(data, entry.offset)? != LOCAL_HEADER {
+        return None;
+    }
+    let start = entry.offset + 30 + u16_at(data, entry.offset + 26)? as usize + u16_at(data, entry.offset + 28)? as usize;
+    let raw = data.get(start..start + entry.compressed_size)?;
+    match entry.method {
+        METHOD_STORED => Some(raw.iter().take(limit as usize).copied().collect()),
+        METHOD_DEFLATE => {
+            let mut out = Vec::new();
+            DeflateDecoder::new(raw).take(limit).read_to_end(&mut out).ok()?;
+            Some(out)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    // (이름, 압축 방식, 원본) 항목으로 zip을 만듦
+    fn build(files: &[(&str, u16, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut central = Vec::new();
+        for (name, method, content) in files {
+            let stored = match *method {
+                METHOD_DEFLATE => {
+                    let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                    encoder.write_all(content).unwrap();
+                    encoder.finish().unwrap()
+                }
+                _ => content.to_vec(),
+            };
+            let offset = data.len() as u32;
+            data.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+            data.extend_from_slice(&[0u8; 4]);
+            data.extend_from_slice(&method.to_le_bytes());
+            data.extend_from_slice(&[0u8; 8]);
+            data.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(content.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            data.extend_from_slice(&0u16.to_le_bytes());
+            data.extend_from_slice(name.as_bytes());
+            data.extend_from_slice(&stored);
+
+            central.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+            central.extend_from_slice(&[0u8; 6]);
+            central.extend_from_slice(&method.to_le_bytes());
+            central.extend_from_slice(&[0u8; 8]);
+            central.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+            central.extend_from_slice(&(content.len() as u32).to_le_bytes());
+            central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            central.extend_from_slice(&[0u8; 12]);
+            central.extend_from_slice(&offset.to_le_bytes());
+            central.extend_from_slice(name.as_bytes());
+        }
+        let central_offset = data.len() as u32;
+        data.extend_from_slice(&central);
+        data.extend_from_slice(&END_OF_CENTRAL_DIRECTORY.to_le_bytes());
+        data.extend_from_slice(&[0u8; 4]);
+        data.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(files.len() as u16).to_le_bytes());
+        data.extend_from_slice(&(central.len() as u32).to_le_bytes());
+        data.extend_from_slice(&central_offset.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn reads_stored_and_deflated_entries() {
+        let json = br#"{"id":"GHSA-xxxx","affected":[]}"#.repeat(20);
+        let data = build(&[("a.json", METHOD_STORED, b"stored"), ("dir/b.json", METHOD_DEFLATE, &json)]);
+        let entries = entries(&data).unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["a.json", "dir/b.json"]);
+        assert_eq!(read(&data, &entries[0], 1024).unwrap(), b"stored");
+        assert_eq!(read(&data, &entries[1], u64::MAX).unwrap(), json);
+        assert_eq!(read(&data, &entries[1], 10).unwrap(), &json[..10]);
+    }
+
+    #[test]
+    fn rejects_unsupported_or_broken_archives() {
+        assert!(entries(b"not a zip").is_none());
+        let data = build(&[("a", 12, b"bzip2")]);
+        let entries_ = entries(&data).unwrap();
+        assert!(read(&data, &entries_[0], 1024).is_none());
+        let mut truncated = build(&[("a", METHOD_STORED, b"x")]);
+        let len = truncated.len();
+        truncated[len - 6] = 0xff;
+        assert!(entries(&truncated).is_none());
+    }
+}